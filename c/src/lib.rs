@@ -14,8 +14,9 @@ use font_kit::handle::Handle;
 use foreign_types::ForeignTypeRef;
 use gl;
 use pathfinder_canvas::{CanvasFontContext, CanvasRenderingContext2D, FillStyle, LineJoin, Path2D};
-use pathfinder_canvas::{TextAlign, TextMetrics};
+use pathfinder_canvas::{TextAlign, TextBaseline, TextDirection, TextMetrics};
 use pathfinder_content::color::{ColorF, ColorU};
+use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::ArcDirection;
 use pathfinder_content::stroke::LineCap;
 use pathfinder_geometry::rect::{RectF, RectI};
@@ -56,12 +57,27 @@ pub const PF_LINE_JOIN_ROUND:   u8 = 2;
 pub const PF_TEXT_ALIGN_LEFT:   u8 = 0;
 pub const PF_TEXT_ALIGN_CENTER: u8 = 1;
 pub const PF_TEXT_ALIGN_RIGHT:  u8 = 2;
+pub const PF_TEXT_ALIGN_START:  u8 = 3;
+pub const PF_TEXT_ALIGN_END:    u8 = 4;
+
+pub const PF_TEXT_BASELINE_ALPHABETIC:  u8 = 0;
+pub const PF_TEXT_BASELINE_TOP:         u8 = 1;
+pub const PF_TEXT_BASELINE_HANGING:     u8 = 2;
+pub const PF_TEXT_BASELINE_MIDDLE:      u8 = 3;
+pub const PF_TEXT_BASELINE_IDEOGRAPHIC: u8 = 4;
+pub const PF_TEXT_BASELINE_BOTTOM:      u8 = 5;
+
+pub const PF_TEXT_DIRECTION_LTR: u8 = 0;
+pub const PF_TEXT_DIRECTION_RTL: u8 = 1;
 
 // `content`
 
 pub const PF_ARC_DIRECTION_CW:  u8 = 0;
 pub const PF_ARC_DIRECTION_CCW: u8 = 1;
 
+pub const PF_FILL_RULE_WINDING:  u8 = 0;
+pub const PF_FILL_RULE_EVEN_ODD: u8 = 1;
+
 // `gl`
 
 pub const PF_GL_VERSION_GL3:    u8 = 0;
@@ -84,10 +100,24 @@ pub type PFFillStyleRef = *mut FillStyle;
 pub type PFLineCap = u8;
 pub type PFLineJoin = u8;
 pub type PFArcDirection = u8;
+pub type PFFillRule = u8;
 pub type PFTextAlign = u8;
+pub type PFTextBaseline = u8;
+pub type PFTextDirection = u8;
 #[repr(C)]
 pub struct PFTextMetrics {
     pub width: f32,
+    pub actual_bounding_box_left: f32,
+    pub actual_bounding_box_right: f32,
+    pub font_bounding_box_ascent: f32,
+    pub font_bounding_box_descent: f32,
+    pub actual_bounding_box_ascent: f32,
+    pub actual_bounding_box_descent: f32,
+    pub em_height_ascent: f32,
+    pub em_height_descent: f32,
+    pub hanging_baseline: f32,
+    pub alphabetic_baseline: f32,
+    pub ideographic_baseline: f32,
 }
 
 // `content`
@@ -342,6 +372,13 @@ pub unsafe extern "C" fn PFCanvasSetFontByPostScriptName(canvas: PFCanvasRef,
     (*canvas).set_font_by_postscript_name(to_rust_string(&postscript_name, postscript_name_len))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetFont(canvas: PFCanvasRef,
+                                         font: *const c_char,
+                                         font_len: usize) {
+    (*canvas).set_font(to_rust_string(&font, font_len))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFCanvasSetFontSize(canvas: PFCanvasRef, new_font_size: f32) {
     (*canvas).set_font_size(new_font_size)
@@ -352,26 +389,65 @@ pub unsafe extern "C" fn PFCanvasSetTextAlign(canvas: PFCanvasRef, new_text_alig
     (*canvas).set_text_align(match new_text_align {
         PF_TEXT_ALIGN_CENTER => TextAlign::Center,
         PF_TEXT_ALIGN_RIGHT  => TextAlign::Right,
+        PF_TEXT_ALIGN_START  => TextAlign::Start,
+        PF_TEXT_ALIGN_END    => TextAlign::End,
         _                    => TextAlign::Left,
     });
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetTextBaseline(canvas: PFCanvasRef,
+                                                 new_text_baseline: PFTextBaseline) {
+    (*canvas).set_text_baseline(match new_text_baseline {
+        PF_TEXT_BASELINE_TOP         => TextBaseline::Top,
+        PF_TEXT_BASELINE_HANGING     => TextBaseline::Hanging,
+        PF_TEXT_BASELINE_MIDDLE      => TextBaseline::Middle,
+        PF_TEXT_BASELINE_IDEOGRAPHIC => TextBaseline::Ideographic,
+        PF_TEXT_BASELINE_BOTTOM      => TextBaseline::Bottom,
+        _                            => TextBaseline::Alphabetic,
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetDirection(canvas: PFCanvasRef,
+                                              new_direction: PFTextDirection) {
+    (*canvas).set_direction(match new_direction {
+        PF_TEXT_DIRECTION_RTL => TextDirection::Rtl,
+        _                     => TextDirection::Ltr,
+    });
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFCanvasSetFillStyle(canvas: PFCanvasRef, fill_style: PFFillStyleRef) {
-    (*canvas).set_fill_style(*fill_style)
+    (*canvas).set_fill_style((*fill_style).clone())
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn PFCanvasSetStrokeStyle(canvas: PFCanvasRef,
                                                 stroke_style: PFFillStyleRef) {
-    (*canvas).set_stroke_style(*stroke_style)
+    (*canvas).set_stroke_style((*stroke_style).clone())
 }
 
 /// This function automatically destroys the path. If you wish to use the path again, clone it
 /// first.
 #[no_mangle]
 pub unsafe extern "C" fn PFCanvasFillPath(canvas: PFCanvasRef, path: PFPathRef) {
-    (*canvas).fill_path(*Box::from_raw(path))
+    (*canvas).fill_path(*Box::from_raw(path), FillRule::Winding)
+}
+
+/// Like `PFCanvasFillPath()`, but with the given fill rule instead of the nonzero winding rule.
+///
+/// This function automatically destroys the path. If you wish to use the path again, clone it
+/// first.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasFillPathWithRule(canvas: PFCanvasRef,
+                                                  path: PFPathRef,
+                                                  fill_rule: PFFillRule) {
+    let fill_rule = match fill_rule {
+        PF_FILL_RULE_EVEN_ODD => FillRule::EvenOdd,
+        _                     => FillRule::Winding,
+    };
+    (*canvas).fill_path(*Box::from_raw(path), fill_rule)
 }
 
 /// This function automatically destroys the path. If you wish to use the path again, clone it
@@ -706,7 +782,20 @@ trait TextMetricsExt {
 
 impl TextMetricsExt for TextMetrics {
     fn to_c(&self) -> PFTextMetrics {
-        PFTextMetrics { width: self.width }
+        PFTextMetrics {
+            width: self.width,
+            actual_bounding_box_left: self.actual_bounding_box_left,
+            actual_bounding_box_right: self.actual_bounding_box_right,
+            font_bounding_box_ascent: self.font_bounding_box_ascent,
+            font_bounding_box_descent: self.font_bounding_box_descent,
+            actual_bounding_box_ascent: self.actual_bounding_box_ascent,
+            actual_bounding_box_descent: self.actual_bounding_box_descent,
+            em_height_ascent: self.em_height_ascent,
+            em_height_descent: self.em_height_descent,
+            hanging_baseline: self.hanging_baseline,
+            alphabetic_baseline: self.alphabetic_baseline,
+            ideographic_baseline: self.ideographic_baseline,
+        }
     }
 }
 
@@ -0,0 +1,244 @@
+// pathfinder/canvas/src/css.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parsing of the CSS `font` shorthand.
+
+// The reference size for the absolute size keywords, as in CSS.
+const MEDIUM_FONT_SIZE: f32 = 16.0;
+
+const CSS_PIXELS_PER_INCH: f32 = 96.0;
+
+/// The parts of a CSS `font` shorthand value that affect font selection and layout.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct FontShorthand {
+    pub(crate) style: FontStyle,
+    /// A CSS font weight, between 1 and 1000.
+    pub(crate) weight: u16,
+    /// A CSS font stretch, as a percentage of the normal width.
+    pub(crate) stretch: u16,
+    /// The font size in pixels.
+    pub(crate) size: f32,
+    pub(crate) families: Vec<FontFamilyName>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum FontFamilyName {
+    Named(String),
+    Serif,
+    SansSerif,
+    Monospace,
+    Cursive,
+    Fantasy,
+}
+
+/// Parses a value like `italic bold 14px/1.5 'Open Sans', sans-serif`. Relative sizes are
+/// resolved against `reference_size`. Returns `None` if the value is invalid.
+///
+/// Font variants and line heights are accepted but ignored, since they don't affect canvas text.
+pub(crate) fn parse_font_shorthand(input: &str, reference_size: f32) -> Option<FontShorthand> {
+    let mut shorthand = FontShorthand {
+        style: FontStyle::Normal,
+        weight: 400,
+        stretch: 100,
+        size: reference_size,
+        families: vec![],
+    };
+
+    // Style, variant, weight, and stretch may appear in any order before the size.
+    let mut rest = input.trim_start();
+    let mut prefix_count = 0;
+    loop {
+        let (token, after_token) = split_token(rest);
+        if token.is_empty() {
+            return None;
+        }
+
+        let (size_token, line_height) = match token.find('/') {
+            Some(index) => (&token[..index], Some(&token[(index + 1)..])),
+            None => (token, None),
+        };
+        if let Some(size) = parse_font_size(size_token, reference_size) {
+            shorthand.size = size;
+            rest = match line_height {
+                Some("") => split_token(after_token).1,
+                Some(_) => after_token,
+                None => {
+                    match after_token.trim_start().strip_prefix('/') {
+                        Some(line_height) => split_token(line_height).1,
+                        None => after_token,
+                    }
+                }
+            };
+            break;
+        }
+
+        prefix_count += 1;
+        if prefix_count > 4 {
+            return None;
+        }
+        match &*token.to_ascii_lowercase() {
+            "normal" | "small-caps" => {}
+            "italic" => shorthand.style = FontStyle::Italic,
+            "oblique" => shorthand.style = FontStyle::Oblique,
+            "bold" => shorthand.weight = 700,
+            "ultra-condensed" => shorthand.stretch = 50,
+            "extra-condensed" => shorthand.stretch = 62,
+            "condensed" => shorthand.stretch = 75,
+            "semi-condensed" => shorthand.stretch = 87,
+            "semi-expanded" => shorthand.stretch = 112,
+            "expanded" => shorthand.stretch = 125,
+            "extra-expanded" => shorthand.stretch = 150,
+            "ultra-expanded" => shorthand.stretch = 200,
+            keyword => {
+                match keyword.parse::<u16>() {
+                    Ok(weight) if (1..=1000).contains(&weight) => shorthand.weight = weight,
+                    _ => return None,
+                }
+            }
+        }
+        rest = after_token;
+    }
+
+    shorthand.families = parse_font_families(rest)?;
+    Some(shorthand)
+}
+
+// Returns the first whitespace-delimited token of `input` and the rest of the string.
+fn split_token(input: &str) -> (&str, &str) {
+    let input = input.trim_start();
+    let end = input.find(char::is_whitespace).unwrap_or(input.len());
+    (&input[..end], &input[end..])
+}
+
+fn parse_font_size(token: &str, reference_size: f32) -> Option<f32> {
+    let keyword_scale = match &*token.to_ascii_lowercase() {
+        "xx-small" => Some(3.0 / 5.0),
+        "x-small" => Some(3.0 / 4.0),
+        "small" => Some(8.0 / 9.0),
+        "medium" => Some(1.0),
+        "large" => Some(6.0 / 5.0),
+        "x-large" => Some(3.0 / 2.0),
+        "xx-large" => Some(2.0),
+        "xxx-large" => Some(3.0),
+        "larger" => return Some(reference_size * 1.2),
+        "smaller" => return Some(reference_size / 1.2),
+        _ => None,
+    };
+    if let Some(keyword_scale) = keyword_scale {
+        return Some(MEDIUM_FONT_SIZE * keyword_scale);
+    }
+
+    let units: [(&str, f32); 9] = [
+        ("px", 1.0),
+        ("pt", CSS_PIXELS_PER_INCH / 72.0),
+        ("pc", CSS_PIXELS_PER_INCH / 6.0),
+        ("in", CSS_PIXELS_PER_INCH),
+        ("cm", CSS_PIXELS_PER_INCH / 2.54),
+        ("mm", CSS_PIXELS_PER_INCH / 25.4),
+        ("rem", reference_size),
+        ("em", reference_size),
+        ("%", reference_size / 100.0),
+    ];
+    let token = token.to_ascii_lowercase();
+    for &(unit, scale) in &units {
+        if !token.ends_with(unit) {
+            continue;
+        }
+        return match token[..(token.len() - unit.len())].parse::<f32>() {
+            Ok(value) if value.is_finite() && value >= 0.0 => Some(value * scale),
+            _ => None,
+        };
+    }
+    None
+}
+
+fn parse_font_families(input: &str) -> Option<Vec<FontFamilyName>> {
+    let mut families = vec![];
+    for family in split_font_families(input)? {
+        let family = family.trim();
+        let quote = family.chars().next()?;
+
+        if quote == '"' || quote == '\'' {
+            if family.len() < 2 || !family.ends_with(quote) {
+                return None;
+            }
+            families.push(FontFamilyName::Named(family[1..(family.len() - 1)].to_owned()));
+            continue;
+        }
+
+        let family = family.split_whitespace().collect::<Vec<_>>().join(" ");
+        families.push(match &*family.to_ascii_lowercase() {
+            "serif" => FontFamilyName::Serif,
+            "sans-serif" | "system-ui" => FontFamilyName::SansSerif,
+            "monospace" => FontFamilyName::Monospace,
+            "cursive" => FontFamilyName::Cursive,
+            "fantasy" => FontFamilyName::Fantasy,
+            _ => FontFamilyName::Named(family),
+        });
+    }
+    Some(families)
+}
+
+// Splits a family list at the commas that aren't inside quotes.
+fn split_font_families(input: &str) -> Option<Vec<&str>> {
+    let mut families = vec![];
+    let (mut start, mut quote) = (0, None);
+    for (index, ch) in input.char_indices() {
+        match (ch, quote) {
+            ('"', None) | ('\'', None) => quote = Some(ch),
+            (_, Some(open_quote)) if ch == open_quote => quote = None,
+            (',', None) => {
+                families.push(&input[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if quote.is_some() {
+        return None;
+    }
+    families.push(&input[start..]);
+    Some(families)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FontFamilyName, FontShorthand, FontStyle, parse_font_shorthand};
+
+    #[test]
+    fn test_parse_font_shorthand() {
+        assert_eq!(parse_font_shorthand("italic bold 14px/1.5 'Open Sans', sans-serif", 10.0),
+                   Some(FontShorthand {
+                       style: FontStyle::Italic,
+                       weight: 700,
+                       stretch: 100,
+                       size: 14.0,
+                       families: vec![
+                           FontFamilyName::Named("Open Sans".to_owned()),
+                           FontFamilyName::SansSerif,
+                       ],
+                   }));
+
+        let shorthand = parse_font_shorthand("condensed 300 2em Helvetica  Neue", 12.0).unwrap();
+        assert_eq!((shorthand.weight, shorthand.stretch, shorthand.size), (300, 75, 24.0));
+        assert_eq!(shorthand.families, vec![FontFamilyName::Named("Helvetica Neue".to_owned())]);
+
+        assert_eq!(parse_font_shorthand("bold sans-serif", 10.0), None);
+        assert_eq!(parse_font_shorthand("12px 'Open Sans", 10.0), None);
+        assert_eq!(parse_font_shorthand("12px", 10.0), None);
+    }
+}
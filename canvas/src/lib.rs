@@ -12,15 +12,19 @@
 
 use pathfinder_content::color::ColorU;
 use pathfinder_content::dash::OutlineDash;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::Gradient;
 use pathfinder_content::outline::{ArcDirection, Contour, Outline};
+use pathfinder_content::pattern::{Image, Pattern, RepeatMode};
 use pathfinder_content::stroke::{LineCap, LineJoin as StrokeLineJoin};
 use pathfinder_content::stroke::{OutlineStrokeToFill, StrokeStyle};
 use pathfinder_geometry::line_segment::LineSegment2F;
-use pathfinder_geometry::vector::Vector2F;
-use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_renderer::paint::{Paint, PaintId};
-use pathfinder_renderer::scene::{PathObject, Scene};
+use pathfinder_renderer::scene::{PathObject, Scene, SceneGroup};
 use std::default::Default;
 use std::f32::consts::PI;
 use std::mem;
@@ -33,6 +37,8 @@ pub use text::CanvasFontContext;
 const HAIRLINE_STROKE_WIDTH: f32 = 0.0333;
 const DEFAULT_FONT_SIZE: f32 = 10.0;
 
+#[cfg(feature = "pf-text")]
+mod css;
 #[cfg_attr(not(feature = "pf-text"), path = "text_no_text.rs")]
 mod text;
 
@@ -76,7 +82,7 @@ impl CanvasRenderingContext2D {
     pub fn fill_rect(&mut self, rect: RectF) {
         let mut path = Path2D::new();
         path.rect(rect);
-        self.fill_path(path);
+        self.fill_path(path, FillRule::Winding);
     }
 
     #[inline]
@@ -137,11 +143,40 @@ impl CanvasRenderingContext2D {
         self.current_state.stroke_paint = new_stroke_style.to_paint();
     }
 
+    /// Creates a gradient along the line from `from` to `to`. Add color stops to the result with
+    /// `Gradient::add_color_stop()`, then pass it to `set_fill_style()` or `set_stroke_style()`.
+    ///
+    /// As in HTML canvas, the gradient is positioned using the transform that is current when a
+    /// path is drawn with it.
+    #[inline]
+    pub fn create_linear_gradient(&self, from: Vector2F, to: Vector2F) -> Gradient {
+        Gradient::linear(LineSegment2F::new(from, to))
+    }
+
+    /// Creates a gradient between the circle centered at `from` with radius `start_radius` and the
+    /// circle centered at `to` with radius `end_radius`.
+    #[inline]
+    pub fn create_radial_gradient(&self,
+                                  from: Vector2F,
+                                  start_radius: f32,
+                                  to: Vector2F,
+                                  end_radius: f32)
+                                  -> Gradient {
+        Gradient::radial(LineSegment2F::new(from, to), start_radius, end_radius)
+    }
+
+    /// Creates a pattern that tiles `image` according to `repeat`, with one image pixel per unit.
+    /// Use `Pattern::set_transform()` to position or scale it.
+    #[inline]
+    pub fn create_pattern(&self, image: Image, repeat: RepeatMode) -> Pattern {
+        Pattern::new(image, repeat)
+    }
+
     // Shadows
 
     #[inline]
     pub fn set_shadow_color(&mut self, new_shadow_color: ColorU) {
-        self.current_state.shadow_paint = Paint { color: new_shadow_color };
+        self.current_state.shadow_paint = Paint::Color(new_shadow_color);
     }
 
     #[inline]
@@ -149,24 +184,44 @@ impl CanvasRenderingContext2D {
         self.current_state.shadow_offset = new_shadow_offset;
     }
 
+    #[inline]
+    pub fn shadow_blur(&self) -> f32 {
+        self.current_state.shadow_blur
+    }
+
+    /// Sets the blur level of shadows, as in the HTML canvas `shadowBlur` property. Negative and
+    /// non-finite values are ignored.
+    #[inline]
+    pub fn set_shadow_blur(&mut self, new_shadow_blur: f32) {
+        if new_shadow_blur.is_finite() && new_shadow_blur >= 0.0 {
+            self.current_state.shadow_blur = new_shadow_blur;
+        }
+    }
+
     // Drawing paths
 
     #[inline]
-    pub fn fill_path(&mut self, path: Path2D) {
+    pub fn fill_path(&mut self, path: Path2D, fill_rule: FillRule) {
         let mut outline = path.into_outline();
         outline.transform(&self.current_state.transform);
 
-        let paint = self.current_state.resolve_paint(self.current_state.fill_paint);
+        let paint = self.current_state.resolve_paint(&self.current_state.fill_paint);
         let paint_id = self.scene.push_paint(&paint);
 
-        self.push_path(outline, paint_id);
+        self.push_path(outline, paint_id, fill_rule);
     }
 
     #[inline]
     pub fn stroke_path(&mut self, path: Path2D) {
-        let paint = self.current_state.resolve_paint(self.current_state.stroke_paint);
+        let paint = self.current_state.resolve_paint(&self.current_state.stroke_paint);
         let paint_id = self.scene.push_paint(&paint);
 
+        let outline = self.stroke_outline(path);
+        self.push_path(outline, paint_id, FillRule::Winding);
+    }
+
+    // Returns the outline, in scene space, that stroking `path` with the current line style fills.
+    fn stroke_outline(&self, path: Path2D) -> Outline {
         let mut stroke_style = self.current_state.resolve_stroke_style();
         
         // the smaller scale is relevant here, as we multiply by it and want to ensure it is always bigger than HAIRLINE_STROKE_WIDTH
@@ -190,20 +245,187 @@ impl CanvasRenderingContext2D {
         outline = stroke_to_fill.into_outline();
 
         outline.transform(&self.current_state.transform);
-        self.push_path(outline, paint_id);
+        outline
     }
 
-    fn push_path(&mut self, outline: Outline, paint_id: PaintId) {
+    /// Intersects the current clip region with the interior of `path`, as determined by
+    /// `fill_rule`. Like the rest of the drawing state, the clip region is saved and restored by
+    /// `save()` and `restore()`.
+    pub fn clip_path(&mut self, path: Path2D, fill_rule: FillRule) {
+        let mut outline = path.into_outline();
+        outline.transform(&self.current_state.transform);
+
+        if let Some((ref clip_path, clip_fill_rule)) = self.current_state.clip_path {
+            outline.clip_against_outline(fill_rule, clip_path, clip_fill_rule);
+        }
+        self.current_state.clip_path = Some((outline, fill_rule));
+    }
+
+    // Hit testing
+
+    /// Returns true if `point`, in canvas coordinates, is inside `path` as it would be filled with
+    /// the current transform and `fill_rule`.
+    pub fn is_point_in_path(&self, path: &Path2D, point: Vector2F, fill_rule: FillRule) -> bool {
+        let mut outline = path.clone().into_outline();
+        outline.transform(&self.current_state.transform);
+        outline.contains_point(point, fill_rule)
+    }
+
+    /// Returns true if `point`, in canvas coordinates, is inside the area that stroking `path`
+    /// with the current transform and line style would cover.
+    pub fn is_point_in_stroke(&self, path: &Path2D, point: Vector2F) -> bool {
+        self.stroke_outline(path.clone()).contains_point(point, FillRule::Winding)
+    }
+
+    fn push_path(&mut self, outline: Outline, paint_id: PaintId, fill_rule: FillRule) {
         if !self.current_state.shadow_paint.is_fully_transparent() {
-            let paint = self.current_state.resolve_paint(self.current_state.shadow_paint);
-            let paint_id = self.scene.push_paint(&paint);
+            let shadow_paint = self.current_state.shadow_paint.clone();
+            self.push_shadow(outline.clone(), &shadow_paint, fill_rule);
+        }
+
+        let blend_mode = self.current_state.global_composite_operation.to_blend_mode();
+        self.push_clipped_path(outline, paint_id, fill_rule, blend_mode);
+    }
+
+    // Draws the shadow of `outline` with `paint`, both of which are offset by the shadow offset.
+    fn push_shadow(&mut self, mut outline: Outline, paint: &Paint, fill_rule: FillRule) {
+        let offset = Transform2F::from_translation(self.current_state.shadow_offset);
+        outline.transform(&offset);
+        let mut paint = self.current_state.resolve_paint(paint);
+        paint.apply_transform(&offset);
+        let paint_id = self.scene.push_paint(&paint);
 
-            let mut outline = outline.clone();
-            outline.transform(&Transform2F::from_translation(self.current_state.shadow_offset));
-            self.scene.push_path(PathObject::new(outline, paint_id, String::new()))
+        if self.current_state.shadow_blur > 0.0 {
+            self.push_blurred_shadow(outline, paint_id, fill_rule);
+        } else {
+            let blend_mode = self.current_state.global_composite_operation.to_blend_mode();
+            self.push_clipped_path(outline, paint_id, fill_rule, blend_mode);
         }
+    }
+
+    // Draws the shadow of `outline` in a group that the renderer blurs. As for any other drawing,
+    // the result is clipped and composited with the global composite operation, both after the
+    // blur, so that the shadow doesn't spread outside the clip.
+    fn push_blurred_shadow(&mut self, outline: Outline, paint_id: PaintId, fill_rule: FillRule) {
+        let blend_mode = self.current_state.global_composite_operation.to_blend_mode();
+
+        // As in HTML canvas, the standard deviation of the Gaussian is half the blur level.
+        let mut blur_group = SceneGroup::new();
+        blur_group.set_blur(self.current_state.shadow_blur * 0.5);
+
+        let clip_path = self.current_state.clip_path.clone();
+        let (clip_path, clip_fill_rule) = match clip_path {
+            None => {
+                blur_group.set_blend_mode(blend_mode);
+                self.push_blurred_path(blur_group, outline, paint_id, fill_rule);
+                return;
+            }
+            Some(clip_path) => clip_path,
+        };
+
+        // Draw the clip region into a layer of its own, and then keep the part of the blurred
+        // shadow that falls within it.
+        let mut clip_group = SceneGroup::new();
+        clip_group.set_blend_mode(blend_mode);
+        clip_group.set_isolated(true);
+        self.scene.push_group(clip_group);
+        let mask_paint_id = self.scene.push_paint(&Paint::black());
+        let mut mask = PathObject::new(clip_path, mask_paint_id, String::new());
+        mask.set_fill_rule(clip_fill_rule);
+        self.scene.push_path(mask);
+        blur_group.set_blend_mode(BlendMode::SrcIn);
+        self.push_blurred_path(blur_group, outline, paint_id, fill_rule);
+        self.scene.pop_group();
+    }
+
+    fn push_blurred_path(&mut self,
+                         blur_group: SceneGroup,
+                         outline: Outline,
+                         paint_id: PaintId,
+                         fill_rule: FillRule) {
+        self.scene.push_group(blur_group);
+        let mut path = PathObject::new(outline, paint_id, String::new());
+        path.set_fill_rule(fill_rule);
+        self.scene.push_path(path);
+        self.scene.pop_group();
+    }
+
+    fn push_clipped_path(&mut self,
+                         mut outline: Outline,
+                         paint_id: PaintId,
+                         fill_rule: FillRule,
+                         blend_mode: BlendMode) {
+        if let Some((ref clip_path, clip_fill_rule)) = self.current_state.clip_path {
+            outline.clip_against_outline(fill_rule, clip_path, clip_fill_rule);
+        }
+
+        let mut path = PathObject::new(outline, paint_id, String::new());
+        path.set_fill_rule(fill_rule);
+        path.set_blend_mode(blend_mode);
+        self.scene.push_path(path);
+    }
+
+    // Drawing images
 
-        self.scene.push_path(PathObject::new(outline, paint_id, String::new()))
+    /// Draws `image` at its natural size with its upper left corner at `dest_origin`.
+    #[inline]
+    pub fn draw_image(&mut self, image: &Image, dest_origin: Vector2F) {
+        let dest_rect = RectF::new(dest_origin, image.size().to_f32());
+        self.draw_image_scaled(image, dest_rect);
+    }
+
+    /// Draws `image` stretched to fill `dest_rect`.
+    #[inline]
+    pub fn draw_image_scaled(&mut self, image: &Image, dest_rect: RectF) {
+        let src_rect = RectF::new(Vector2F::default(), image.size().to_f32());
+        self.draw_subimage(image, src_rect, dest_rect);
+    }
+
+    /// Draws the portion of `image` within `src_rect`, in image pixels, stretched to fill
+    /// `dest_rect`.
+    ///
+    /// As in HTML canvas, rectangles with negative sizes are normalized, and the parts of
+    /// `src_rect` outside the image are cut away along with the matching parts of `dest_rect`.
+    /// Pixels outside `src_rect` never bleed into the edges of the result.
+    pub fn draw_subimage(&mut self, image: &Image, src_rect: RectF, dest_rect: RectF) {
+        let (src_rect, dest_rect) = (normalize_rect(src_rect), normalize_rect(dest_rect));
+        let image_rect = RectF::new(Vector2F::default(), image.size().to_f32());
+        let clipped_src_rect = match src_rect.intersection(image_rect) {
+            Some(rect) if rect.size().x() > 0.0 && rect.size().y() > 0.0 => rect,
+            _ => return,
+        };
+
+        let scale = dest_rect.size().scale_xy(Vector2F::new(1.0 / src_rect.size().x(),
+                                                            1.0 / src_rect.size().y()));
+        let dest_rect = RectF::new(
+            dest_rect.origin() + (clipped_src_rect.origin() - src_rect.origin()).scale_xy(scale),
+            clipped_src_rect.size().scale_xy(scale));
+
+        // Crop the image so that filtering clamps to the edges of the source rectangle.
+        let crop_rect = clipped_src_rect.round_out().to_i32();
+        let mut pattern = Pattern::new(crop_image(image, crop_rect), RepeatMode::NoRepeat);
+        pattern.set_transform(&(Transform2F::from_translation(dest_rect.origin()) *
+                                Transform2F::from_scale(scale) *
+                                Transform2F::from_translation(crop_rect.origin().to_f32() -
+                                                              clipped_src_rect.origin())));
+
+        let mut path = Path2D::new();
+        path.rect(dest_rect);
+        let mut outline = path.into_outline();
+        outline.transform(&self.current_state.transform);
+
+        // The shadow takes its shape from the alpha of the image, not from the rectangle.
+        if let Paint::Color(shadow_color) = self.current_state.shadow_paint {
+            if shadow_color.a > 0 {
+                let shadow_paint = Paint::Pattern(tint_pattern(&pattern, shadow_color));
+                self.push_shadow(outline.clone(), &shadow_paint, FillRule::Winding);
+            }
+        }
+
+        let paint = self.current_state.resolve_paint(&Paint::Pattern(pattern));
+        let paint_id = self.scene.push_paint(&paint);
+        let blend_mode = self.current_state.global_composite_operation.to_blend_mode();
+        self.push_clipped_path(outline, paint_id, FillRule::Winding, blend_mode);
     }
 
     // Transformations
@@ -235,6 +457,17 @@ impl CanvasRenderingContext2D {
         self.current_state.global_alpha = new_global_alpha;
     }
 
+    #[inline]
+    pub fn global_composite_operation(&self) -> CompositeOperation {
+        self.current_state.global_composite_operation
+    }
+
+    /// Sets how subsequent drawing is combined with what is already on the canvas.
+    #[inline]
+    pub fn set_global_composite_operation(&mut self, new_composite_operation: CompositeOperation) {
+        self.current_state.global_composite_operation = new_composite_operation;
+    }
+
     // The canvas state
 
     #[inline]
@@ -265,8 +498,15 @@ struct State {
     stroke_paint: Paint,
     shadow_paint: Paint,
     shadow_offset: Vector2F,
+    shadow_blur: f32,
     text_align: TextAlign,
+    text_baseline: TextBaseline,
+    text_direction: TextDirection,
     global_alpha: f32,
+    global_composite_operation: CompositeOperation,
+    /// The clip region in scene space and the fill rule that defines its interior, or `None` if
+    /// drawing is unclipped.
+    clip_path: Option<(Outline, FillRule)>,
 }
 
 impl State {
@@ -281,17 +521,24 @@ impl State {
             miter_limit: 10.0,
             line_dash: vec![],
             line_dash_offset: 0.0,
-            fill_paint: Paint { color: ColorU::black() },
-            stroke_paint: Paint { color: ColorU::black() },
-            shadow_paint: Paint { color: ColorU::transparent_black() },
+            fill_paint: Paint::black(),
+            stroke_paint: Paint::black(),
+            shadow_paint: Paint::transparent_black(),
             shadow_offset: Vector2F::default(),
-            text_align: TextAlign::Left,
+            shadow_blur: 0.0,
+            text_align: TextAlign::Start,
+            text_baseline: TextBaseline::Alphabetic,
+            text_direction: TextDirection::Ltr,
             global_alpha: 1.0,
+            global_composite_operation: CompositeOperation::SourceOver,
+            clip_path: None,
         }
     }
 
-    fn resolve_paint(&self, mut paint: Paint) -> Paint {
-        paint.color.a = (paint.color.a as f32 * self.global_alpha).round() as u8;
+    fn resolve_paint(&self, paint: &Paint) -> Paint {
+        let mut paint = paint.clone();
+        paint.apply_transform(&self.transform);
+        paint.apply_opacity(self.global_alpha);
         paint
     }
 
@@ -387,6 +634,77 @@ impl Path2D {
         self.current_contour.close();
     }
 
+    /// Adds a rectangle whose corners are all rounded with the same `radius`.
+    #[inline]
+    pub fn round_rect(&mut self, rect: RectF, radius: f32) {
+        self.round_rect_with_radii(rect, [Vector2F::splat(radius); 4]);
+    }
+
+    /// Adds a rectangle with elliptical corners. `radii` holds the horizontal and vertical radii
+    /// of the upper left, upper right, lower right, and lower left corners, in that order.
+    ///
+    /// As in HTML canvas, if the corners along a side would overlap, all radii are scaled down
+    /// uniformly until they fit.
+    pub fn round_rect_with_radii(&mut self, rect: RectF, radii: [Vector2F; 4]) {
+        let mut radii = [
+            radii[0].max(Vector2F::default()),
+            radii[1].max(Vector2F::default()),
+            radii[2].max(Vector2F::default()),
+            radii[3].max(Vector2F::default()),
+        ];
+
+        let size = rect.size();
+        let mut scale: f32 = 1.0;
+        for &(length, radius_sum) in &[
+            (size.x(), radii[0].x() + radii[1].x()),
+            (size.y(), radii[1].y() + radii[2].y()),
+            (size.x(), radii[2].x() + radii[3].x()),
+            (size.y(), radii[3].y() + radii[0].y()),
+        ] {
+            if radius_sum > length {
+                scale = scale.min(length / radius_sum);
+            }
+        }
+        for radius in &mut radii {
+            *radius = radius.scale(scale);
+        }
+
+        self.flush_current_contour();
+        let corners = [rect.origin(), rect.upper_right(), rect.lower_right(), rect.lower_left()];
+        let centers = [
+            rect.origin() + radii[0],
+            rect.upper_right() + radii[1].scale_xy(Vector2F::new(-1.0, 1.0)),
+            rect.lower_right() - radii[2],
+            rect.lower_left() + radii[3].scale_xy(Vector2F::new(1.0, -1.0)),
+        ];
+        for corner_index in 0..4 {
+            let radius = radii[corner_index];
+            if radius.x() == 0.0 || radius.y() == 0.0 {
+                self.current_contour.push_endpoint(corners[corner_index]);
+                continue;
+            }
+
+            // Each corner sweeps a quarter turn clockwise, starting from the upper left.
+            let transform = Transform2F::from_scale(radius).translate(centers[corner_index]);
+            let start_angle = PI * (1.0 + 0.5 * corner_index as f32);
+            self.current_contour.push_arc(&transform,
+                                          start_angle,
+                                          start_angle + PI * 0.5,
+                                          ArcDirection::CW);
+        }
+        self.current_contour.close();
+    }
+
+    /// Appends the subpaths of `path`, transformed by `transform`, to this path.
+    pub fn add_path(&mut self, path: &Path2D, transform: &Transform2F) {
+        self.flush_current_contour();
+        let mut outline = path.clone().into_outline();
+        outline.transform(transform);
+        for contour in outline.contours() {
+            self.outline.push_contour(contour.clone());
+        }
+    }
+
     pub fn ellipse(&mut self,
                    center: Vector2F,
                    axes: Vector2F,
@@ -415,16 +733,21 @@ impl Path2D {
     }
 }
 
-// TODO(pcwalton): Gradients.
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum FillStyle {
     Color(ColorU),
+    Gradient(Gradient),
+    Pattern(Pattern),
 }
 
 impl FillStyle {
     #[inline]
     fn to_paint(&self) -> Paint {
-        match *self { FillStyle::Color(color) => Paint { color } }
+        match *self {
+            FillStyle::Color(color) => Paint::Color(color),
+            FillStyle::Gradient(ref gradient) => Paint::Gradient(gradient.clone()),
+            FillStyle::Pattern(ref pattern) => Paint::Pattern(pattern.clone()),
+        }
     }
 }
 
@@ -433,6 +756,86 @@ pub enum TextAlign {
     Left,
     Right,
     Center,
+    /// The left edge for left-to-right text and the right edge for right-to-left text.
+    Start,
+    /// The right edge for left-to-right text and the left edge for right-to-left text.
+    End,
+}
+
+/// The line of the text that the text position is placed on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextBaseline {
+    /// The top of the em square.
+    Top,
+    /// The hanging baseline, used by scripts such as Devanagari.
+    Hanging,
+    /// Halfway between the top and the bottom of the em square.
+    Middle,
+    /// The baseline that Latin letters sit on.
+    Alphabetic,
+    /// The bottom of the ideographic character face.
+    Ideographic,
+    /// The bottom of the em square.
+    Bottom,
+}
+
+/// The direction of the text, which determines what `TextAlign::Start` and `TextAlign::End`
+/// mean.
+///
+/// This only affects alignment; glyphs are always laid out left to right.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextDirection {
+    Ltr,
+    Rtl,
+}
+
+/// The operators of the HTML canvas `globalCompositeOperation` property.
+///
+/// Those that change the canvas outside the area being drawn, such as `copy` and `source-in`, are
+/// not supported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompositeOperation {
+    SourceOver,
+    SourceAtop,
+    DestinationOver,
+    DestinationOut,
+    Lighter,
+    Xor,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+}
+
+impl CompositeOperation {
+    fn to_blend_mode(self) -> BlendMode {
+        match self {
+            CompositeOperation::SourceOver => BlendMode::SrcOver,
+            CompositeOperation::SourceAtop => BlendMode::SrcAtop,
+            CompositeOperation::DestinationOver => BlendMode::DestOver,
+            CompositeOperation::DestinationOut => BlendMode::DestOut,
+            CompositeOperation::Lighter => BlendMode::Lighter,
+            CompositeOperation::Xor => BlendMode::Xor,
+            CompositeOperation::Multiply => BlendMode::Multiply,
+            CompositeOperation::Screen => BlendMode::Screen,
+            CompositeOperation::Overlay => BlendMode::Overlay,
+            CompositeOperation::Darken => BlendMode::Darken,
+            CompositeOperation::Lighten => BlendMode::Lighten,
+            CompositeOperation::ColorDodge => BlendMode::ColorDodge,
+            CompositeOperation::ColorBurn => BlendMode::ColorBurn,
+            CompositeOperation::HardLight => BlendMode::HardLight,
+            CompositeOperation::SoftLight => BlendMode::SoftLight,
+            CompositeOperation::Difference => BlendMode::Difference,
+            CompositeOperation::Exclusion => BlendMode::Exclusion,
+        }
+    }
 }
 
 // We duplicate `pathfinder_content::stroke::LineJoin` here because the HTML canvas API treats the
@@ -447,3 +850,281 @@ pub enum LineJoin {
     Round,
 }
 
+// Swaps the corners of `rect` as needed so that its size is nonnegative.
+fn normalize_rect(rect: RectF) -> RectF {
+    RectF::from_points(rect.origin().min(rect.lower_right()),
+                       rect.origin().max(rect.lower_right()))
+}
+
+// Copies the pixels of `image` within `rect`, which must lie inside the image.
+fn crop_image(image: &Image, rect: RectI) -> Image {
+    if rect.origin() == Vector2I::default() && rect.size() == image.size() {
+        return image.clone();
+    }
+    let pixels = (rect.min_y()..rect.max_y()).flat_map(|y| {
+        let row_start = (y * image.size().x()) as usize;
+        image.pixels()[row_start + rect.min_x() as usize..row_start + rect.max_x() as usize]
+            .iter()
+            .cloned()
+    }).collect();
+    Image::new(rect.size(), Arc::new(pixels))
+}
+
+// Returns a pattern that is `color` wherever `pattern` is opaque, for drawing its shadow.
+fn tint_pattern(pattern: &Pattern, color: ColorU) -> Pattern {
+    let image = pattern.image();
+    let pixels = image.pixels().iter().map(|pixel| {
+        let alpha = (pixel.a as u32 * color.a as u32 + 127) / 255;
+        ColorU { a: alpha as u8, ..color }
+    }).collect();
+    let mut tinted = Pattern::new(Image::new(image.size(), Arc::new(pixels)), pattern.repeat());
+    tinted.set_transform(&pattern.transform());
+    tinted.apply_opacity(pattern.opacity());
+    tinted
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CanvasFontContext, CanvasRenderingContext2D, CompositeOperation, FillStyle};
+    use crate::Path2D;
+    use pathfinder_content::color::ColorU;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::pattern::Image;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use pathfinder_renderer::options::RasterizeOptions;
+    use std::sync::Arc;
+
+    fn new_canvas() -> CanvasRenderingContext2D {
+        CanvasRenderingContext2D::new(CanvasFontContext::from_system_source(),
+                                      Vector2F::splat(32.0))
+    }
+
+    // A 4x4 image whose left half is red and whose right half is blue.
+    fn red_and_blue_image() -> Image {
+        let red = ColorU { r: 255, g: 0, b: 0, a: 255 };
+        let blue = ColorU { r: 0, g: 0, b: 255, a: 255 };
+        let pixels = (0..16).map(|index| if index % 4 < 2 { red } else { blue }).collect();
+        Image::new(Vector2I::splat(4), Arc::new(pixels))
+    }
+
+    // Renders the canvas and returns a function from pixel coordinates to RGBA values.
+    fn rasterize(canvas: CanvasRenderingContext2D) -> impl Fn(usize, usize) -> [u8; 4] {
+        let pixels = canvas.into_scene()
+                           .rasterize_to_pixels(Vector2I::splat(32), RasterizeOptions::default());
+        move |x, y| {
+            let index = (y * 32 + x) * 4;
+            [pixels[index], pixels[index + 1], pixels[index + 2], pixels[index + 3]]
+        }
+    }
+
+    fn assert_pixel_eq(actual: [u8; 4], expected: [u8; 4]) {
+        for (&actual_channel, &expected_channel) in actual.iter().zip(expected.iter()) {
+            assert!((actual_channel as i32 - expected_channel as i32).abs() <= 1,
+                    "expected {:?} but got {:?}",
+                    expected,
+                    actual);
+        }
+    }
+
+    #[test]
+    fn test_draw_image() {
+        let mut canvas = new_canvas();
+        canvas.draw_image(&red_and_blue_image(), Vector2F::splat(4.0));
+        let pixel = rasterize(canvas);
+        assert_pixel_eq(pixel(5, 5), [255, 0, 0, 255]);
+        assert_pixel_eq(pixel(6, 7), [0, 0, 255, 255]);
+        assert_pixel_eq(pixel(3, 5), [0, 0, 0, 0]);
+        assert_pixel_eq(pixel(8, 5), [0, 0, 0, 0]);
+        assert_pixel_eq(pixel(5, 8), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_draw_image_scaled() {
+        let mut canvas = new_canvas();
+        let dest_rect = RectF::new(Vector2F::default(), Vector2F::new(16.0, 8.0));
+        canvas.draw_image_scaled(&red_and_blue_image(), dest_rect);
+        let pixel = rasterize(canvas);
+        assert_pixel_eq(pixel(2, 4), [255, 0, 0, 255]);
+        assert_pixel_eq(pixel(13, 4), [0, 0, 255, 255]);
+        assert_pixel_eq(pixel(17, 4), [0, 0, 0, 0]);
+        assert_pixel_eq(pixel(2, 9), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_draw_subimage() {
+        let mut canvas = new_canvas();
+        // Only the blue half of the image is drawn.
+        let src_rect = RectF::new(Vector2F::new(2.0, 0.0), Vector2F::new(2.0, 4.0));
+        let dest_rect = RectF::new(Vector2F::splat(8.0), Vector2F::splat(8.0));
+        canvas.draw_subimage(&red_and_blue_image(), src_rect, dest_rect);
+        let pixel = rasterize(canvas);
+        assert_pixel_eq(pixel(12, 12), [0, 0, 255, 255]);
+        assert_pixel_eq(pixel(14, 9), [0, 0, 255, 255]);
+        assert_pixel_eq(pixel(4, 12), [0, 0, 0, 0]);
+        assert_pixel_eq(pixel(17, 12), [0, 0, 0, 0]);
+        // The red half doesn't bleed into the edge.
+        assert_pixel_eq(pixel(8, 12), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_draw_subimage_normalizes_and_clips() {
+        let mut canvas = new_canvas();
+        // Negative sizes select the same blue half, and the part outside the image is cut.
+        let src_rect = RectF::new(Vector2F::new(4.0, 4.0), Vector2F::new(-2.0, -8.0));
+        let dest_rect = RectF::new(Vector2F::new(16.0, 24.0), Vector2F::new(-8.0, -16.0));
+        canvas.draw_subimage(&red_and_blue_image(), src_rect, dest_rect);
+        let pixel = rasterize(canvas);
+        // The destination shrinks to match, to (8, 16) to (16, 24).
+        assert_pixel_eq(pixel(8, 16), [0, 0, 255, 255]);
+        assert_pixel_eq(pixel(15, 23), [0, 0, 255, 255]);
+        assert_pixel_eq(pixel(12, 14), [0, 0, 0, 0]);
+        assert_pixel_eq(pixel(12, 25), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_draw_image_shadow() {
+        let mut canvas = new_canvas();
+        canvas.set_shadow_color(ColorU { r: 0, g: 0, b: 255, a: 255 });
+        canvas.set_shadow_offset(Vector2F::new(16.0, 0.0));
+        // The right half of this image is transparent, so it casts no shadow.
+        let red = ColorU { r: 255, g: 0, b: 0, a: 255 };
+        let pixels = (0..16).map(|index| {
+            if index % 4 < 2 { red } else { ColorU::transparent_black() }
+        }).collect();
+        let image = Image::new(Vector2I::splat(4), Arc::new(pixels));
+        canvas.draw_image_scaled(&image, RectF::new(Vector2F::default(), Vector2F::splat(16.0)));
+        let pixel = rasterize(canvas);
+        assert_pixel_eq(pixel(4, 8), [255, 0, 0, 255]);
+        assert_pixel_eq(pixel(20, 8), [0, 0, 255, 255]);
+        assert_pixel_eq(pixel(28, 8), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_shadow_blur() {
+        let mut canvas = new_canvas();
+        canvas.set_shadow_color(ColorU { r: 0, g: 0, b: 255, a: 255 });
+        canvas.set_shadow_offset(Vector2F::new(4.0, 0.0));
+        canvas.set_shadow_blur(4.0);
+        canvas.fill_rect(RectF::new(Vector2F::splat(8.0), Vector2F::splat(16.0)));
+        let pixel = rasterize(canvas);
+        assert_pixel_eq(pixel(10, 16), [0, 0, 0, 255]);
+        // The shadow ends at x = 28, but its edge is blurred.
+        let inside = pixel(26, 16);
+        let outside = pixel(29, 16);
+        assert_eq!(&inside[0..3], &[0, 0, 255]);
+        assert_eq!(&outside[0..3], &[0, 0, 255]);
+        assert!(inside[3] > 128 && inside[3] < 255);
+        assert!(outside[3] > 0 && outside[3] < 128);
+    }
+
+    #[test]
+    fn test_shadow_blur_is_clipped_after_blurring() {
+        let mut canvas = new_canvas();
+        let mut clip = Path2D::new();
+        clip.rect(RectF::new(Vector2F::default(), Vector2F::new(20.0, 32.0)));
+        canvas.clip_path(clip, FillRule::Winding);
+        canvas.set_shadow_color(ColorU { r: 0, g: 0, b: 255, a: 255 });
+        canvas.set_shadow_offset(Vector2F::new(4.0, 0.0));
+        canvas.set_shadow_blur(4.0);
+        canvas.fill_rect(RectF::new(Vector2F::splat(8.0), Vector2F::splat(16.0)));
+        let pixel = rasterize(canvas);
+        // The blur spreads above the shape within the clip, but not past the clip's edge.
+        let above = pixel(16, 6);
+        assert_eq!(&above[0..3], &[0, 0, 255]);
+        assert!(above[3] > 0);
+        assert_pixel_eq(pixel(21, 16), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_shadow_blur_uses_composite_operation() {
+        let mut canvas = new_canvas();
+        canvas.set_fill_style(FillStyle::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+        canvas.fill_rect(RectF::new(Vector2F::default(), Vector2F::splat(32.0)));
+        canvas.set_global_composite_operation(CompositeOperation::DestinationOver);
+        canvas.set_shadow_color(ColorU { r: 0, g: 0, b: 255, a: 255 });
+        canvas.set_shadow_offset(Vector2F::new(4.0, 0.0));
+        canvas.set_shadow_blur(4.0);
+        canvas.fill_rect(RectF::new(Vector2F::splat(8.0), Vector2F::splat(16.0)));
+        // Everything is drawn beneath the opaque red background.
+        let pixel = rasterize(canvas);
+        assert_pixel_eq(pixel(26, 16), [255, 0, 0, 255]);
+        assert_pixel_eq(pixel(16, 16), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_round_rect() {
+        let canvas = new_canvas();
+        let mut path = Path2D::new();
+        path.round_rect(RectF::new(Vector2F::default(), Vector2F::new(100.0, 50.0)), 10.0);
+        let contains = |x, y| {
+            canvas.is_point_in_path(&path, Vector2F::new(x, y), FillRule::Winding)
+        };
+
+        assert!(contains(50.0, 25.0));
+        assert!(contains(50.0, 1.0));
+        assert!(contains(1.0, 25.0));
+        // Just inside the corners of the rectangle, but outside the rounded corners.
+        assert!(!contains(1.0, 1.0));
+        assert!(!contains(99.0, 1.0));
+        assert!(!contains(99.0, 49.0));
+        assert!(!contains(1.0, 49.0));
+        // Inside the rounded corners.
+        assert!(contains(4.0, 4.0));
+        assert!(contains(96.0, 46.0));
+    }
+
+    #[test]
+    fn test_round_rect_with_radii() {
+        let canvas = new_canvas();
+        let mut path = Path2D::new();
+        let rect = RectF::new(Vector2F::default(), Vector2F::new(100.0, 20.0));
+        // The left radii overlap along the left side, so all radii are halved.
+        path.round_rect_with_radii(rect, [
+            Vector2F::splat(20.0),
+            Vector2F::default(),
+            Vector2F::new(40.0, 10.0),
+            Vector2F::splat(20.0),
+        ]);
+        let contains = |x, y| {
+            canvas.is_point_in_path(&path, Vector2F::new(x, y), FillRule::Winding)
+        };
+
+        // The upper right corner is square.
+        assert!(contains(99.5, 0.5));
+        // The upper left and lower left corners have a radius of 10.
+        assert!(!contains(2.0, 2.0));
+        assert!(contains(1.0, 10.0));
+        assert!(!contains(2.0, 18.0));
+        // The lower right corner is an ellipse with radii of 20 and 5.
+        assert!(!contains(99.0, 19.0));
+        assert!(contains(99.0, 15.5));
+        assert!(contains(82.0, 19.5));
+    }
+
+    #[test]
+    fn test_add_path() {
+        let canvas = new_canvas();
+        let mut square = Path2D::new();
+        square.rect(RectF::new(Vector2F::default(), Vector2F::splat(10.0)));
+
+        // The subpath in progress is kept ahead of the added ones.
+        let mut path = Path2D::new();
+        path.move_to(Vector2F::new(40.0, 0.0));
+        path.line_to(Vector2F::new(50.0, 0.0));
+        path.line_to(Vector2F::new(50.0, 10.0));
+        path.add_path(&square, &Transform2F::from_translation(Vector2F::new(20.0, 0.0)));
+        path.add_path(&square, &Transform2F::from_scale(Vector2F::splat(0.5)));
+        let contains = |x, y| {
+            canvas.is_point_in_path(&path, Vector2F::new(x, y), FillRule::Winding)
+        };
+
+        assert!(contains(25.0, 5.0));
+        assert!(!contains(15.0, 5.0));
+        assert!(contains(4.0, 4.0));
+        assert!(!contains(6.0, 6.0));
+        assert!(contains(48.0, 2.0));
+        assert!(!contains(42.0, 8.0));
+    }
+}
@@ -8,70 +8,150 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use crate::css::{self, FontFamilyName, FontShorthand, FontStyle};
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::outline::Outline;
+use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::vector::Vector2F;
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_renderer::paint::PaintId;
+use pathfinder_renderer::paint::{Paint, PaintId};
+use pathfinder_renderer::scene::Scene;
 use std::sync::Arc;
 use std::iter;
 use font_kit::family_name::FamilyName;
 use font_kit::handle::Handle;
 use font_kit::hinting::HintingOptions;
-use font_kit::properties::Properties;
+use font_kit::properties::{Properties, Stretch, Style, Weight};
 use font_kit::source::{Source, SystemSource};
 use font_kit::sources::mem::MemSource;
 pub use skribo::{FontCollection, FontFamily, Layout, TextStyle};
 use pathfinder_text::{SceneExt, TextRenderMode};
 pub use font_kit::loaders::default::Font;
-pub use crate::{CanvasRenderingContext2D, TextAlign};
+pub use crate::{CanvasRenderingContext2D, TextAlign, TextBaseline, TextDirection};
+
+// Browsers place the hanging baseline at this fraction of the ascent, since fonts rarely record
+// it.
+const HANGING_BASELINE_RATIO: f32 = 0.8;
 
 impl CanvasRenderingContext2D {
     pub fn fill_text(&mut self, string: &str, position: Vector2F) {
-        let paint_id = self.scene.push_paint(&self.current_state.fill_paint);
+        let paint = self.current_state.resolve_paint(&self.current_state.fill_paint);
+        let paint_id = self.scene.push_paint(&paint);
         self.fill_or_stroke_text(string, position, paint_id, TextRenderMode::Fill);
     }
 
     pub fn stroke_text(&mut self, string: &str, position: Vector2F) {
-        let paint_id = self.scene.push_paint(&self.current_state.stroke_paint);
+        let paint = self.current_state.resolve_paint(&self.current_state.stroke_paint);
+        let paint_id = self.scene.push_paint(&paint);
         let render_mode = TextRenderMode::Stroke(self.current_state.resolve_stroke_style());
         self.fill_or_stroke_text(string, position, paint_id, render_mode);
     }
 
+    /// Measures `string` as it would be drawn with the current font, alignment, and baseline.
+    ///
+    /// Horizontal distances are measured from the text position, and vertical distances from the
+    /// current text baseline, with positive values pointing up.
     pub fn measure_text(&self, string: &str) -> TextMetrics {
-        TextMetrics { width: self.layout_text(string).width() }
+        let layout = self.layout_text(string);
+        let origin = self.text_origin(&layout);
+        let baseline = -origin.y();
+        let font_extents = layout.font_extents();
+        let bounds = layout.actual_bounds().unwrap_or_default();
+
+        TextMetrics {
+            width: layout.width(),
+            actual_bounding_box_left: -(bounds.min_x() + origin.x()),
+            actual_bounding_box_right: bounds.max_x() + origin.x(),
+            font_bounding_box_ascent: baseline + font_extents.ascent,
+            font_bounding_box_descent: font_extents.descent - baseline,
+            actual_bounding_box_ascent: baseline - bounds.min_y(),
+            actual_bounding_box_descent: bounds.max_y() - baseline,
+            em_height_ascent: baseline + font_extents.em_ascent,
+            em_height_descent: font_extents.em_descent - baseline,
+            hanging_baseline: baseline + font_extents.hanging,
+            alphabetic_baseline: baseline,
+            ideographic_baseline: baseline - font_extents.ideographic,
+        }
     }
 
     pub fn fill_layout(&mut self, layout: &Layout, transform: Transform2F) {
-        let paint_id = self.scene.push_paint(&self.current_state.fill_paint);
-        drop(self.scene.push_layout(&layout,
-                                    &TextStyle { size: self.current_state.font_size },
-                                    &(transform * self.current_state.transform),
-                                    TextRenderMode::Fill,
-                                    HintingOptions::None,
-                                    paint_id));
+        let paint = self.current_state.resolve_paint(&self.current_state.fill_paint);
+        let paint_id = self.scene.push_paint(&paint);
+        let transform = transform * self.current_state.transform;
+        self.push_text_layout(layout, &transform, TextRenderMode::Fill, paint_id);
     }
 
     fn fill_or_stroke_text(&mut self,
                            string: &str,
-                           mut position: Vector2F,
+                           position: Vector2F,
                            paint_id: PaintId,
                            render_mode: TextRenderMode) {
         let layout = self.layout_text(string);
-
-        match self.current_state.text_align {
-            TextAlign::Left => {},
-            TextAlign::Right => position.set_x(position.x() - layout.width()),
-            TextAlign::Center => position.set_x(position.x() - layout.width() * 0.5),
-        }
+        let position = position + self.text_origin(&layout);
 
         let transform = self.current_state.transform * Transform2F::from_translation(position);
+        self.push_text_layout(&layout, &transform, render_mode, paint_id);
+    }
+
+    // Lays the glyphs out into a scratch scene first, and then merges them into a single path that
+    // goes through `push_path()`. So the text picks up the shadow and clip like any other path,
+    // and no glyph's shadow lands on top of the glyphs before it.
+    fn push_text_layout(&mut self,
+                        layout: &Layout,
+                        transform: &Transform2F,
+                        render_mode: TextRenderMode,
+                        paint_id: PaintId) {
+        let mut glyph_scene = Scene::new();
+        let glyph_paint_id = glyph_scene.push_paint(&Paint::black());
 
         // TODO(pcwalton): Report errors.
-        drop(self.scene.push_layout(&layout,
-                                    &TextStyle { size: self.current_state.font_size },
-                                    &transform,
-                                    render_mode,
-                                    HintingOptions::None,
-                                    paint_id));
+        drop(glyph_scene.push_layout(layout,
+                                     &TextStyle { size: self.current_state.font_size },
+                                     transform,
+                                     render_mode,
+                                     HintingOptions::None,
+                                     glyph_paint_id));
+
+        let mut text_outline = Outline::new();
+        for (_, outline, _) in glyph_scene.paths() {
+            for contour in outline.contours() {
+                text_outline.push_contour(contour.clone());
+            }
+        }
+        if !text_outline.contours().is_empty() {
+            self.push_path(text_outline, paint_id, FillRule::Winding);
+        }
+    }
+
+    // Returns the offset from the text position to the start of the alphabetic baseline of
+    // `layout`, according to the current alignment and baseline.
+    fn text_origin(&self, layout: &Layout) -> Vector2F {
+        let text_align = match (self.current_state.text_align, self.current_state.text_direction) {
+            (TextAlign::Start, TextDirection::Ltr) | (TextAlign::End, TextDirection::Rtl) => {
+                TextAlign::Left
+            }
+            (TextAlign::Start, TextDirection::Rtl) | (TextAlign::End, TextDirection::Ltr) => {
+                TextAlign::Right
+            }
+            (text_align, _) => text_align,
+        };
+        let x = match text_align {
+            TextAlign::Right => -layout.width(),
+            TextAlign::Center => -layout.width() * 0.5,
+            _ => 0.0,
+        };
+
+        let font_extents = layout.font_extents();
+        let baseline = match self.current_state.text_baseline {
+            TextBaseline::Top => -font_extents.em_ascent,
+            TextBaseline::Hanging => -font_extents.hanging,
+            TextBaseline::Middle => (font_extents.em_descent - font_extents.em_ascent) * 0.5,
+            TextBaseline::Alphabetic => 0.0,
+            TextBaseline::Ideographic => font_extents.ideographic,
+            TextBaseline::Bottom => font_extents.em_descent,
+        };
+
+        Vector2F::new(x, -baseline)
     }
 
     fn layout_text(&self, string: &str) -> Layout {
@@ -103,8 +183,10 @@ impl CanvasRenderingContext2D {
     }
 
     /// A convenience method to set a single font family consisting of a single font.
+    ///
+    /// This was called `set_font()` before that name was taken by the CSS shorthand setter.
     #[inline]
-    pub fn set_font(&mut self, font: Font) {
+    pub fn set_font_face(&mut self, font: Font) {
         self.set_font_family(FontFamily::new_from_font(font))
     }
 
@@ -113,7 +195,41 @@ impl CanvasRenderingContext2D {
     #[inline]
     pub fn set_font_by_postscript_name(&mut self, postscript_name: &str) {
         let font = self.font_context.font_source.select_by_postscript_name(postscript_name);
-        self.set_font(font.expect("Didn't find the font!").load().unwrap());
+        self.set_font_face(font.expect("Didn't find the font!").load().unwrap());
+    }
+
+    /// Sets the font and font size from a CSS `font` shorthand value, such as
+    /// `italic bold 14px 'Open Sans', sans-serif`.
+    ///
+    /// Each family is looked up in the font source of the font context. Characters that a family
+    /// can't display fall back to the next family in the list, and finally to the default
+    /// sans-serif font. As in HTML canvas, values that can't be parsed are ignored.
+    ///
+    /// This method used to take a `Font`. Code that sets a loaded font directly should call
+    /// `set_font_face()` instead.
+    pub fn set_font(&mut self, font: &str) {
+        let shorthand = match css::parse_font_shorthand(font, self.current_state.font_size) {
+            None => return,
+            Some(shorthand) => shorthand,
+        };
+
+        let properties = shorthand.to_properties();
+        let mut font_collection = FontCollection::new();
+        let mut family_names: Vec<_> =
+            shorthand.families.iter().map(FontFamilyName::to_font_kit_family_name).collect();
+        if !shorthand.families.contains(&FontFamilyName::SansSerif) {
+            family_names.push(FamilyName::SansSerif);
+        }
+        for family_name in family_names {
+            let font_source = &self.font_context.font_source;
+            let handle = font_source.select_best_match(&[family_name], &properties);
+            if let Ok(Ok(font)) = handle.map(|handle| handle.load()) {
+                font_collection.add_family(FontFamily::new_from_font(font));
+            }
+        }
+
+        self.current_state.font_collection = Arc::new(font_collection);
+        self.current_state.font_size = shorthand.size;
     }
 
     #[inline]
@@ -121,16 +237,52 @@ impl CanvasRenderingContext2D {
         self.current_state.font_size = new_font_size;
     }
 
+    #[inline]
+    pub fn text_align(&self) -> TextAlign {
+        self.current_state.text_align
+    }
+
     #[inline]
     pub fn set_text_align(&mut self, new_text_align: TextAlign) {
         self.current_state.text_align = new_text_align;
     }
+
+    #[inline]
+    pub fn text_baseline(&self) -> TextBaseline {
+        self.current_state.text_baseline
+    }
+
+    #[inline]
+    pub fn set_text_baseline(&mut self, new_text_baseline: TextBaseline) {
+        self.current_state.text_baseline = new_text_baseline;
+    }
+
+    #[inline]
+    pub fn direction(&self) -> TextDirection {
+        self.current_state.text_direction
+    }
+
+    #[inline]
+    pub fn set_direction(&mut self, new_direction: TextDirection) {
+        self.current_state.text_direction = new_direction;
+    }
 }
 
-// TODO(pcwalton): Support other fields.
+/// The dimensions of a piece of text, as in the HTML canvas `TextMetrics` interface.
 #[derive(Clone, Copy, Debug)]
 pub struct TextMetrics {
     pub width: f32,
+    pub actual_bounding_box_left: f32,
+    pub actual_bounding_box_right: f32,
+    pub font_bounding_box_ascent: f32,
+    pub font_bounding_box_descent: f32,
+    pub actual_bounding_box_ascent: f32,
+    pub actual_bounding_box_descent: f32,
+    pub em_height_ascent: f32,
+    pub em_height_descent: f32,
+    pub hanging_baseline: f32,
+    pub alphabetic_baseline: f32,
+    pub ideographic_baseline: f32,
 }
 
 #[derive(Clone)]
@@ -169,10 +321,41 @@ impl CanvasFontContext {
     }
 }
 
+// Font selection utilities
+
+impl FontShorthand {
+    fn to_properties(&self) -> Properties {
+        Properties {
+            style: match self.style {
+                FontStyle::Normal => Style::Normal,
+                FontStyle::Italic => Style::Italic,
+                FontStyle::Oblique => Style::Oblique,
+            },
+            weight: Weight(self.weight as f32),
+            stretch: Stretch(self.stretch as f32 / 100.0),
+        }
+    }
+}
+
+impl FontFamilyName {
+    fn to_font_kit_family_name(&self) -> FamilyName {
+        match *self {
+            FontFamilyName::Named(ref name) => FamilyName::Title(name.clone()),
+            FontFamilyName::Serif => FamilyName::Serif,
+            FontFamilyName::SansSerif => FamilyName::SansSerif,
+            FontFamilyName::Monospace => FamilyName::Monospace,
+            FontFamilyName::Cursive => FamilyName::Cursive,
+            FontFamilyName::Fantasy => FamilyName::Fantasy,
+        }
+    }
+}
+
 // Text layout utilities
 
 pub trait LayoutExt {
     fn width(&self) -> f32;
+    fn actual_bounds(&self) -> Option<RectF>;
+    fn font_extents(&self) -> FontExtents;
 }
 
 impl LayoutExt for Layout {
@@ -188,4 +371,64 @@ impl LayoutExt for Layout {
         let scale_factor = self.size / font_metrics.units_per_em as f32;
         last_glyph.offset.x + glyph_rect.max_x() * scale_factor
     }
+
+    /// Returns the union of the bounding boxes of the glyphs, relative to the start of the
+    /// alphabetic baseline, or `None` if there are no glyphs.
+    fn actual_bounds(&self) -> Option<RectF> {
+        let mut bounds: Option<RectF> = None;
+        for glyph in &self.glyphs {
+            let glyph_rect = match glyph.font.font.typographic_bounds(glyph.glyph_id) {
+                Ok(glyph_rect) => glyph_rect,
+                Err(_) => continue,
+            };
+            let scale_factor = self.size / glyph.font.font.metrics().units_per_em as f32;
+            let offset = Vector2F::new(glyph.offset.x, glyph.offset.y);
+            // Font units point up, so flip them.
+            let upper_left = Vector2F::new(glyph_rect.min_x(), -glyph_rect.max_y());
+            let lower_right = Vector2F::new(glyph_rect.max_x(), -glyph_rect.min_y());
+            let glyph_bounds = RectF::from_points(offset + upper_left.scale(scale_factor),
+                                                  offset + lower_right.scale(scale_factor));
+            bounds = Some(match bounds {
+                None => glyph_bounds,
+                Some(bounds) => bounds.union_rect(glyph_bounds),
+            });
+        }
+        bounds
+    }
+
+    /// Returns the largest vertical extents among the fonts used by the glyphs.
+    fn font_extents(&self) -> FontExtents {
+        let mut extents = FontExtents::default();
+        for glyph in &self.glyphs {
+            let metrics = glyph.font.font.metrics();
+            let scale_factor = self.size / metrics.units_per_em as f32;
+            let ascent = metrics.ascent * scale_factor;
+            let descent = -metrics.descent * scale_factor;
+            let em_ascent = if ascent + descent > 0.0 {
+                self.size * ascent / (ascent + descent)
+            } else {
+                self.size
+            };
+
+            extents.ascent = extents.ascent.max(ascent);
+            extents.descent = extents.descent.max(descent);
+            extents.em_ascent = extents.em_ascent.max(em_ascent);
+            extents.em_descent = extents.em_descent.max(self.size - em_ascent);
+            extents.hanging = extents.hanging.max(ascent * HANGING_BASELINE_RATIO);
+            extents.ideographic = extents.ideographic.max(descent);
+        }
+        extents
+    }
+}
+
+/// Vertical font metrics in scene units. Each is a distance from the alphabetic baseline: upward
+/// for the ascents and the hanging baseline, and downward for the rest.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FontExtents {
+    pub ascent: f32,
+    pub descent: f32,
+    pub em_ascent: f32,
+    pub em_descent: f32,
+    pub hanging: f32,
+    pub ideographic: f32,
 }
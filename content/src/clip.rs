@@ -8,14 +8,16 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::outline::{Contour, PointFlags, PushSegmentFlags};
+use crate::fill::FillRule;
+use crate::outline::{Contour, Outline, PointFlags, PushSegmentFlags};
 use crate::segment::{CubicSegment, Segment};
 use arrayvec::ArrayVec;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
-use pathfinder_geometry::util::lerp;
+use pathfinder_geometry::util::{self, lerp};
 use pathfinder_geometry::vector::{Vector2F, Vector4F};
 use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::mem;
 
@@ -376,6 +378,359 @@ impl ContourRectClipper {
     }
 }
 
+// General outline clipping and boolean operations
+//
+// Both outlines are flattened to line segments, which are split wherever they cross. Each piece is
+// kept if the interior of the result lies on exactly one side of it, and the kept pieces are then
+// linked back up into contours.
+
+const FLATTENING_TOLERANCE: f32 = 0.1;
+const MAX_FLATTENING_SUBDIVISIONS: u32 = 16;
+const INTERSECTION_EPSILON: f32 = 1e-5;
+
+/// How the interiors of two outlines are combined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BooleanOp {
+    /// Points inside either outline.
+    Union,
+    /// Points inside both outlines.
+    Intersection,
+    /// Points inside the first outline but not the second.
+    Difference,
+    /// Points inside exactly one of the outlines.
+    Xor,
+}
+
+/// Combines an outline with another, both arbitrarily complex, using a boolean operation. Clipping
+/// is the intersection. Curves in the result are flattened.
+///
+/// The result winds once around its interior, so it is filled correctly under either fill rule.
+pub(crate) struct OutlineClipper {
+    subject: WindingIndex,
+    subject_fill_rule: FillRule,
+    clip: WindingIndex,
+    clip_fill_rule: FillRule,
+    op: BooleanOp,
+}
+
+impl OutlineClipper {
+    pub(crate) fn new(subject: &Outline,
+                      subject_fill_rule: FillRule,
+                      clip: &Outline,
+                      clip_fill_rule: FillRule,
+                      op: BooleanOp)
+                      -> OutlineClipper {
+        OutlineClipper {
+            subject: WindingIndex::new(flatten_outline(subject)),
+            subject_fill_rule,
+            clip: WindingIndex::new(flatten_outline(clip)),
+            clip_fill_rule,
+            op,
+        }
+    }
+
+    pub(crate) fn clip(self) -> Outline {
+        let mut edges = self.subject.edges.clone();
+        edges.extend_from_slice(&self.clip.edges);
+
+        let mut kept_pieces = vec![];
+        let mut seen_pieces = HashSet::new();
+        for piece in split_edges_at_intersections(&edges) {
+            let vector = piece.vector();
+            let length = vector.length();
+            let midpoint = piece.midpoint();
+            let scale = 1.0 + midpoint.x().abs().max(midpoint.y().abs());
+            let normal = Vector2F::new(-vector.y(), vector.x()).scale(1e-4 * scale / length);
+
+            let inside_left = self.point_is_inside(midpoint + normal);
+            let inside_right = self.point_is_inside(midpoint - normal);
+            if inside_left == inside_right {
+                continue;
+            }
+
+            // Keep the interior consistently on the same side of each piece.
+            let piece = if inside_right { piece } else { piece.reversed() };
+            if seen_pieces.insert((point_key(piece.from()), point_key(piece.to()))) {
+                kept_pieces.push(piece);
+            }
+        }
+
+        link_pieces(&kept_pieces)
+    }
+
+    fn point_is_inside(&self, point: Vector2F) -> bool {
+        let inside_subject = self.subject_fill_rule.winding_is_inside(self.subject.winding(point));
+        let inside_clip = self.clip_fill_rule.winding_is_inside(self.clip.winding(point));
+        match self.op {
+            BooleanOp::Union => inside_subject || inside_clip,
+            BooleanOp::Intersection => inside_subject && inside_clip,
+            BooleanOp::Difference => inside_subject && !inside_clip,
+            BooleanOp::Xor => inside_subject != inside_clip,
+        }
+    }
+}
+
+// Line segments bucketed into horizontal bands for fast winding number queries.
+struct WindingIndex {
+    edges: Vec<LineSegment2F>,
+    min_y: f32,
+    band_height: f32,
+    bands: Vec<Vec<u32>>,
+}
+
+impl WindingIndex {
+    fn new(edges: Vec<LineSegment2F>) -> WindingIndex {
+        let (mut min_y, mut max_y) = (f32::INFINITY, f32::NEG_INFINITY);
+        for edge in &edges {
+            min_y = min_y.min(edge.min_y());
+            max_y = max_y.max(edge.max_y());
+        }
+
+        let band_count = util::clamp((edges.len() as f32).sqrt().ceil(), 1.0, 256.0) as usize;
+        let band_height = if max_y > min_y { (max_y - min_y) / band_count as f32 } else { 1.0 };
+        let mut index = WindingIndex { edges, min_y, band_height, bands: vec![vec![]; band_count] };
+        for (edge_index, edge) in index.edges.iter().enumerate() {
+            let first_band = index.band_of(edge.min_y());
+            let last_band = index.band_of(edge.max_y());
+            for band in &mut index.bands[first_band..=last_band] {
+                band.push(edge_index as u32);
+            }
+        }
+        index
+    }
+
+    fn band_of(&self, y: f32) -> usize {
+        let band = ((y - self.min_y) / self.band_height).floor();
+        if band > 0.0 {
+            (band as usize).min(self.bands.len() - 1)
+        } else {
+            0
+        }
+    }
+
+    // Casts a ray in the +x direction and sums the signed crossings.
+    fn winding(&self, point: Vector2F) -> i32 {
+        if self.edges.is_empty() {
+            return 0;
+        }
+
+        self.bands[self.band_of(point.y())].iter().map(|&edge_index| {
+            edge_winding(self.edges[edge_index as usize], point)
+        }).sum()
+    }
+}
+
+// Returns the signed number of times a ray cast from `point` in the +x direction crosses `edge`.
+pub(crate) fn edge_winding(edge: LineSegment2F, point: Vector2F) -> i32 {
+    let side = edge.vector().det(point - edge.from());
+    if edge.from_y() <= point.y() && edge.to_y() > point.y() && side > 0.0 {
+        1
+    } else if edge.to_y() <= point.y() && edge.from_y() > point.y() && side < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+pub(crate) fn flatten_outline(outline: &Outline) -> Vec<LineSegment2F> {
+    let mut edges = vec![];
+    for contour in outline.contours() {
+        for segment in contour.iter() {
+            flatten_segment(&segment, FLATTENING_TOLERANCE, &mut edges);
+        }
+
+        // Filling closes contours implicitly, so clipping must too.
+        if !contour.is_closed() && contour.len() > 1 {
+            edges.push(LineSegment2F::new(contour.position_of(contour.len() - 1),
+                                          contour.position_of(0)));
+        }
+    }
+    edges.retain(|edge| !edge.is_zero_length());
+    edges
+}
+
+pub(crate) fn flatten_segment(segment: &Segment, tolerance: f32, edges: &mut Vec<LineSegment2F>) {
+    if segment.is_line() {
+        edges.push(segment.baseline);
+        return;
+    }
+
+    let mut segment = segment.to_cubic();
+    loop {
+        let (mut before, mut after) = (segment, None);
+        let mut split_t = 1.0;
+        let mut subdivisions = 0;
+        while !before.as_cubic_segment().is_flat(tolerance) &&
+                subdivisions < MAX_FLATTENING_SUBDIVISIONS {
+            split_t *= 0.5;
+            let (new_before, new_after) = segment.as_cubic_segment().split(split_t);
+            before = new_before;
+            after = Some(new_after);
+            subdivisions += 1;
+        }
+
+        edges.push(before.baseline);
+        match after {
+            Some(after) => segment = after,
+            None => return,
+        }
+    }
+}
+
+// Splits every edge at every point where it meets another edge. The same split point is used for
+// both edges, so pieces that meet share endpoints exactly.
+fn split_edges_at_intersections(edges: &[LineSegment2F]) -> Vec<LineSegment2F> {
+    let mut split_points: Vec<Vec<(f32, Vector2F)>> = vec![vec![]; edges.len()];
+
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|&a, &b| edges[a].min_x().total_cmp(&edges[b].min_x()));
+    for (order_index, &index_a) in order.iter().enumerate() {
+        let edge_a = edges[index_a];
+        for &index_b in &order[(order_index + 1)..] {
+            let edge_b = edges[index_b];
+            if edge_b.min_x() > edge_a.max_x() {
+                break;
+            }
+            if edge_b.min_y() > edge_a.max_y() || edge_b.max_y() < edge_a.min_y() {
+                continue;
+            }
+
+            for (t_a, t_b) in intersect_edges(edge_a, edge_b) {
+                // Snap to an existing endpoint if there is one.
+                let point = if t_a <= 0.0 {
+                    edge_a.from()
+                } else if t_a >= 1.0 {
+                    edge_a.to()
+                } else if t_b <= 0.0 {
+                    edge_b.from()
+                } else if t_b >= 1.0 {
+                    edge_b.to()
+                } else {
+                    edge_a.sample(t_a)
+                };
+                if t_a > 0.0 && t_a < 1.0 {
+                    split_points[index_a].push((t_a, point));
+                }
+                if t_b > 0.0 && t_b < 1.0 {
+                    split_points[index_b].push((t_b, point));
+                }
+            }
+        }
+    }
+
+    let mut pieces = vec![];
+    for (edge, mut points) in edges.iter().zip(split_points) {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut from = edge.from();
+        for (_, point) in points.into_iter().chain(Some((1.0, edge.to()))) {
+            if point_key(point) != point_key(from) {
+                pieces.push(LineSegment2F::new(from, point));
+                from = point;
+            }
+        }
+    }
+    pieces
+}
+
+// Returns the parameters along each edge at which the two edges meet, snapped to 0 or 1 near the
+// endpoints. Overlapping collinear edges meet at the endpoints of the overlap.
+fn intersect_edges(a: LineSegment2F, b: LineSegment2F) -> SmallVec<[(f32, f32); 2]> {
+    let mut results = SmallVec::new();
+    let (vector_a, vector_b) = (a.vector(), b.vector());
+    let denom = vector_a.det(vector_b);
+    let offset = b.from() - a.from();
+
+    let scale = vector_a.length() * vector_b.length();
+    if denom.abs() <= INTERSECTION_EPSILON * scale {
+        if offset.det(vector_a).abs() > INTERSECTION_EPSILON * vector_a.length() *
+                offset.length().max(1.0) {
+            return results;
+        }
+
+        // Collinear: project each edge's endpoints onto the other.
+        let (square_length_a, square_length_b) = (a.square_length(), b.square_length());
+        for &(point, t_b) in &[(b.from(), 0.0), (b.to(), 1.0)] {
+            let t_a = snap_t((point - a.from()).dot(vector_a) / square_length_a);
+            if t_a > 0.0 && t_a < 1.0 {
+                results.push((t_a, t_b));
+            }
+        }
+        for &(point, t_a) in &[(a.from(), 0.0), (a.to(), 1.0)] {
+            let t_b = snap_t((point - b.from()).dot(vector_b) / square_length_b);
+            if t_b > 0.0 && t_b < 1.0 {
+                results.push((t_a, t_b));
+            }
+        }
+        return results;
+    }
+
+    let t_a = snap_t(offset.det(vector_b) / denom);
+    let t_b = snap_t(offset.det(vector_a) / denom);
+    if (0.0..=1.0).contains(&t_a) && (0.0..=1.0).contains(&t_b) {
+        results.push((t_a, t_b));
+    }
+    results
+}
+
+fn snap_t(t: f32) -> f32 {
+    if t.abs() <= INTERSECTION_EPSILON {
+        0.0
+    } else if (t - 1.0).abs() <= INTERSECTION_EPSILON {
+        1.0
+    } else {
+        t
+    }
+}
+
+// Joins directed pieces end to start into closed contours.
+fn link_pieces(pieces: &[LineSegment2F]) -> Outline {
+    let mut pieces_by_start: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (piece_index, piece) in pieces.iter().enumerate() {
+        pieces_by_start.entry(point_key(piece.from())).or_default().push(piece_index);
+    }
+
+    let mut outline = Outline::new();
+    let mut used = vec![false; pieces.len()];
+    for first_piece_index in 0..pieces.len() {
+        if used[first_piece_index] {
+            continue;
+        }
+
+        let mut contour = Contour::new();
+        let start_key = point_key(pieces[first_piece_index].from());
+        let mut piece_index = first_piece_index;
+        loop {
+            used[piece_index] = true;
+            let piece = pieces[piece_index];
+            contour.push_endpoint(piece.from());
+
+            let end_key = point_key(piece.to());
+            if end_key == start_key {
+                break;
+            }
+            let next_piece_index = pieces_by_start.get(&end_key).and_then(|candidates| {
+                candidates.iter().cloned().find(|&candidate| !used[candidate])
+            });
+            match next_piece_index {
+                Some(next_piece_index) => piece_index = next_piece_index,
+                None => {
+                    contour.push_endpoint(piece.to());
+                    break;
+                }
+            }
+        }
+
+        contour.close();
+        outline.push_contour(contour);
+    }
+    outline
+}
+
+#[inline]
+fn point_key(point: Vector2F) -> (u32, u32) {
+    (point.x().to_bits(), point.y().to_bits())
+}
+
 // 3D quad clipping
 
 pub struct PolygonClipper3D {
@@ -553,3 +908,100 @@ bitflags! {
         const BOTTOM = 0x08;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::fill::FillRule;
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+
+    fn polygon(points: &[(f32, f32)]) -> Outline {
+        let mut contour = Contour::new();
+        for &(x, y) in points {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    fn area(outline: &Outline) -> f32 {
+        let mut area = 0.0;
+        for contour in outline.contours() {
+            for index in 0..contour.len() {
+                let from = contour.position_of(index);
+                let to = contour.position_of((index + 1) % contour.len());
+                area += from.det(to);
+            }
+        }
+        area.abs() * 0.5
+    }
+
+    #[test]
+    fn test_clip_against_concave_outline() {
+        let mut square = polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        let l_shape = polygon(&[(5.0, -5.0), (15.0, -5.0), (15.0, 15.0), (-5.0, 15.0),
+                                (-5.0, 5.0), (5.0, 5.0)]);
+        square.clip_against_outline(FillRule::Winding, &l_shape, FillRule::Winding);
+        assert!((area(&square) - 75.0).abs() < 0.01);
+        assert_eq!(square.bounds().origin(), Vector2F::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let mut outline = polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        let mut hole = Contour::new();
+        for &(x, y) in &[(3.0, 3.0), (7.0, 3.0), (7.0, 7.0), (3.0, 7.0)] {
+            hole.push_endpoint(Vector2F::new(x, y));
+        }
+        hole.close();
+        outline.push_contour(hole);
+
+        assert!(outline.contains_point(Vector2F::new(1.0, 5.0), FillRule::EvenOdd));
+        assert!(!outline.contains_point(Vector2F::new(5.0, 5.0), FillRule::EvenOdd));
+        assert!(outline.contains_point(Vector2F::new(5.0, 5.0), FillRule::Winding));
+        assert!(!outline.contains_point(Vector2F::new(11.0, 5.0), FillRule::Winding));
+    }
+
+    #[test]
+    fn test_boolean_ops() {
+        let a = polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        let b = polygon(&[(5.0, 5.0), (15.0, 5.0), (15.0, 15.0), (5.0, 15.0)]);
+        let winding = FillRule::Winding;
+
+        // `area` sums signed areas, so these also check that the contours wind consistently.
+        assert!((area(&a.union(winding, &b, winding)) - 175.0).abs() < 0.01);
+        assert!((area(&a.intersection(winding, &b, winding)) - 25.0).abs() < 0.01);
+        assert!((area(&a.difference(winding, &b, winding)) - 75.0).abs() < 0.01);
+        assert!((area(&a.xor(winding, &b, winding)) - 150.0).abs() < 0.01);
+
+        // Under the even-odd rule, the overlap of two contours is a hole.
+        let mut both = a.clone();
+        both.push_contour(b.contours()[0].clone());
+        let frame = polygon(&[(-1.0, -1.0), (16.0, -1.0), (16.0, 16.0), (-1.0, 16.0)]);
+        let even_odd = both.intersection(FillRule::EvenOdd, &frame, winding);
+        assert!((area(&even_odd) - 150.0).abs() < 0.01);
+        assert!(!even_odd.contains_point(Vector2F::new(7.5, 7.5), winding));
+        let nonzero = both.intersection(winding, &frame, winding);
+        assert!((area(&nonzero) - 175.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_non_finite_input() {
+        let triangle = polygon(&[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)]);
+        // Scaling by infinity turns zeros into NaNs.
+        let mut broken = polygon(&[(0.0, 5.0), (10.0, 5.0), (0.0, 15.0)]);
+        broken.transform(&Transform2F::from_scale(Vector2F::new(f32::INFINITY, 1.0)));
+        let winding = FillRule::Winding;
+
+        // Nothing useful can come of these, but they mustn't panic.
+        let mut clipped = triangle.clone();
+        clipped.clip_against_outline(winding, &broken, winding);
+        let mut clipped = broken.clone();
+        clipped.clip_against_outline(winding, &triangle, winding);
+        triangle.union(winding, &broken, winding);
+        broken.xor(winding, &triangle, winding);
+    }
+}
@@ -9,10 +9,13 @@
 // except according to those terms.
 
 use pathfinder_simd::default::F32x4;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt::{self, Debug, Formatter};
 
 // TODO(pcwalton): Maybe this should be a u32?
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ColorU {
     pub r: u8,
     pub g: u8,
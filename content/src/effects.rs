@@ -0,0 +1,134 @@
+// pathfinder/content/src/effects.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Special effects that can be applied to paths.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How a path is composited onto the content beneath it.
+///
+/// The Porter-Duff operators and the separable blend modes are those of the W3C "Compositing and
+/// Blending" specification, as used by HTML canvas `globalCompositeOperation` and SVG
+/// `mix-blend-mode`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BlendMode {
+    // Porter-Duff operators.
+    SrcOver,
+    SrcIn,
+    SrcOut,
+    SrcAtop,
+    DestOver,
+    DestIn,
+    DestOut,
+    DestAtop,
+    Xor,
+    Lighter,
+    Copy,
+
+    // Separable blend modes, composited with source-over.
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+}
+
+impl Default for BlendMode {
+    #[inline]
+    fn default() -> BlendMode {
+        BlendMode::SrcOver
+    }
+}
+
+impl BlendMode {
+    /// Returns true if this is a Porter-Duff operator, which depends only on the alpha of the
+    /// content beneath rather than on its color.
+    #[inline]
+    pub fn is_porter_duff(self) -> bool {
+        match self {
+            BlendMode::SrcOver |
+            BlendMode::SrcIn |
+            BlendMode::SrcOut |
+            BlendMode::SrcAtop |
+            BlendMode::DestOver |
+            BlendMode::DestIn |
+            BlendMode::DestOut |
+            BlendMode::DestAtop |
+            BlendMode::Xor |
+            BlendMode::Lighter |
+            BlendMode::Copy => true,
+            BlendMode::Multiply |
+            BlendMode::Screen |
+            BlendMode::Overlay |
+            BlendMode::Darken |
+            BlendMode::Lighten |
+            BlendMode::ColorDodge |
+            BlendMode::ColorBurn |
+            BlendMode::HardLight |
+            BlendMode::SoftLight |
+            BlendMode::Difference |
+            BlendMode::Exclusion => false,
+        }
+    }
+
+    /// Returns true if this is a Porter-Duff operator that changes the content beneath even where
+    /// the source is transparent, as `SrcIn` and `Copy` do.
+    ///
+    /// Such operators can't be drawn by folding the coverage of a path into the alpha of its
+    /// paint, or the transparent parts of antialiased edges would clear the content beneath.
+    #[inline]
+    pub fn is_unbounded(self) -> bool {
+        match self {
+            BlendMode::SrcIn |
+            BlendMode::SrcOut |
+            BlendMode::DestIn |
+            BlendMode::DestAtop |
+            BlendMode::Copy => true,
+            BlendMode::SrcOver |
+            BlendMode::SrcAtop |
+            BlendMode::DestOver |
+            BlendMode::DestOut |
+            BlendMode::Xor |
+            BlendMode::Lighter |
+            BlendMode::Multiply |
+            BlendMode::Screen |
+            BlendMode::Overlay |
+            BlendMode::Darken |
+            BlendMode::Lighten |
+            BlendMode::ColorDodge |
+            BlendMode::ColorBurn |
+            BlendMode::HardLight |
+            BlendMode::SoftLight |
+            BlendMode::Difference |
+            BlendMode::Exclusion => false,
+        }
+    }
+
+    /// Returns true if drawing with this mode requires reading the color of the content beneath,
+    /// rather than leaving the compositing to the blending hardware.
+    #[inline]
+    pub fn needs_readable_framebuffer(self) -> bool {
+        !self.is_porter_duff() || self.is_unbounded()
+    }
+
+    /// Returns true if opaque content drawn with this mode completely hides whatever is beneath.
+    #[inline]
+    pub fn occludes_backdrop(self) -> bool {
+        self == BlendMode::SrcOver || self == BlendMode::Copy
+    }
+}
@@ -0,0 +1,42 @@
+// pathfinder/content/src/fill.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Fill rules, which determine which points are inside a path.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The rule used to decide whether a point lies inside a path, given the winding number of the
+/// path around it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FillRule {
+    /// Points with a nonzero winding number are inside.
+    Winding,
+    /// Points with an odd winding number are inside.
+    EvenOdd,
+}
+
+impl FillRule {
+    #[inline]
+    pub fn winding_is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::Winding => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+impl Default for FillRule {
+    #[inline]
+    fn default() -> FillRule {
+        FillRule::Winding
+    }
+}
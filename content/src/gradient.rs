@@ -0,0 +1,335 @@
+// pathfinder/content/src/gradient.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Linear and radial gradients.
+
+use crate::color::ColorU;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::util;
+use pathfinder_geometry::vector::Vector2F;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Gradient {
+    geometry: GradientGeometry,
+    stops: Vec<ColorStop>,
+    /// Transforms gradient space to scene space.
+    transform: Transform2F,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GradientGeometry {
+    /// A gradient that varies along the given line.
+    Linear(LineSegment2F),
+    /// A gradient that varies between two circles, as in the HTML canvas
+    /// `createRadialGradient()` method.
+    ///
+    /// The line connects the centers of the start and end circles.
+    Radial { line: LineSegment2F, start_radius: f32, end_radius: f32 },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ColorStop {
+    pub offset: f32,
+    pub color: ColorU,
+}
+
+impl Gradient {
+    #[inline]
+    pub fn linear(line: LineSegment2F) -> Gradient {
+        Gradient {
+            geometry: GradientGeometry::Linear(line),
+            stops: vec![],
+            transform: Transform2F::default(),
+        }
+    }
+
+    #[inline]
+    pub fn radial(line: LineSegment2F, start_radius: f32, end_radius: f32) -> Gradient {
+        Gradient {
+            geometry: GradientGeometry::Radial { line, start_radius, end_radius },
+            stops: vec![],
+            transform: Transform2F::default(),
+        }
+    }
+
+    /// Adds a color stop. Stops at the same offset keep their insertion order, so adding two
+    /// stops at one offset produces a hard edge.
+    pub fn add_color_stop(&mut self, offset: f32, color: ColorU) {
+        let stop = ColorStop { offset: util::clamp(offset, 0.0, 1.0), color };
+        let index = self.stops.iter().position(|other| other.offset > stop.offset);
+        match index {
+            Some(index) => self.stops.insert(index, stop),
+            None => self.stops.push(stop),
+        }
+    }
+
+    #[inline]
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    #[inline]
+    pub fn geometry(&self) -> &GradientGeometry {
+        &self.geometry
+    }
+
+    #[inline]
+    pub fn transform(&self) -> Transform2F {
+        self.transform
+    }
+
+    /// Applies `transform` after the current gradient transform.
+    #[inline]
+    pub fn apply_transform(&mut self, transform: &Transform2F) {
+        self.transform = *transform * self.transform;
+    }
+
+    /// Multiplies the alpha of every color stop by `alpha`.
+    pub fn apply_opacity(&mut self, alpha: f32) {
+        for stop in &mut self.stops {
+            stop.color.a = (stop.color.a as f32 * alpha).round() as u8;
+        }
+    }
+
+    /// Returns the color at parameter `t` along the gradient, clamping to the end stops. A NaN
+    /// `t`, such as a singular gradient transform yields, is transparent.
+    pub fn sample(&self, t: f32) -> ColorU {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) if !t.is_nan() => (first, last),
+            _ => return ColorU::transparent_black(),
+        };
+        if t <= first.offset {
+            return first.color;
+        }
+        if t >= last.offset {
+            return last.color;
+        }
+
+        let next_index = self.stops.iter().position(|stop| stop.offset > t).unwrap();
+        let (prev, next) = (&self.stops[next_index - 1], &self.stops[next_index]);
+        let denom = next.offset - prev.offset;
+        if denom <= 0.0 {
+            return next.color;
+        }
+        let local_t = (t - prev.offset) / denom;
+        prev.color.to_f32().lerp(next.color.to_f32(), local_t).to_u8()
+    }
+
+    /// Returns the color of the gradient at the given point in scene space.
+    pub fn color_at(&self, point: Vector2F) -> ColorU {
+        match self.geometry.t_at(self.transform.inverse() * point) {
+            Some(t) => self.sample(t),
+            None => ColorU::transparent_black(),
+        }
+    }
+
+    #[inline]
+    pub fn is_opaque(&self) -> bool {
+        !self.stops.is_empty() && self.stops.iter().all(|stop| stop.color.a == 255)
+    }
+
+    #[inline]
+    pub fn is_fully_transparent(&self) -> bool {
+        self.stops.iter().all(|stop| stop.color.is_fully_transparent())
+    }
+}
+
+impl GradientGeometry {
+    /// Returns the parameter along the gradient at the given point in gradient space, or `None`
+    /// where the gradient isn't defined.
+    pub fn t_at(&self, point: Vector2F) -> Option<f32> {
+        match *self {
+            GradientGeometry::Linear(line) => {
+                let vector = line.vector();
+                let square_length = vector.square_length();
+                if square_length == 0.0 {
+                    return None;
+                }
+                Some((point - line.from()).dot(vector) / square_length)
+            }
+            GradientGeometry::Radial { line, start_radius, end_radius } => {
+                radial_t(point, line, start_radius, end_radius)
+            }
+        }
+    }
+}
+
+// Solves for the largest `t` such that `point` lies on the circle interpolated between the start
+// and end circles with a nonnegative radius. This is the two-point conical gradient described in
+// the HTML canvas specification.
+fn radial_t(point: Vector2F, line: LineSegment2F, start_radius: f32, end_radius: f32)
+            -> Option<f32> {
+    let center_delta = line.vector();
+    let radius_delta = end_radius - start_radius;
+    let point_delta = point - line.from();
+
+    let a = center_delta.square_length() - radius_delta * radius_delta;
+    let b = point_delta.dot(center_delta) + start_radius * radius_delta;
+    let c = point_delta.square_length() - start_radius * start_radius;
+
+    if a.abs() < 1e-6 {
+        if b == 0.0 {
+            return None;
+        }
+        let t = c / (2.0 * b);
+        return if start_radius + t * radius_delta >= 0.0 { Some(t) } else { None };
+    }
+
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let sqrt_discriminant = discriminant.sqrt();
+    let (t0, t1) = ((b + sqrt_discriminant) / a, (b - sqrt_discriminant) / a);
+    let (t_max, t_min) = if t0 >= t1 { (t0, t1) } else { (t1, t0) };
+    if start_radius + t_max * radius_delta >= 0.0 {
+        Some(t_max)
+    } else if start_radius + t_min * radius_delta >= 0.0 {
+        Some(t_min)
+    } else {
+        None
+    }
+}
+
+// Floats are compared by their bits, in both `eq()` and `hash()`, so that the two agree even for
+// zeroes and NaNs.
+impl PartialEq for Gradient {
+    fn eq(&self, other: &Gradient) -> bool {
+        self.stops.len() == other.stops.len() &&
+            self.stops.iter().zip(other.stops.iter()).all(|(a, b)| {
+                a.offset.to_bits() == b.offset.to_bits() && a.color == b.color
+            }) &&
+            transform_bits(&self.transform) == transform_bits(&other.transform) &&
+            geometry_bits(&self.geometry) == geometry_bits(&other.geometry)
+    }
+}
+
+impl Eq for Gradient {}
+
+impl Hash for Gradient {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        geometry_bits(&self.geometry).hash(state);
+        for stop in &self.stops {
+            stop.offset.to_bits().hash(state);
+            stop.color.hash(state);
+        }
+        transform_bits(&self.transform).hash(state);
+    }
+}
+
+// Returns the kind of the geometry, followed by the bits of its line and radii.
+fn geometry_bits(geometry: &GradientGeometry) -> (u8, [u32; 6]) {
+    let (kind, line, start_radius, end_radius) = match *geometry {
+        GradientGeometry::Linear(line) => (0, line, 0.0, 0.0),
+        GradientGeometry::Radial { line, start_radius, end_radius } => {
+            (1, line, start_radius, end_radius)
+        }
+    };
+    (kind, [
+        line.from_x().to_bits(),
+        line.from_y().to_bits(),
+        line.to_x().to_bits(),
+        line.to_y().to_bits(),
+        start_radius.to_bits(),
+        end_radius.to_bits(),
+    ])
+}
+
+pub(crate) fn transform_bits(transform: &Transform2F) -> [u32; 6] {
+    let matrix = transform.matrix.0;
+    [
+        matrix[0].to_bits(),
+        matrix[1].to_bits(),
+        matrix[2].to_bits(),
+        matrix[3].to_bits(),
+        transform.vector.x().to_bits(),
+        transform.vector.y().to_bits(),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::color::ColorU;
+    use crate::gradient::Gradient;
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+    use std::collections::hash_map::DefaultHasher;
+    use std::f32;
+    use std::hash::{Hash, Hasher};
+
+    fn black_to_white(mut gradient: Gradient) -> Gradient {
+        gradient.add_color_stop(0.0, ColorU::black());
+        gradient.add_color_stop(1.0, ColorU { r: 255, g: 255, b: 255, a: 255 });
+        gradient
+    }
+
+    #[test]
+    fn test_linear_gradient() {
+        let line = LineSegment2F::new(Vector2F::new(0.0, 0.0), Vector2F::new(100.0, 0.0));
+        let gradient = black_to_white(Gradient::linear(line));
+        assert_eq!(gradient.color_at(Vector2F::new(-10.0, 5.0)), ColorU::black());
+        assert_eq!(gradient.color_at(Vector2F::new(50.0, 30.0)).r, 128);
+        assert_eq!(gradient.color_at(Vector2F::new(200.0, 0.0)).r, 255);
+    }
+
+    #[test]
+    fn test_radial_gradient() {
+        let center = Vector2F::new(50.0, 50.0);
+        let gradient = black_to_white(Gradient::radial(LineSegment2F::new(center, center),
+                                                       0.0,
+                                                       50.0));
+        assert_eq!(gradient.color_at(center), ColorU::black());
+        assert_eq!(gradient.color_at(Vector2F::new(75.0, 50.0)).r, 128);
+        assert_eq!(gradient.color_at(Vector2F::new(50.0, 150.0)).r, 255);
+    }
+
+    #[test]
+    fn test_non_finite_t() {
+        let line = LineSegment2F::new(Vector2F::new(0.0, 0.0), Vector2F::new(100.0, 0.0));
+        let mut gradient = black_to_white(Gradient::linear(line));
+        assert_eq!(gradient.sample(f32::NAN), ColorU::transparent_black());
+        assert_eq!(gradient.sample(f32::NEG_INFINITY), ColorU::black());
+        assert_eq!(gradient.sample(f32::INFINITY).r, 255);
+
+        // A singular transform has no inverse to find the parameter with.
+        gradient.apply_transform(&Transform2F::from_scale(Vector2F::splat(0.0)));
+        assert_eq!(gradient.color_at(Vector2F::new(50.0, 0.0)), ColorU::transparent_black());
+    }
+
+    #[test]
+    fn test_gradient_eq_and_hash_agree() {
+        let hash = |gradient: &Gradient| {
+            let mut hasher = DefaultHasher::new();
+            gradient.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let line = LineSegment2F::new(Vector2F::new(0.0, 0.0), Vector2F::new(100.0, 0.0));
+        let negative_zero_line = LineSegment2F::new(Vector2F::new(-0.0, 0.0),
+                                                    Vector2F::new(100.0, 0.0));
+        let a = black_to_white(Gradient::linear(line));
+        let b = black_to_white(Gradient::linear(negative_zero_line));
+        assert_ne!(a, b);
+        assert_ne!(hash(&a), hash(&b));
+
+        let nan_line = LineSegment2F::new(Vector2F::new(f32::NAN, 0.0), Vector2F::new(1.0, 0.0));
+        let c = black_to_white(Gradient::linear(nan_line));
+        assert_eq!(c, c.clone());
+        assert_eq!(hash(&c), hash(&c.clone()));
+    }
+}
@@ -20,10 +20,16 @@ extern crate log;
 pub mod clip;
 pub mod color;
 pub mod dash;
+pub mod effects;
+pub mod fill;
+pub mod gradient;
 pub mod orientation;
 pub mod outline;
+pub mod pattern;
 pub mod segment;
 pub mod stroke;
 pub mod transform;
 
 mod dilation;
+#[cfg(feature = "serde")]
+mod serialization;
@@ -10,8 +10,9 @@
 
 //! A compressed in-memory representation of paths.
 
-use crate::clip::{self, ContourPolygonClipper, ContourRectClipper};
+use crate::clip::{self, BooleanOp, ContourPolygonClipper, ContourRectClipper, OutlineClipper};
 use crate::dilation::ContourDilator;
+use crate::fill::FillRule;
 use crate::orientation::Orientation;
 use crate::segment::{Segment, SegmentFlags, SegmentKind};
 use pathfinder_geometry::line_segment::LineSegment2F;
@@ -19,6 +20,7 @@ use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::transform3d::Perspective;
 use pathfinder_geometry::unit_vector::UnitVector;
+use pathfinder_geometry::util;
 use pathfinder_geometry::vector::Vector2F;
 use std::f32::consts::PI;
 use std::fmt::{self, Debug, Formatter};
@@ -164,6 +166,28 @@ impl Outline {
         self.bounds = self.bounds.dilate(amount);
     }
 
+    /// Replaces every curve with line segments that stray no further than `tolerance` from it.
+    pub fn flatten(&mut self, tolerance: f32) {
+        let mut new_bounds = None;
+        for contour in &mut self.contours {
+            contour.flatten(tolerance);
+            contour.update_bounds(&mut new_bounds);
+        }
+        self.bounds = new_bounds.unwrap_or_default();
+    }
+
+    /// Removes detail no larger than `tolerance`: segments that short are dropped, curves that
+    /// nearly straight become lines, and runs of lines that stay that close to a single line are
+    /// merged into it.
+    pub fn simplify(&mut self, tolerance: f32) {
+        let mut new_bounds = None;
+        for contour in &mut self.contours {
+            contour.simplify(tolerance);
+            contour.update_bounds(&mut new_bounds);
+        }
+        self.bounds = new_bounds.unwrap_or_default();
+    }
+
     pub fn prepare_for_tiling(&mut self, view_box: RectF) {
         self.contours
             .iter_mut()
@@ -202,6 +226,92 @@ impl Outline {
             self.push_contour(ContourRectClipper::new(clip_rect, contour).clip());
         }
     }
+
+    /// Clips this outline, filled with `fill_rule`, to the interior of `clip`, which may be any
+    /// shape and is filled with `clip_fill_rule`. The result is valid under `fill_rule`.
+    pub fn clip_against_outline(&mut self,
+                                fill_rule: FillRule,
+                                clip: &Outline,
+                                clip_fill_rule: FillRule) {
+        if !self.bounds.intersects(clip.bounds) {
+            *self = Outline::new();
+            return;
+        }
+        if let Some(clip_rect) = clip.as_rect() {
+            self.clip_against_rect(clip_rect);
+            return;
+        }
+
+        *self = OutlineClipper::new(self, fill_rule, clip, clip_fill_rule, BooleanOp::Intersection)
+            .clip();
+    }
+
+    /// Returns the area covered by either this outline, filled with `fill_rule`, or `other`,
+    /// filled with `other_fill_rule`.
+    ///
+    /// Like the other boolean operations, this flattens curves. The result has no overlapping
+    /// contours, and all of its contours wind the same way except those around holes, so it is
+    /// filled identically under either fill rule.
+    pub fn union(&self, fill_rule: FillRule, other: &Outline, other_fill_rule: FillRule)
+                 -> Outline {
+        OutlineClipper::new(self, fill_rule, other, other_fill_rule, BooleanOp::Union).clip()
+    }
+
+    /// Returns the area covered by both this outline and `other`.
+    pub fn intersection(&self, fill_rule: FillRule, other: &Outline, other_fill_rule: FillRule)
+                        -> Outline {
+        if !self.bounds.intersects(other.bounds) {
+            return Outline::new();
+        }
+        OutlineClipper::new(self, fill_rule, other, other_fill_rule, BooleanOp::Intersection)
+            .clip()
+    }
+
+    /// Returns the area covered by this outline but not by `other`.
+    pub fn difference(&self, fill_rule: FillRule, other: &Outline, other_fill_rule: FillRule)
+                      -> Outline {
+        OutlineClipper::new(self, fill_rule, other, other_fill_rule, BooleanOp::Difference).clip()
+    }
+
+    /// Returns the area covered by exactly one of this outline and `other`.
+    pub fn xor(&self, fill_rule: FillRule, other: &Outline, other_fill_rule: FillRule)
+               -> Outline {
+        OutlineClipper::new(self, fill_rule, other, other_fill_rule, BooleanOp::Xor).clip()
+    }
+
+    /// Returns true if `point` lies in the interior of this outline as determined by `fill_rule`.
+    ///
+    /// Curves are flattened first, so points very close to a curved edge may be misclassified.
+    pub fn contains_point(&self, point: Vector2F, fill_rule: FillRule) -> bool {
+        if !self.bounds.contains_point(point) {
+            return false;
+        }
+        fill_rule.winding_is_inside(self.winding_number(point))
+    }
+
+    /// Returns the winding number of this outline around `point`.
+    pub fn winding_number(&self, point: Vector2F) -> i32 {
+        clip::flatten_outline(self).into_iter().map(|edge| clip::edge_winding(edge, point)).sum()
+    }
+
+    // Returns the rectangle this outline describes, if it consists of a single axis-aligned
+    // rectangular contour.
+    fn as_rect(&self) -> Option<RectF> {
+        if self.contours.len() != 1 {
+            return None;
+        }
+        let contour = &self.contours[0];
+        if contour.len() != 4 || (0..4).any(|index| !contour.point_is_endpoint(index)) {
+            return None;
+        }
+        for index in 0..4 {
+            let (from, to) = (contour.position_of(index), contour.position_of((index + 1) % 4));
+            if from.x() != to.x() && from.y() != to.y() {
+                return None;
+            }
+        }
+        Some(self.bounds)
+    }
 }
 
 impl Debug for Outline {
@@ -242,6 +352,8 @@ impl Contour {
         )
     }
 
+    /// Returns the segments of this contour. A closed contour ends with a line back to its first
+    /// point, unless it has more than one point and the last is already there.
     #[inline]
     pub fn iter(&self) -> ContourIter {
         ContourIter {
@@ -550,6 +662,130 @@ impl Contour {
         self.bounds = self.bounds.dilate(amount);
     }
 
+    /// Replaces every curve in this contour with line segments that stray no further than
+    /// `tolerance` from it.
+    pub fn flatten(&mut self, tolerance: f32) {
+        if self.is_empty() {
+            return;
+        }
+
+        let contour = self.take();
+        self.push_endpoint(contour.position_of(0));
+        let mut edges = vec![];
+        for segment in contour.explicit_segments() {
+            edges.clear();
+            clip::flatten_segment(&segment, tolerance, &mut edges);
+            for edge in &edges {
+                self.push_endpoint(edge.to());
+            }
+        }
+        self.closed = contour.closed;
+    }
+
+    /// Removes detail no larger than `tolerance` from this contour. See `Outline::simplify()`.
+    pub fn simplify(&mut self, tolerance: f32) {
+        if self.is_empty() {
+            return;
+        }
+
+        // Drop degenerate segments, reconnecting the rest, and straighten nearly flat curves. The
+        // line closing the contour is included so that it can be merged too.
+        let contour = self.take();
+        let mut segments = vec![];
+        let mut from = contour.position_of(0);
+        for mut segment in contour.iter() {
+            segment.baseline.set_from(from);
+            if segment_is_within(&segment, from, tolerance) {
+                continue;
+            }
+            if !segment.is_line() && segment.to_cubic().as_cubic_segment().is_flat(tolerance) {
+                segment = Segment::line(segment.baseline);
+            }
+            from = segment.baseline.to();
+            segments.push(segment);
+        }
+
+        // Merge runs of lines, remembering the points that were merged away so that every one of
+        // them can be checked against the line replacing them.
+        let mut merged: Vec<(Segment, Vec<Vector2F>)> = vec![];
+        for segment in segments {
+            if let Some((prev_segment, merged_points)) = merged.last_mut() {
+                if let Some(line) = merge_lines(prev_segment, merged_points, &segment, tolerance) {
+                    merged_points.push(prev_segment.baseline.to());
+                    *prev_segment = line;
+                    continue;
+                }
+            }
+            merged.push((segment, vec![]));
+        }
+
+        // A closed contour may also have started partway along a straight run.
+        if contour.closed && merged.len() > 2 {
+            let (last_segment, mut last_merged_points) = merged.pop().unwrap();
+            let (first_segment, first_merged_points) = &mut merged[0];
+            last_merged_points.extend(first_merged_points.iter().cloned());
+            match merge_lines(&last_segment, &last_merged_points, first_segment, tolerance) {
+                Some(line) => *first_segment = line,
+                None => merged.push((last_segment, last_merged_points)),
+            }
+        }
+
+        if let Some(&(first_segment, _)) = merged.first() {
+            self.push_endpoint(first_segment.baseline.from());
+        } else {
+            self.push_endpoint(contour.position_of(0));
+        }
+        for (segment_index, &(segment, _)) in merged.iter().enumerate() {
+            // The final line of a closed contour is implicit.
+            if contour.closed && segment_index + 1 == merged.len() && segment.is_line() {
+                break;
+            }
+            self.push_segment_without_from(&segment);
+        }
+        self.closed = contour.closed;
+    }
+
+    /// Returns a copy of this contour in which every segment, including the line that closes it,
+    /// is a cubic Bézier curve.
+    pub fn to_cubics(&self) -> Contour {
+        let mut contour = Contour::new();
+        if self.is_empty() {
+            return contour;
+        }
+
+        contour.push_endpoint(self.position_of(0));
+        for segment in self.explicit_segments() {
+            contour.push_segment_without_from(&segment.to_cubic());
+        }
+        if self.closed {
+            let closing_line = LineSegment2F::new(self.position_of_last(1), self.position_of(0));
+            if !closing_line.is_zero_length() {
+                contour.push_segment_without_from(&Segment::line(closing_line).to_cubic());
+            }
+        }
+        contour.closed = self.closed;
+        contour
+    }
+
+    // Returns the segments of this contour, leaving out the line that implicitly closes it.
+    fn explicit_segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        let has_closing_line = self.closed && !self.is_empty() &&
+            (self.len() == 1 || self.position_of_last(1) != self.position_of(0));
+        let closing_line_count = if has_closing_line { 1 } else { 0 };
+        self.iter().take(self.iter().count() - closing_line_count)
+    }
+
+    // Appends a segment that starts at the last point of this contour.
+    fn push_segment_without_from(&mut self, segment: &Segment) {
+        if segment.is_line() {
+            self.push_endpoint(segment.baseline.to());
+        } else if segment.is_quadratic() {
+            self.push_quadratic(segment.ctrl.from(), segment.baseline.to());
+        } else {
+            self.push_cubic(segment.ctrl.from(), segment.ctrl.to(), segment.baseline.to());
+        }
+    }
+
     fn prepare_for_tiling(&mut self, view_box: RectF) {
         // Snap points to the view box bounds. This mops up floating point error from the clipping
         // process.
@@ -801,6 +1037,9 @@ impl<'a> Iterator for ContourIter<'a> {
         if self.index == contour.len() {
             let point1 = contour.position_of(0);
             self.index += 1;
+            if point1 == point0 && contour.len() > 1 {
+                return None;
+            }
             return Some(Segment::line(LineSegment2F::new(point0, point1)));
         }
 
@@ -829,6 +1068,44 @@ impl<'a> Iterator for ContourIter<'a> {
     }
 }
 
+// Returns true if every point of the segment lies within `tolerance` of `point`.
+fn segment_is_within(segment: &Segment, point: Vector2F, tolerance: f32) -> bool {
+    let is_near = |other: Vector2F| (other - point).square_length() <= tolerance * tolerance;
+    is_near(segment.baseline.from()) && is_near(segment.baseline.to()) &&
+        (segment.is_line() || is_near(segment.ctrl.from())) &&
+        (segment.is_line() || segment.is_quadratic() || is_near(segment.ctrl.to()))
+}
+
+// Returns the line replacing `prev_segment` and `segment` if both are lines and every point on
+// the run lies within `tolerance` of it.
+fn merge_lines(prev_segment: &Segment,
+               merged_points: &[Vector2F],
+               segment: &Segment,
+               tolerance: f32)
+               -> Option<Segment> {
+    if !prev_segment.is_line() || !segment.is_line() {
+        return None;
+    }
+    let line = LineSegment2F::new(prev_segment.baseline.from(), segment.baseline.to());
+    let joint = prev_segment.baseline.to();
+    if merged_points.iter().chain(Some(&joint)).all(|&point| {
+        distance_to_line_segment(line, point) <= tolerance
+    }) {
+        Some(Segment::line(line))
+    } else {
+        None
+    }
+}
+
+fn distance_to_line_segment(line: LineSegment2F, point: Vector2F) -> f32 {
+    let square_length = line.square_length();
+    if square_length == 0.0 {
+        return (point - line.from()).length();
+    }
+    let t = util::clamp((point - line.from()).dot(line.vector()) / square_length, 0.0, 1.0);
+    (point - line.sample(t)).length()
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ArcDirection {
     CW,
@@ -843,3 +1120,78 @@ pub(crate) fn union_rect(bounds: &mut RectF, new_point: Vector2F, first: bool) {
         *bounds = bounds.union_point(new_point)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::fill::FillRule;
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+
+    #[test]
+    fn test_flatten() {
+        let mut contour = Contour::new();
+        contour.push_ellipse(&Transform2F::from_scale(Vector2F::splat(10.0)));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline.flatten(0.01);
+
+        let contour = &outline.contours()[0];
+        assert!(contour.is_closed());
+        assert!(contour.iter().all(|segment| segment.is_line()));
+        for point_index in 0..contour.len() {
+            let radius = contour.position_of(point_index).length();
+            assert!((radius - 10.0).abs() <= 0.02);
+        }
+        assert!((outline.bounds().size().x() - 20.0).abs() <= 0.02);
+    }
+
+    #[test]
+    fn test_simplify() {
+        // A square with an extra point partway along each side, starting in the middle of one, and
+        // a duplicated corner.
+        let mut contour = Contour::new();
+        for &(x, y) in &[(5.0, 0.0), (10.0, 0.0), (10.0, 4.0), (10.001, 7.0), (10.0, 10.0),
+                         (10.0, 10.0), (0.0, 10.0), (0.0, 3.0), (0.0, 0.0)] {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline.simplify(0.01);
+
+        let contour = &outline.contours()[0];
+        assert!(contour.is_closed());
+        assert_eq!(contour.len(), 4);
+        assert!(outline.contains_point(Vector2F::new(9.0, 9.0), FillRule::Winding));
+
+        // Lines that turn back on themselves aren't merged.
+        let mut contour = Contour::new();
+        for &(x, y) in &[(0.0, 0.0), (10.0, 0.0), (5.0, 0.0)] {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        contour.simplify(0.01);
+        assert_eq!(contour.len(), 3);
+    }
+
+    #[test]
+    fn test_to_cubics() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(Vector2F::new(0.0, 0.0));
+        contour.push_quadratic(Vector2F::new(5.0, 10.0), Vector2F::new(10.0, 0.0));
+        contour.push_endpoint(Vector2F::new(10.0, -5.0));
+        contour.close();
+
+        let cubics = contour.to_cubics();
+        assert!(cubics.is_closed());
+        assert_eq!(cubics.iter().count(), 3);
+        assert!(cubics.iter().all(|segment| segment.is_cubic()));
+        for (segment, cubic) in contour.iter().zip(cubics.iter()) {
+            for t_num in 0..=4 {
+                let t = t_num as f32 / 4.0;
+                assert!((segment.sample(t) - cubic.sample(t)).length() < 0.001);
+            }
+        }
+    }
+}
@@ -0,0 +1,334 @@
+// pathfinder/content/src/pattern.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Raster image patterns.
+
+use crate::color::{ColorF, ColorU};
+use crate::gradient::transform_bits;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_simd::default::F32x4;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// An RGBA image, stored in row-major order with straight (non-premultiplied) alpha.
+///
+/// Cloning an image is cheap, as the pixels are reference counted.
+#[derive(Clone)]
+pub struct Image {
+    size: Vector2I,
+    pixels: Arc<Vec<ColorU>>,
+    pixels_hash: u64,
+    is_opaque: bool,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pattern {
+    image: Image,
+    /// Transforms pattern space, where one unit is one image pixel, to scene space.
+    transform: Transform2F,
+    repeat: RepeatMode,
+    /// Multiplied into the alpha of every pixel.
+    opacity: f32,
+}
+
+/// How a pattern repeats, as in the HTML canvas `createPattern()` method.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RepeatMode {
+    Repeat,
+    RepeatX,
+    RepeatY,
+    NoRepeat,
+}
+
+impl Image {
+    /// Creates an image from RGBA pixels in row-major order.
+    ///
+    /// Panics if the number of pixels does not match the size.
+    pub fn new(size: Vector2I, pixels: Arc<Vec<ColorU>>) -> Image {
+        assert_eq!(size.x().max(0) as usize * size.y().max(0) as usize, pixels.len());
+        let mut hasher = DefaultHasher::new();
+        pixels.hash(&mut hasher);
+        let pixels_hash = hasher.finish();
+        let is_opaque = pixels.iter().all(|pixel| pixel.a == 255);
+        Image { size, pixels, pixels_hash, is_opaque }
+    }
+
+    /// Creates an image from tightly packed 8-bit RGBA data, as produced by most image decoders.
+    ///
+    /// Panics if the data isn't exactly four bytes for each pixel.
+    pub fn from_rgba8(size: Vector2I, data: &[u8]) -> Image {
+        assert!(data.chunks_exact(4).remainder().is_empty(),
+                "RGBA8 image data must be a whole number of pixels!");
+        let pixel_count = size.x().max(0) as usize * size.y().max(0) as usize;
+        assert!(data.len() == pixel_count * 4,
+                "RGBA8 image data must have four bytes for each pixel of the image!");
+        let pixels = data.chunks(4).map(|texel| {
+            ColorU { r: texel[0], g: texel[1], b: texel[2], a: texel[3] }
+        }).collect();
+        Image::new(size, Arc::new(pixels))
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vector2I {
+        self.size
+    }
+
+    #[inline]
+    pub fn pixels(&self) -> &Arc<Vec<ColorU>> {
+        &self.pixels
+    }
+
+    #[inline]
+    pub fn is_opaque(&self) -> bool {
+        self.is_opaque
+    }
+
+    #[inline]
+    fn pixel(&self, position: Vector2I) -> ColorU {
+        self.pixels[(position.y() * self.size.x() + position.x()) as usize]
+    }
+}
+
+impl PartialEq for Image {
+    #[inline]
+    fn eq(&self, other: &Image) -> bool {
+        self.size == other.size &&
+            self.pixels_hash == other.pixels_hash &&
+            (Arc::ptr_eq(&self.pixels, &other.pixels) || self.pixels == other.pixels)
+    }
+}
+
+impl Eq for Image {}
+
+impl Hash for Image {
+    #[inline]
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.size.x().hash(state);
+        self.size.y().hash(state);
+        self.pixels_hash.hash(state);
+    }
+}
+
+impl Debug for Image {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "Image({}x{})", self.size.x(), self.size.y())
+    }
+}
+
+impl Pattern {
+    #[inline]
+    pub fn new(image: Image, repeat: RepeatMode) -> Pattern {
+        Pattern { image, transform: Transform2F::default(), repeat, opacity: 1.0 }
+    }
+
+    #[inline]
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    #[inline]
+    pub fn repeat(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    #[inline]
+    pub fn transform(&self) -> Transform2F {
+        self.transform
+    }
+
+    /// Replaces the pattern transform, like `CanvasPattern.setTransform()`.
+    #[inline]
+    pub fn set_transform(&mut self, transform: &Transform2F) {
+        self.transform = *transform;
+    }
+
+    /// Applies `transform` after the current pattern transform.
+    #[inline]
+    pub fn apply_transform(&mut self, transform: &Transform2F) {
+        self.transform = *transform * self.transform;
+    }
+
+    #[inline]
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Multiplies the alpha of every pixel by `alpha`.
+    #[inline]
+    pub fn apply_opacity(&mut self, alpha: f32) {
+        self.opacity *= alpha;
+    }
+
+    /// Returns true if the pattern covers the whole plane with opaque pixels.
+    #[inline]
+    pub fn is_opaque(&self) -> bool {
+        self.repeat == RepeatMode::Repeat && self.image.is_opaque() && self.opacity >= 1.0
+    }
+
+    #[inline]
+    pub fn is_fully_transparent(&self) -> bool {
+        self.opacity <= 0.0
+    }
+
+    /// Returns the bilinearly-filtered color of the pattern at the given point in scene space.
+    ///
+    /// Along an axis that doesn't repeat, the pattern is transparent outside the image, and the
+    /// filter clamps to the edge pixels inside it so that the edges of the image stay sharp.
+    pub fn color_at(&self, point: Vector2F) -> ColorU {
+        let size = self.image.size();
+        if size.x() <= 0 || size.y() <= 0 {
+            return ColorU::transparent_black();
+        }
+
+        let (repeat_x, repeat_y) = self.repeat_axes();
+        let position = self.transform.inverse() * point;
+        if (!repeat_x && (position.x() < 0.0 || position.x() >= size.x() as f32)) ||
+                (!repeat_y && (position.y() < 0.0 || position.y() >= size.y() as f32)) {
+            return ColorU::transparent_black();
+        }
+
+        // Sample relative to pixel centers.
+        let position = position - Vector2F::splat(0.5);
+        let origin = position.floor();
+        let fract = position - origin;
+        let origin = origin.to_i32();
+
+        // Filter in premultiplied space to avoid dark fringes around transparent pixels.
+        let mut accumulator = ColorF::transparent_black();
+        for &(offset, weight) in &[
+            (Vector2I::new(0, 0), (1.0 - fract.x()) * (1.0 - fract.y())),
+            (Vector2I::new(1, 0), fract.x() * (1.0 - fract.y())),
+            (Vector2I::new(0, 1), (1.0 - fract.x()) * fract.y()),
+            (Vector2I::new(1, 1), fract.x() * fract.y()),
+        ] {
+            if weight == 0.0 {
+                continue;
+            }
+            let texel = origin + offset;
+            let texel = Vector2I::new(wrap_axis(texel.x(), size.x(), repeat_x),
+                                      wrap_axis(texel.y(), size.y(), repeat_y));
+            let color = self.image.pixel(texel).to_f32();
+            let premultiplied = ColorF::new(color.r() * color.a(),
+                                            color.g() * color.a(),
+                                            color.b() * color.a(),
+                                            color.a());
+            accumulator = ColorF(accumulator.0 + premultiplied.0 * F32x4::splat(weight));
+        }
+
+        let alpha = accumulator.a();
+        if alpha <= 0.0 {
+            return ColorU::transparent_black();
+        }
+        let inv_alpha = 1.0 / alpha;
+        ColorF::new(accumulator.r() * inv_alpha,
+                    accumulator.g() * inv_alpha,
+                    accumulator.b() * inv_alpha,
+                    alpha * self.opacity).to_u8()
+    }
+
+    /// Returns whether the pattern repeats horizontally and vertically.
+    #[inline]
+    pub fn repeat_axes(&self) -> (bool, bool) {
+        match self.repeat {
+            RepeatMode::Repeat => (true, true),
+            RepeatMode::RepeatX => (true, false),
+            RepeatMode::RepeatY => (false, true),
+            RepeatMode::NoRepeat => (false, false),
+        }
+    }
+}
+
+// As with gradients, floats are compared by their bits so that `eq()` and `hash()` agree.
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Pattern) -> bool {
+        self.image == other.image &&
+            transform_bits(&self.transform) == transform_bits(&other.transform) &&
+            self.repeat == other.repeat &&
+            self.opacity.to_bits() == other.opacity.to_bits()
+    }
+}
+
+impl Eq for Pattern {}
+
+impl Hash for Pattern {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.image.hash(state);
+        transform_bits(&self.transform).hash(state);
+        self.repeat.hash(state);
+        self.opacity.to_bits().hash(state);
+    }
+}
+
+// Maps a texel coordinate into the image, wrapping around if the axis repeats and clamping to the
+// edge otherwise.
+fn wrap_axis(value: i32, length: i32, repeat: bool) -> i32 {
+    if repeat {
+        value.rem_euclid(length)
+    } else {
+        value.max(0).min(length - 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::color::ColorU;
+    use crate::pattern::{Image, Pattern, RepeatMode};
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use std::sync::Arc;
+
+    fn checkerboard() -> Image {
+        let (black, white) = (ColorU::black(), ColorU { r: 255, g: 255, b: 255, a: 255 });
+        Image::new(Vector2I::new(2, 2), Arc::new(vec![black, white, white, black]))
+    }
+
+    #[test]
+    fn test_pattern_repeat_modes() {
+        let repeat = Pattern::new(checkerboard(), RepeatMode::Repeat);
+        assert_eq!(repeat.color_at(Vector2F::new(0.5, 0.5)), ColorU::black());
+        assert_eq!(repeat.color_at(Vector2F::new(3.5, 0.5)).r, 255);
+        assert_eq!(repeat.color_at(Vector2F::new(-1.5, -1.5)), ColorU::black());
+
+        let repeat_x = Pattern::new(checkerboard(), RepeatMode::RepeatX);
+        assert_eq!(repeat_x.color_at(Vector2F::new(4.5, 1.5)).r, 255);
+        assert!(repeat_x.color_at(Vector2F::new(0.5, 5.0)).is_fully_transparent());
+
+        let no_repeat = Pattern::new(checkerboard(), RepeatMode::NoRepeat);
+        assert!(no_repeat.color_at(Vector2F::new(10.0, 0.5)).is_fully_transparent());
+        assert!(no_repeat.color_at(Vector2F::new(0.5, -0.1)).is_fully_transparent());
+        // The filter doesn't fade the edges of the image.
+        assert_eq!(no_repeat.color_at(Vector2F::new(0.1, 0.1)), ColorU::black());
+    }
+
+    #[test]
+    fn test_image_from_rgba8() {
+        let image = Image::from_rgba8(Vector2I::new(2, 1), &[0, 0, 0, 255, 255, 255, 255, 128]);
+        assert_eq!(image.pixels()[1], ColorU { r: 255, g: 255, b: 255, a: 128 });
+        assert!(!image.is_opaque());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_image_from_rgba8_partial_pixel() {
+        Image::from_rgba8(Vector2I::new(1, 1), &[0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_image_from_rgba8_wrong_size() {
+        Image::from_rgba8(Vector2I::new(2, 2), &[0; 12]);
+    }
+}
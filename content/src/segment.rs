@@ -136,7 +136,6 @@ impl Segment {
     }
 
     // FIXME(pcwalton): We should basically never use this function.
-    // FIXME(pcwalton): Handle lines!
     #[inline]
     pub fn to_cubic(&self) -> Segment {
         if self.is_cubic() {
@@ -144,10 +143,15 @@ impl Segment {
         }
 
         let mut new_segment = *self;
-        let p1_2 = self.ctrl.from() + self.ctrl.from();
-        new_segment.ctrl =
-            LineSegment2F::new(self.baseline.from() + p1_2, p1_2 + self.baseline.to())
-                .scale(1.0 / 3.0);
+        if self.is_line() {
+            new_segment.ctrl = LineSegment2F::new(self.baseline.sample(1.0 / 3.0),
+                                                  self.baseline.sample(2.0 / 3.0));
+        } else {
+            let p1_2 = self.ctrl.from() + self.ctrl.from();
+            new_segment.ctrl =
+                LineSegment2F::new(self.baseline.from() + p1_2, p1_2 + self.baseline.to())
+                    .scale(1.0 / 3.0);
+        }
         new_segment.kind = SegmentKind::Cubic;
         new_segment
     }
@@ -0,0 +1,115 @@
+// pathfinder/content/src/serialization.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Serde support for types with cached state, enabled with the `serde` feature.
+//!
+//! Bounds, hashes, and the like aren't serialized; they're recomputed when deserializing.
+
+use crate::color::ColorU;
+use crate::outline::{Contour, Outline, PointFlags};
+use crate::pattern::Image;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use serde::de::Error as DeserializeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::sync::Arc;
+
+impl Serialize for Outline {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.contours.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Outline {
+    fn deserialize<D>(deserializer: D) -> Result<Outline, D::Error> where D: Deserializer<'de> {
+        let mut outline = Outline::new();
+        for contour in Vec::<Contour>::deserialize(deserializer)? {
+            outline.push_contour(contour);
+        }
+        Ok(outline)
+    }
+}
+
+#[derive(Serialize)]
+struct SerializedContourRef<'a> {
+    points: &'a [Vector2F],
+    flags: Vec<u8>,
+    closed: bool,
+}
+
+#[derive(Deserialize)]
+struct SerializedContour {
+    points: Vec<Vector2F>,
+    flags: Vec<u8>,
+    closed: bool,
+}
+
+impl Serialize for Contour {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        SerializedContourRef {
+            points: &self.points,
+            flags: self.flags.iter().map(|flags| flags.bits()).collect(),
+            closed: self.closed,
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Contour {
+    fn deserialize<D>(deserializer: D) -> Result<Contour, D::Error> where D: Deserializer<'de> {
+        let serialized = SerializedContour::deserialize(deserializer)?;
+        if serialized.points.len() != serialized.flags.len() {
+            return Err(D::Error::custom("contour has mismatched point and flag counts"));
+        }
+
+        let mut contour = Contour::new();
+        for (&point, &flags) in serialized.points.iter().zip(serialized.flags.iter()) {
+            if !point.x().is_finite() || !point.y().is_finite() {
+                return Err(D::Error::custom("contour has a non-finite point"));
+            }
+            let flags = PointFlags::from_bits(flags).ok_or_else(|| {
+                D::Error::custom("contour has invalid point flags")
+            })?;
+            contour.push_point(point, flags, true);
+        }
+        if serialized.closed {
+            contour.close();
+        }
+        Ok(contour)
+    }
+}
+
+#[derive(Serialize)]
+struct SerializedImageRef<'a> {
+    size: Vector2I,
+    pixels: &'a [ColorU],
+}
+
+#[derive(Deserialize)]
+struct SerializedImage {
+    size: Vector2I,
+    pixels: Vec<ColorU>,
+}
+
+impl Serialize for Image {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        SerializedImageRef { size: self.size(), pixels: self.pixels() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Image {
+    fn deserialize<D>(deserializer: D) -> Result<Image, D::Error> where D: Deserializer<'de> {
+        let serialized = SerializedImage::deserialize(deserializer)?;
+        let size = serialized.size;
+        if size.x() < 0 || size.y() < 0 ||
+                size.x() as usize * size.y() as usize != serialized.pixels.len() {
+            return Err(D::Error::custom("image size doesn't match its pixel count"));
+        }
+        Ok(Image::new(size, Arc::new(serialized.pixels)))
+    }
+}
@@ -15,15 +15,30 @@ use crate::segment::Segment;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::util::lerp;
 use pathfinder_geometry::vector::Vector2F;
 use std::f32;
 
-const TOLERANCE: f32 = 0.01;
+const DEFAULT_TOLERANCE: f32 = 0.01;
+
+// The deepest that offsetting will subdivide a segment to meet the tolerance, which bounds each
+// segment at 2^8 offset pieces. Past this, the offset curve is accepted as is.
+const MAX_SUBDIVISION_DEPTH: u32 = 8;
+
+// Used when finding the inflection points and cusps of cubic curves.
+const SPLIT_EPSILON: f32 = 0.000001;
+const SPLIT_T_EPSILON: f32 = 0.001;
+const CUSP_EPSILON: f32 = 0.01;
+
+// Round joins between pieces whose directions differ by less than this are skipped.
+const ROUND_JOIN_EPSILON: f32 = 0.0001;
 
 pub struct OutlineStrokeToFill<'a> {
     input: &'a Outline,
     output: Outline,
     style: StrokeStyle,
+    width_profile: WidthProfile,
+    tolerance: f32,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -47,35 +62,76 @@ pub enum LineJoin {
     Round,
 }
 
+/// How the width of a stroke varies along each contour, for tapered or pressure-sensitive strokes.
+///
+/// Widths are given as multiples of `StrokeStyle::line_width`.
+pub enum WidthProfile {
+    /// The same width everywhere.
+    Constant,
+    /// A width for each endpoint of each contour, interpolated linearly along the segments in
+    /// between. Endpoints without a width get 1.0.
+    PerVertex(Vec<Vec<f32>>),
+    /// A function of the distance along a contour and the length of the whole contour.
+    ArcLength(Box<dyn Fn(f32, f32) -> f32>),
+}
+
 impl<'a> OutlineStrokeToFill<'a> {
     #[inline]
     pub fn new(input: &Outline, style: StrokeStyle) -> OutlineStrokeToFill {
-        OutlineStrokeToFill { input, output: Outline::new(), style }
+        OutlineStrokeToFill::with_width_profile(input, style, WidthProfile::Constant)
+    }
+
+    #[inline]
+    pub fn with_width_profile(input: &Outline, style: StrokeStyle, width_profile: WidthProfile)
+                              -> OutlineStrokeToFill {
+        OutlineStrokeToFill {
+            input,
+            output: Outline::new(),
+            style,
+            width_profile,
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Sets the maximum distance, in pixels, that the offset curves may stray from the true
+    /// outline of the stroke. The default is 0.01.
+    ///
+    /// Panics if the tolerance isn't positive.
+    #[inline]
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        assert!(tolerance > 0.0, "The stroke tolerance must be positive!");
+        self.tolerance = tolerance;
     }
 
     pub fn offset(&mut self) {
         let mut new_contours = vec![];
-        for input in &self.input.contours {
+        for (contour_index, input) in self.input.contours.iter().enumerate() {
             let closed = input.closed;
+            let radius = StrokeRadius::new(input,
+                                           contour_index,
+                                           self.style.line_width * 0.5,
+                                           &self.width_profile);
             let mut stroker = ContourStrokeToFill::new(input,
                                                        Contour::new(),
-                                                       self.style.line_width * 0.5,
-                                                       self.style.line_join);
+                                                       &radius,
+                                                       self.style.line_join,
+                                                       self.tolerance);
 
             stroker.offset_forward();
             if closed {
                 self.push_stroked_contour(&mut new_contours, stroker, true);
                 stroker = ContourStrokeToFill::new(input,
                                                    Contour::new(),
-                                                   self.style.line_width * 0.5,
-                                                   self.style.line_join);
+                                                   &radius,
+                                                   self.style.line_join,
+                                                   self.tolerance);
             } else {
-                self.add_cap(&mut stroker.output);
+                self.add_cap(&mut stroker.output, radius.at_end() * 2.0);
             }
 
             stroker.offset_backward();
             if !closed {
-                self.add_cap(&mut stroker.output);
+                self.add_cap(&mut stroker.output, radius.at_start() * 2.0);
             }
 
             self.push_stroked_contour(&mut new_contours, stroker, closed);
@@ -93,7 +149,7 @@ impl<'a> OutlineStrokeToFill<'a> {
         self.output
     }
 
-    fn push_stroked_contour(&mut self,
+    fn push_stroked_contour(&self,
                             new_contours: &mut Vec<Contour>,
                             mut stroker: ContourStrokeToFill,
                             closed: bool) {
@@ -101,7 +157,7 @@ impl<'a> OutlineStrokeToFill<'a> {
         if closed && stroker.output.might_need_join(self.style.line_join) {
             let (p1, p0) = (stroker.output.position_of(1), stroker.output.position_of(0));
             let final_segment = LineSegment2F::new(p1, p0);
-            stroker.output.add_join(self.style.line_width * 0.5,
+            stroker.output.add_join(stroker.radius.at_start(),
                                     self.style.line_join,
                                     stroker.input.position_of(0),
                                     final_segment);
@@ -111,12 +167,11 @@ impl<'a> OutlineStrokeToFill<'a> {
         new_contours.push(stroker.output);
     }
 
-    fn add_cap(&mut self, contour: &mut Contour) {
+    fn add_cap(&self, contour: &mut Contour, width: f32) {
         if self.style.line_cap == LineCap::Butt || contour.len() < 2 {
             return
         }
 
-        let width = self.style.line_width;
         let (p0, p1) = (contour.position_of_last(2), contour.position_of_last(1));
         let gradient = (p1 - p0).normalize();
 
@@ -150,14 +205,20 @@ impl<'a> OutlineStrokeToFill<'a> {
 struct ContourStrokeToFill<'a> {
     input: &'a Contour,
     output: Contour,
-    radius: f32,
+    radius: &'a StrokeRadius<'a>,
     join: LineJoin,
+    tolerance: f32,
 }
 
 impl<'a> ContourStrokeToFill<'a> {
     #[inline]
-    fn new(input: &Contour, output: Contour, radius: f32, join: LineJoin) -> ContourStrokeToFill {
-        ContourStrokeToFill { input, output, radius, join }
+    fn new(input: &'a Contour,
+           output: Contour,
+           radius: &'a StrokeRadius<'a>,
+           join: LineJoin,
+           tolerance: f32)
+           -> ContourStrokeToFill<'a> {
+        ContourStrokeToFill { input, output, radius, join, tolerance }
     }
 
     fn offset_forward(&mut self) {
@@ -165,7 +226,9 @@ impl<'a> ContourStrokeToFill<'a> {
             // FIXME(pcwalton): We negate the radius here so that round end caps can be drawn
             // clockwise. Of course, we should just implement anticlockwise arcs to begin with...
             let join = if segment_index == 0 { LineJoin::Bevel } else { self.join };
-            segment.offset(-self.radius, join, &mut self.output);
+            let radius = self.radius;
+            let distance = move |t| -radius.at(segment_index, t);
+            self.offset_segment(&segment, OffsetDistance::new(&distance), join);
         }
     }
 
@@ -176,46 +239,260 @@ impl<'a> ContourStrokeToFill<'a> {
             .map(|segment| segment.reversed())
             .collect();
         segments.reverse();
+        let segment_count = segments.len();
         for (segment_index, segment) in segments.iter().enumerate() {
             // FIXME(pcwalton): We negate the radius here so that round end caps can be drawn
             // clockwise. Of course, we should just implement anticlockwise arcs to begin with...
             let join = if segment_index == 0 { LineJoin::Bevel } else { self.join };
-            segment.offset(-self.radius, join, &mut self.output);
+            let (radius, input_segment_index) = (self.radius, segment_count - 1 - segment_index);
+            let distance = move |t: f32| -radius.at(input_segment_index, 1.0 - t);
+            self.offset_segment(segment, OffsetDistance::new(&distance), join);
+        }
+    }
+
+    // Cubic curves are split at their inflection points and cusps first, so that each piece
+    // bends only one way and its offset can be approximated well. A cusp reverses the
+    // direction of the curve, so the pieces on either side of one are connected with a round
+    // join, which traces the circular end of the pen as it turns around.
+    fn offset_segment(&mut self, segment: &Segment, distance: OffsetDistance, join: LineJoin) {
+        let split_points = if segment.is_cubic() {
+            find_cubic_split_points(segment)
+        } else {
+            vec![]
+        };
+
+        let (mut segment, mut distance, mut join, mut prev_t) = (*segment, distance, join, 0.0);
+        for split_point in split_points {
+            let t = (split_point.t - prev_t) / (1.0 - prev_t);
+            let (before, after) = segment.split(t);
+            let (distance_before, distance_after) = distance.split(t);
+            before.offset(distance_before, join, self.tolerance, 0, &mut self.output);
+
+            segment = after;
+            distance = distance_after;
+            join = if split_point.is_cusp { LineJoin::Round } else { self.join };
+            prev_t = split_point.t;
+        }
+
+        segment.offset(distance, join, self.tolerance, 0, &mut self.output);
+    }
+}
+
+// The radius of a stroke along one of its contours.
+struct StrokeRadius<'a> {
+    half_width: f32,
+    profile: &'a WidthProfile,
+    contour_index: usize,
+    segment_count: usize,
+    endpoint_count: usize,
+    // For arc length profiles, the distance along the contour at which each segment starts,
+    // followed by the length of the contour.
+    segment_distances: Vec<f32>,
+}
+
+impl<'a> StrokeRadius<'a> {
+    fn new(contour: &Contour, contour_index: usize, half_width: f32, profile: &'a WidthProfile)
+           -> StrokeRadius<'a> {
+        let segment_count = contour.iter().count();
+        let endpoint_count =
+            (0..contour.len()).filter(|&point_index| contour.point_is_endpoint(point_index))
+                              .count();
+        let mut segment_distances = vec![];
+        if let WidthProfile::ArcLength(_) = *profile {
+            let mut distance = 0.0;
+            segment_distances.push(distance);
+            for segment in contour.iter() {
+                distance += segment.arc_length();
+                segment_distances.push(distance);
+            }
+        }
+        StrokeRadius {
+            half_width,
+            profile,
+            contour_index,
+            segment_count,
+            endpoint_count,
+            segment_distances,
+        }
+    }
+
+    // Returns the radius at `t` along the given segment of the contour.
+    fn at(&self, segment_index: usize, t: f32) -> f32 {
+        let width = match *self.profile {
+            WidthProfile::Constant => 1.0,
+            WidthProfile::PerVertex(ref widths) => {
+                let widths = match widths.get(self.contour_index) {
+                    None => return self.half_width,
+                    Some(widths) => widths,
+                };
+                let width_at = |endpoint_index| *widths.get(endpoint_index).unwrap_or(&1.0);
+                let next_endpoint_index = (segment_index + 1) % self.endpoint_count.max(1);
+                lerp(width_at(segment_index), width_at(next_endpoint_index), t)
+            }
+            WidthProfile::ArcLength(ref function) => {
+                let distances = &self.segment_distances;
+                let (start, end) = match (distances.get(segment_index),
+                                          distances.get(segment_index + 1)) {
+                    (Some(&start), Some(&end)) => (start, end),
+                    _ => return self.half_width,
+                };
+                function(lerp(start, end, t), *distances.last().unwrap())
+            }
+        };
+        width * self.half_width
+    }
+
+    #[inline]
+    fn at_start(&self) -> f32 {
+        self.at(0, 0.0)
+    }
+
+    #[inline]
+    fn at_end(&self) -> f32 {
+        self.at(self.segment_count.max(1) - 1, 1.0)
+    }
+}
+
+// The distance to offset a segment by, as a function of `t`, which may vary along it.
+#[derive(Clone, Copy)]
+struct OffsetDistance<'a> {
+    function: &'a dyn Fn(f32) -> f32,
+    // The range of `t` of the original segment that the segment being offset covers.
+    t_range: (f32, f32),
+}
+
+impl<'a> OffsetDistance<'a> {
+    #[inline]
+    fn new(function: &'a dyn Fn(f32) -> f32) -> OffsetDistance<'a> {
+        OffsetDistance { function, t_range: (0.0, 1.0) }
+    }
+
+    #[inline]
+    fn at(&self, t: f32) -> f32 {
+        (self.function)(lerp(self.t_range.0, self.t_range.1, t))
+    }
+
+    fn split(&self, t: f32) -> (OffsetDistance<'a>, OffsetDistance<'a>) {
+        let mid = lerp(self.t_range.0, self.t_range.1, t);
+        (OffsetDistance { function: self.function, t_range: (self.t_range.0, mid) },
+         OffsetDistance { function: self.function, t_range: (mid, self.t_range.1) })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct SplitPoint {
+    t: f32,
+    is_cusp: bool,
+}
+
+// Returns the inflection points and cusps of a cubic curve in increasing order of `t`.
+//
+// These are the roots of the cross product of the first and second derivatives. A cusp is a
+// double root, or very nearly one, at which the first derivative also vanishes.
+fn find_cubic_split_points(segment: &Segment) -> Vec<SplitPoint> {
+    let (p0, p3) = (segment.baseline.from(), segment.baseline.to());
+    let (p1, p2) = (segment.ctrl.from(), segment.ctrl.to());
+
+    // B'(t) = 3 (a t^2 + 2 b t + c), and B''(t) = 6 (a t + b).
+    let a = p3 - p0 + (p1 - p2).scale(3.0);
+    let b = p0 + p2 - p1.scale(2.0);
+    let c = p1 - p0;
+
+    let (qa, qb, qc) = (-a.det(b), c.det(a), c.det(b));
+    let scale = qa.abs().max(qb.abs()).max(qc.abs());
+    if scale == 0.0 {
+        return vec![];
+    }
+
+    let (mut roots, mut only_cusps) = (vec![], false);
+    if qa.abs() <= scale * SPLIT_EPSILON {
+        roots.push(-qc / qb);
+    } else {
+        let discriminant = qb * qb - 4.0 * qa * qc;
+        if discriminant > 0.0 {
+            let root = discriminant.sqrt();
+            roots.push((-qb - root) / (2.0 * qa));
+            roots.push((-qb + root) / (2.0 * qa));
+            roots.sort_by(|t0, t1| t0.total_cmp(t1));
+        } else {
+            // There are no inflection points, but there may be a cusp or a tiny loop where the
+            // roots nearly meet.
+            roots.push(-qb / (2.0 * qa));
+            only_cusps = true;
+        }
+    }
+
+    let hull_length = (p1 - p0).length() + (p2 - p1).length() + (p3 - p2).length();
+    let mut split_points: Vec<SplitPoint> = vec![];
+    for t in roots {
+        if !(t > SPLIT_T_EPSILON && t < 1.0 - SPLIT_T_EPSILON) {
+            continue;
+        }
+        if let Some(prev_split_point) = split_points.last() {
+            if t - prev_split_point.t < SPLIT_T_EPSILON {
+                continue;
+            }
+        }
+
+        let derivative = (a.scale(t) + b.scale(2.0)).scale(t) + c;
+        let is_cusp = derivative.length() <= hull_length * CUSP_EPSILON;
+        if only_cusps && !is_cusp {
+            continue;
         }
+        split_points.push(SplitPoint { t, is_cusp });
     }
+    split_points
 }
 
 trait Offset {
-    fn offset(&self, distance: f32, join: LineJoin, contour: &mut Contour);
+    fn offset(&self,
+              distance: OffsetDistance,
+              join: LineJoin,
+              tolerance: f32,
+              depth: u32,
+              contour: &mut Contour);
     fn add_to_contour(&self,
                       distance: f32,
                       join: LineJoin,
                       join_point: Vector2F,
                       contour: &mut Contour);
-    fn offset_once(&self, distance: f32) -> Self;
-    fn error_is_within_tolerance(&self, other: &Segment, distance: f32) -> bool;
+    fn offset_once(&self, distance: OffsetDistance) -> Self;
+    fn error_is_within_tolerance(&self,
+                                 other: &Segment,
+                                 distance: OffsetDistance,
+                                 tolerance: f32)
+                                 -> bool;
 }
 
 impl Offset for Segment {
-    fn offset(&self, distance: f32, join: LineJoin, contour: &mut Contour) {
-        let join_point = self.baseline.from();
-        if self.baseline.square_length() < TOLERANCE * TOLERANCE {
-            self.add_to_contour(distance, join, join_point, contour);
+    fn offset(&self,
+              distance: OffsetDistance,
+              join: LineJoin,
+              tolerance: f32,
+              depth: u32,
+              contour: &mut Contour) {
+        // A piece this small has no meaningful direction to offset in. Dropping it leaves a gap
+        // no wider than the tolerance, which the join to the next piece closes.
+        if hull_is_tiny(self, tolerance) {
             return;
         }
 
+        let join_point = self.baseline.from();
+
         let candidate = self.offset_once(distance);
-        if self.error_is_within_tolerance(&candidate, distance) {
-            candidate.add_to_contour(distance, join, join_point, contour);
+        if depth >= MAX_SUBDIVISION_DEPTH ||
+                self.error_is_within_tolerance(&candidate, distance, tolerance) {
+            candidate.add_to_contour(distance.at(0.0), join, join_point, contour);
             return;
         }
 
         debug!("--- SPLITTING ---");
         debug!("... PRE-SPLIT: {:?}", self);
         let (before, after) = self.split(0.5);
+        let (distance_before, distance_after) = distance.split(0.5);
         debug!("... AFTER-SPLIT: {:?} {:?}", before, after);
-        before.offset(distance, join, contour);
-        after.offset(distance, join, contour);
+        before.offset(distance_before, join, tolerance, depth + 1, contour);
+        after.offset(distance_after, join, tolerance, depth + 1, contour);
     }
 
     fn add_to_contour(&self,
@@ -242,16 +519,19 @@ impl Offset for Segment {
         contour.push_segment(self, flags);
     }
 
-    fn offset_once(&self, distance: f32) -> Segment {
+    fn offset_once(&self, distance: OffsetDistance) -> Segment {
         if self.is_line() {
-            return Segment::line(self.baseline.offset(distance));
+            return Segment::line(offset_line(self.baseline, distance.at(0.0), distance.at(1.0)));
         }
 
+        // Each leg of the control polygon is offset by the distances at the `t` values its
+        // control points roughly correspond to, so that varying widths are interpolated.
         if self.is_quadratic() {
+            let (d0, d1, d2) = (distance.at(0.0), distance.at(0.5), distance.at(1.0));
             let mut segment_0 = LineSegment2F::new(self.baseline.from(), self.ctrl.from());
             let mut segment_1 = LineSegment2F::new(self.ctrl.from(), self.baseline.to());
-            segment_0 = segment_0.offset(distance);
-            segment_1 = segment_1.offset(distance);
+            segment_0 = offset_line(segment_0, d0, d1);
+            segment_1 = offset_line(segment_1, d1, d2);
             let ctrl = match segment_0.intersection_t(segment_1) {
                 Some(t) => segment_0.sample(t),
                 None => segment_0.to().lerp(segment_1.from(), 0.5),
@@ -262,11 +542,14 @@ impl Offset for Segment {
 
         debug_assert!(self.is_cubic());
 
+        let (d0, d1) = (distance.at(0.0), distance.at(1.0 / 3.0));
+        let (d2, d3) = (distance.at(2.0 / 3.0), distance.at(1.0));
+
         if self.baseline.from() == self.ctrl.from() {
             let mut segment_0 = LineSegment2F::new(self.baseline.from(), self.ctrl.to());
             let mut segment_1 = LineSegment2F::new(self.ctrl.to(), self.baseline.to());
-            segment_0 = segment_0.offset(distance);
-            segment_1 = segment_1.offset(distance);
+            segment_0 = offset_line(segment_0, d0, d2);
+            segment_1 = offset_line(segment_1, d2, d3);
             let ctrl = match segment_0.intersection_t(segment_1) {
                 Some(t) => segment_0.sample(t),
                 None => segment_0.to().lerp(segment_1.from(), 0.5),
@@ -279,8 +562,8 @@ impl Offset for Segment {
         if self.ctrl.to() == self.baseline.to() {
             let mut segment_0 = LineSegment2F::new(self.baseline.from(), self.ctrl.from());
             let mut segment_1 = LineSegment2F::new(self.ctrl.from(), self.baseline.to());
-            segment_0 = segment_0.offset(distance);
-            segment_1 = segment_1.offset(distance);
+            segment_0 = offset_line(segment_0, d0, d1);
+            segment_1 = offset_line(segment_1, d1, d3);
             let ctrl = match segment_0.intersection_t(segment_1) {
                 Some(t) => segment_0.sample(t),
                 None => segment_0.to().lerp(segment_1.from(), 0.5),
@@ -293,9 +576,9 @@ impl Offset for Segment {
         let mut segment_0 = LineSegment2F::new(self.baseline.from(), self.ctrl.from());
         let mut segment_1 = LineSegment2F::new(self.ctrl.from(), self.ctrl.to());
         let mut segment_2 = LineSegment2F::new(self.ctrl.to(), self.baseline.to());
-        segment_0 = segment_0.offset(distance);
-        segment_1 = segment_1.offset(distance);
-        segment_2 = segment_2.offset(distance);
+        segment_0 = offset_line(segment_0, d0, d1);
+        segment_1 = offset_line(segment_1, d1, d2);
+        segment_2 = offset_line(segment_2, d2, d3);
         let (ctrl_0, ctrl_1) = match (
             segment_0.intersection_t(segment_1),
             segment_1.intersection_t(segment_2),
@@ -311,25 +594,27 @@ impl Offset for Segment {
         Segment::cubic(baseline, ctrl)
     }
 
-    fn error_is_within_tolerance(&self, other: &Segment, distance: f32) -> bool {
-        let (mut min, mut max) = (
-            f32::abs(distance) - TOLERANCE,
-            f32::abs(distance) + TOLERANCE,
-        );
-        min = if min <= 0.0 { 0.0 } else { min * min };
-        max = if max <= 0.0 { 0.0 } else { max * max };
-
+    // Measures how far the candidate strays from the true offset curve along the normals of this
+    // segment. Distance along the tangent only reflects the two curves being parameterized
+    // differently, so it doesn't count.
+    fn error_is_within_tolerance(&self,
+                                 other: &Segment,
+                                 distance: OffsetDistance,
+                                 tolerance: f32)
+                                 -> bool {
         for t_num in 0..(SAMPLE_COUNT + 1) {
             let t = t_num as f32 / SAMPLE_COUNT as f32;
-            // FIXME(pcwalton): Use signed distance!
-            let (this_p, other_p) = (self.sample(t), other.sample(t));
-            let vector = this_p - other_p;
-            let square_distance = vector.square_length();
-            debug!(
-                "this_p={:?} other_p={:?} vector={:?} sqdist={:?} min={:?} max={:?}",
-                this_p, other_p, vector, square_distance, min, max
-            );
-            if square_distance < min || square_distance > max {
+            let tangent = derivative(self, t);
+            if tangent.square_length() == 0.0 {
+                continue;
+            }
+
+            let normal = tangent.yx().scale_xy(Vector2F::new(-1.0, 1.0)).normalize();
+            let expected_p = self.sample(t) + normal.scale(distance.at(t));
+            let other_p = other.sample(t);
+            let error = (other_p - expected_p).dot(normal);
+            debug!("expected_p={:?} other_p={:?} error={:?}", expected_p, other_p, error);
+            if error.abs() > tolerance {
                 return false;
             }
         }
@@ -340,6 +625,44 @@ impl Offset for Segment {
     }
 }
 
+// Returns the derivative of the segment with respect to `t`.
+fn derivative(segment: &Segment, t: f32) -> Vector2F {
+    if segment.is_line() {
+        return segment.baseline.vector();
+    }
+
+    let segment = segment.to_cubic();
+    let (p0, p3) = (segment.baseline.from(), segment.baseline.to());
+    let (p1, p2) = (segment.ctrl.from(), segment.ctrl.to());
+    let s = 1.0 - t;
+    ((p1 - p0).scale(s * s) + (p2 - p1).scale(2.0 * s * t) + (p3 - p2).scale(t * t)).scale(3.0)
+}
+
+// Returns true if all the points of the segment lie within `tolerance` of its start point.
+//
+// The baseline alone isn't enough to go on, since a closed loop has a zero-length baseline.
+fn hull_is_tiny(segment: &Segment, tolerance: f32) -> bool {
+    let from = segment.baseline.from();
+    let is_near = |point: Vector2F| (point - from).square_length() < tolerance * tolerance;
+    if !is_near(segment.baseline.to()) {
+        return false;
+    }
+    if segment.is_quadratic() {
+        is_near(segment.ctrl.from())
+    } else if segment.is_cubic() {
+        is_near(segment.ctrl.from()) && is_near(segment.ctrl.to())
+    } else {
+        true
+    }
+}
+
+// Offsets a line by a distance that varies linearly from one end to the other.
+fn offset_line(line: LineSegment2F, from_distance: f32, to_distance: f32) -> LineSegment2F {
+    let from = line.offset(from_distance).from();
+    let to = line.offset(to_distance).to();
+    LineSegment2F::new(from, to)
+}
+
 impl Contour {
     fn might_need_join(&self, join: LineJoin) -> bool {
         if self.len() < 2 {
@@ -372,10 +695,21 @@ impl Contour {
                 }
             }
             LineJoin::Round => {
+                let (chord_from, chord_to) =
+                    (prev_tangent.to() - join_point, next_tangent.to() - join_point);
+                if chord_from.square_length() == 0.0 || chord_to.square_length() == 0.0 {
+                    return;
+                }
+
+                // Skip joins between pieces that already meet smoothly, since an arc with no
+                // sweep can't be approximated.
+                let (chord_from, chord_to) = (chord_from.normalize(), chord_to.normalize());
+                if chord_from.dot(chord_to) >= 1.0 - ROUND_JOIN_EPSILON {
+                    return;
+                }
+
                 let scale = Vector2F::splat(distance.abs());
                 let transform = Transform2F::from_scale(scale).translate(join_point);
-                let chord_from = (prev_tangent.to() - join_point).normalize();
-                let chord_to = (next_tangent.to() - join_point).normalize();
                 let chord = LineSegment2F::new(chord_from, chord_to);
                 self.push_arc_from_unit_chord(&transform, chord, ArcDirection::CW);
             }
@@ -403,3 +737,142 @@ impl Default for LineJoin {
     #[inline]
     fn default() -> LineJoin { LineJoin::Miter(10.0) }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::fill::FillRule;
+    use crate::outline::{Contour, Outline};
+    use crate::outline::PushSegmentFlags;
+    use crate::segment::Segment;
+    use super::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle, WidthProfile};
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::vector::Vector2F;
+
+    #[test]
+    fn test_tapered_stroke() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(Vector2F::new(0.0, 0.0));
+        contour.push_endpoint(Vector2F::new(100.0, 0.0));
+        contour.push_endpoint(Vector2F::new(200.0, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let style = StrokeStyle {
+            line_width: 10.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Bevel,
+        };
+        let profile = WidthProfile::PerVertex(vec![vec![2.0, 1.0, 0.0]]);
+        let mut stroker = OutlineStrokeToFill::with_width_profile(&outline, style, profile);
+        stroker.offset();
+        let stroked = stroker.into_outline();
+
+        assert!(stroked.contains_point(Vector2F::new(1.0, 9.0), FillRule::Winding));
+        assert!(stroked.contains_point(Vector2F::new(100.0, -4.0), FillRule::Winding));
+        assert!(!stroked.contains_point(Vector2F::new(100.0, 6.0), FillRule::Winding));
+        assert!(!stroked.contains_point(Vector2F::new(190.0, 1.0), FillRule::Winding));
+
+        let profile = WidthProfile::ArcLength(Box::new(|distance, length| 1.0 - distance / length));
+        let mut stroker = OutlineStrokeToFill::with_width_profile(&outline, style, profile);
+        stroker.offset();
+        let stroked = stroker.into_outline();
+        assert!(stroked.contains_point(Vector2F::new(100.0, 2.0), FillRule::Winding));
+        assert!(!stroked.contains_point(Vector2F::new(100.0, 3.0), FillRule::Winding));
+    }
+
+    #[test]
+    fn test_thick_stroke_around_cusp() {
+        let curve = Segment::cubic(LineSegment2F::new(Vector2F::new(0.0, 0.0),
+                                                      Vector2F::new(100.0, 0.0)),
+                                   LineSegment2F::new(Vector2F::new(100.0, 100.0),
+                                                      Vector2F::new(0.0, 100.0)));
+        let mut contour = Contour::new();
+        contour.push_endpoint(curve.baseline.from());
+        contour.push_segment(&curve, PushSegmentFlags::UPDATE_BOUNDS);
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let style = StrokeStyle {
+            line_width: 20.0,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Round,
+        };
+        let mut stroker = OutlineStrokeToFill::new(&outline, style);
+        stroker.offset();
+        let stroked = stroker.into_outline();
+
+        // The pen turns around at the cusp, at (50, 75).
+        assert!(stroked.contains_point(Vector2F::new(50.0, 84.0), FillRule::Winding));
+        assert!(!stroked.contains_point(Vector2F::new(50.0, 86.0), FillRule::Winding));
+
+        let curve_points: Vec<_> = (0..=1000).map(|i| curve.sample(i as f32 / 1000.0)).collect();
+        for segment in stroked.contours().iter().flat_map(|contour| contour.iter()) {
+            for t_num in 0..=8 {
+                let point = segment.sample(t_num as f32 / 8.0);
+                let distance = curve_points.iter()
+                                           .map(|curve_point| (*curve_point - point).length())
+                                           .fold(f32::MAX, f32::min);
+                assert!(distance <= 10.05);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tiny_tolerance() {
+        let curve = Segment::cubic(LineSegment2F::new(Vector2F::new(0.0, 0.0),
+                                                      Vector2F::new(100.0, 0.0)),
+                                   LineSegment2F::new(Vector2F::new(0.0, 100.0),
+                                                      Vector2F::new(100.0, 100.0)));
+        let mut contour = Contour::new();
+        contour.push_endpoint(curve.baseline.from());
+        contour.push_segment(&curve, PushSegmentFlags::UPDATE_BOUNDS);
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        // Subdivision stops at a fixed depth even if the tolerance can never be met.
+        let mut stroker = OutlineStrokeToFill::new(&outline, StrokeStyle::default());
+        stroker.set_tolerance(f32::MIN_POSITIVE);
+        stroker.offset();
+        let stroked = stroker.into_outline();
+        assert!(stroked.contains_point(Vector2F::new(50.0, 75.0), FillRule::Winding));
+    }
+
+    #[test]
+    fn test_subdivision_is_bounded() {
+        let curve = Segment::cubic(LineSegment2F::new(Vector2F::new(0.0, 0.0),
+                                                      Vector2F::new(100.0, 0.0)),
+                                   LineSegment2F::new(Vector2F::new(100.0, 100.0),
+                                                      Vector2F::new(0.0, 100.0)));
+        let mut contour = Contour::new();
+        contour.push_endpoint(curve.baseline.from());
+        contour.push_segment(&curve, PushSegmentFlags::UPDATE_BOUNDS);
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let style = StrokeStyle {
+            line_width: 200.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Bevel,
+        };
+        let mut stroker = OutlineStrokeToFill::new(&outline, style);
+        stroker.set_tolerance(f32::MIN_POSITIVE);
+        stroker.offset();
+        let stroked = stroker.into_outline();
+        // Each side of the stroke offsets at most three pieces of the curve, split at its cusps
+        // and inflections, into at most 2^8 pieces each, with a join after every piece. The caps
+        // add two more segments.
+        let segment_count: usize = stroked.contours()
+                                          .iter()
+                                          .map(|contour| contour.iter().count())
+                                          .sum();
+        assert!(segment_count <= 2 * 3 * (1 << 8) * 2 + 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_tolerance() {
+        let outline = Outline::new();
+        let mut stroker = OutlineStrokeToFill::new(&outline, StrokeStyle::default());
+        stroker.set_tolerance(0.0);
+    }
+}
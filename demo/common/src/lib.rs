@@ -274,6 +274,7 @@ impl<W> DemoApp<W> where W: Window {
                 Vector2F::default()
             },
             subpixel_aa_enabled: self.ui_model.subpixel_aa_effect_enabled,
+            ..BuildOptions::default()
         };
 
         self.render_command_stream = Some(self.scene_proxy.build_with_stream(build_options));
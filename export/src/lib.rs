@@ -1,3 +1,5 @@
+use pathfinder_content::color::ColorU;
+use pathfinder_renderer::paint::Paint;
 use pathfinder_renderer::scene::Scene;
 use pathfinder_geometry::vector::Vector2F;
 use pathfinder_content::segment::SegmentKind;
@@ -32,6 +34,20 @@ impl Export for Scene {
     }
 }
 
+// TODO(pcwalton): Export gradients and patterns. For now, approximate gradients with their first
+// color stop and patterns with their first pixel.
+fn paint_color(paint: &Paint) -> ColorU {
+    match *paint {
+        Paint::Color(color) => color,
+        Paint::Gradient(ref gradient) => {
+            gradient.stops().first().map_or(ColorU::transparent_black(), |stop| stop.color)
+        }
+        Paint::Pattern(ref pattern) => {
+            pattern.image().pixels().first().cloned().unwrap_or(ColorU::transparent_black())
+        }
+    }
+}
+
 fn export_svg<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
     let view_box = scene.view_box();
     writeln!(
@@ -50,7 +66,7 @@ fn export_svg<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
         writeln!(
             writer,
             " fill=\"{:?}\" d=\"{:?}\" />",
-            paint.color, outline
+            paint_color(paint), outline
         )?;
     }
     writeln!(writer, "</svg>")?;
@@ -69,7 +85,7 @@ fn export_pdf<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
     };
     
     for (paint, outline, _) in scene.paths() {
-        pdf.set_fill_color(paint.color);
+        pdf.set_fill_color(paint_color(paint));
         
         for contour in outline.contours() {
             for (segment_index, segment) in contour.iter().enumerate() {
@@ -130,7 +146,7 @@ fn export_ps<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
         } else {
             writeln!(writer, "newpath")?;
         }
-        let color = paint.color.to_f32();
+        let color = paint_color(paint).to_f32();
         for contour in outline.contours() {
             for (segment_index, segment) in contour.iter().enumerate() {
                 if segment_index == 0 {
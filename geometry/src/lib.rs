@@ -17,3 +17,6 @@ pub mod transform3d;
 pub mod unit_vector;
 pub mod util;
 pub mod vector;
+
+#[cfg(feature = "serde")]
+mod serialization;
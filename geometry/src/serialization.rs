@@ -0,0 +1,144 @@
+// pathfinder/geometry/src/serialization.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Serde support for the geometry types, enabled with the `serde` feature.
+//!
+//! The SIMD representations aren't portable, so each type is serialized as a tuple of its
+//! components.
+
+use crate::line_segment::{LineSegment2F, LineSegmentU4, LineSegmentU8};
+use crate::rect::{RectF, RectI};
+use crate::transform2d::Transform2F;
+use crate::vector::{Vector2F, Vector2I, Vector4F};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for Vector2F {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        (self.x(), self.y()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Vector2F {
+    fn deserialize<D>(deserializer: D) -> Result<Vector2F, D::Error> where D: Deserializer<'de> {
+        let (x, y) = Deserialize::deserialize(deserializer)?;
+        Ok(Vector2F::new(x, y))
+    }
+}
+
+impl Serialize for Vector2I {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        (self.x(), self.y()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Vector2I {
+    fn deserialize<D>(deserializer: D) -> Result<Vector2I, D::Error> where D: Deserializer<'de> {
+        let (x, y) = Deserialize::deserialize(deserializer)?;
+        Ok(Vector2I::new(x, y))
+    }
+}
+
+impl Serialize for Vector4F {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        (self.x(), self.y(), self.z(), self.w()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Vector4F {
+    fn deserialize<D>(deserializer: D) -> Result<Vector4F, D::Error> where D: Deserializer<'de> {
+        let (x, y, z, w) = Deserialize::deserialize(deserializer)?;
+        Ok(Vector4F::new(x, y, z, w))
+    }
+}
+
+impl Serialize for LineSegment2F {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        (self.from(), self.to()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LineSegment2F {
+    fn deserialize<D>(deserializer: D) -> Result<LineSegment2F, D::Error>
+                      where D: Deserializer<'de> {
+        let (from, to) = Deserialize::deserialize(deserializer)?;
+        Ok(LineSegment2F::new(from, to))
+    }
+}
+
+impl Serialize for LineSegmentU4 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        (self.from, self.to).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LineSegmentU4 {
+    fn deserialize<D>(deserializer: D) -> Result<LineSegmentU4, D::Error>
+                      where D: Deserializer<'de> {
+        let (from, to) = Deserialize::deserialize(deserializer)?;
+        Ok(LineSegmentU4 { from, to })
+    }
+}
+
+impl Serialize for LineSegmentU8 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        (self.from_x, self.from_y, self.to_x, self.to_y).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LineSegmentU8 {
+    fn deserialize<D>(deserializer: D) -> Result<LineSegmentU8, D::Error>
+                      where D: Deserializer<'de> {
+        let (from_x, from_y, to_x, to_y) = Deserialize::deserialize(deserializer)?;
+        Ok(LineSegmentU8 { from_x, from_y, to_x, to_y })
+    }
+}
+
+impl Serialize for RectF {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        (self.origin(), self.size()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RectF {
+    fn deserialize<D>(deserializer: D) -> Result<RectF, D::Error> where D: Deserializer<'de> {
+        let (origin, size) = Deserialize::deserialize(deserializer)?;
+        Ok(RectF::new(origin, size))
+    }
+}
+
+impl Serialize for RectI {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        (self.origin(), self.size()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RectI {
+    fn deserialize<D>(deserializer: D) -> Result<RectI, D::Error> where D: Deserializer<'de> {
+        let (origin, size) = Deserialize::deserialize(deserializer)?;
+        Ok(RectI::new(origin, size))
+    }
+}
+
+// Transforms are serialized in row-major order, as in `Transform2F::row_major()`.
+impl Serialize for Transform2F {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let translation = self.translation();
+        (self.m11(), self.m12(), self.m21(), self.m22(), translation.x(), translation.y())
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Transform2F {
+    fn deserialize<D>(deserializer: D) -> Result<Transform2F, D::Error>
+                      where D: Deserializer<'de> {
+        let (m11, m12, m21, m22, m31, m32) = Deserialize::deserialize(deserializer)?;
+        Ok(Transform2F::row_major(m11, m12, m21, m22, m31, m32))
+    }
+}
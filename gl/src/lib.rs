@@ -19,7 +19,7 @@ use half::f16;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_gpu::resources::ResourceLoader;
-use pathfinder_gpu::{BlendFunc, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
+use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
 use pathfinder_gpu::{DepthFunc, Device, Primitive, RenderOptions, RenderState, RenderTarget};
 use pathfinder_gpu::{ShaderKind, StencilFunc, TextureData, TextureDataRef, TextureFormat};
 use pathfinder_gpu::{UniformData, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
@@ -92,23 +92,10 @@ impl GLDevice {
                     gl::Disable(gl::BLEND); ck();
                 }
                 Some(ref blend) => {
-                    match blend.func {
-                        BlendFunc::RGBOneAlphaOne => {
-                            gl::BlendFunc(gl::ONE, gl::ONE); ck();
-                        }
-                        BlendFunc::RGBOneAlphaOneMinusSrcAlpha => {
-                            gl::BlendFuncSeparate(gl::ONE,
-                                                  gl::ONE_MINUS_SRC_ALPHA,
-                                                  gl::ONE,
-                                                  gl::ONE); ck();
-                        }
-                        BlendFunc::RGBSrcAlphaAlphaOneMinusSrcAlpha => {
-                            gl::BlendFuncSeparate(gl::SRC_ALPHA,
-                                                  gl::ONE_MINUS_SRC_ALPHA,
-                                                  gl::ONE,
-                                                  gl::ONE); ck();
-                        }
-                    }
+                    gl::BlendFuncSeparate(blend.src_rgb_factor.to_gl_blend_factor(),
+                                          blend.dest_rgb_factor.to_gl_blend_factor(),
+                                          blend.src_alpha_factor.to_gl_blend_factor(),
+                                          blend.dest_alpha_factor.to_gl_blend_factor()); ck();
                     match blend.op {
                         BlendOp::Add => {
                             gl::BlendEquation(gl::FUNC_ADD); ck();
@@ -968,6 +955,23 @@ impl Drop for GLTimerQuery {
     }
 }
 
+trait BlendFactorExt {
+    fn to_gl_blend_factor(self) -> GLenum;
+}
+
+impl BlendFactorExt for BlendFactor {
+    fn to_gl_blend_factor(self) -> GLenum {
+        match self {
+            BlendFactor::Zero => gl::ZERO,
+            BlendFactor::One => gl::ONE,
+            BlendFactor::SrcAlpha => gl::SRC_ALPHA,
+            BlendFactor::OneMinusSrcAlpha => gl::ONE_MINUS_SRC_ALPHA,
+            BlendFactor::DestAlpha => gl::DST_ALPHA,
+            BlendFactor::OneMinusDestAlpha => gl::ONE_MINUS_DST_ALPHA,
+        }
+    }
+}
+
 trait BufferTargetExt {
     fn to_gl_target(self) -> GLuint;
 }
@@ -210,17 +210,23 @@ pub enum RenderTarget<'a, D> where D: Device {
     Framebuffer(&'a D::Framebuffer),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct BlendState {
-    pub func: BlendFunc,
+    pub src_rgb_factor: BlendFactor,
+    pub dest_rgb_factor: BlendFactor,
+    pub src_alpha_factor: BlendFactor,
+    pub dest_alpha_factor: BlendFactor,
     pub op: BlendOp,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub enum BlendFunc {
-    RGBOneAlphaOne,
-    RGBOneAlphaOneMinusSrcAlpha,
-    RGBSrcAlphaAlphaOneMinusSrcAlpha,
+pub enum BlendFactor {
+    Zero,
+    One,
+    SrcAlpha,
+    OneMinusSrcAlpha,
+    DestAlpha,
+    OneMinusDestAlpha,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -268,10 +274,16 @@ impl Default for RenderOptions {
     }
 }
 
-impl Default for BlendFunc {
+impl Default for BlendState {
     #[inline]
-    fn default() -> BlendFunc {
-        BlendFunc::RGBOneAlphaOneMinusSrcAlpha
+    fn default() -> BlendState {
+        BlendState {
+            src_rgb_factor: BlendFactor::One,
+            dest_rgb_factor: BlendFactor::OneMinusSrcAlpha,
+            src_alpha_factor: BlendFactor::One,
+            dest_alpha_factor: BlendFactor::One,
+            op: BlendOp::default(),
+        }
     }
 }
 
@@ -10,6 +10,7 @@
 
 //! Experimental support for Lottie. This is very incomplete.
 
+use pathfinder_content::effects::BlendMode;
 use serde::{Deserialize, Serialize};
 use serde_json::Error as JSONError;
 use std::io::Read;
@@ -70,6 +71,29 @@ pub enum Layer {
     },
 }
 
+impl Layer {
+    /// Returns the blend mode this layer is composited with, or `None` if it uses one of the
+    /// non-separable modes (hue, saturation, color, luminosity), which aren't supported.
+    pub fn blend_mode(&self) -> Option<BlendMode> {
+        let Layer::Shape { blend_mode, .. } = *self;
+        match blend_mode {
+            0 => Some(BlendMode::SrcOver),
+            1 => Some(BlendMode::Multiply),
+            2 => Some(BlendMode::Screen),
+            3 => Some(BlendMode::Overlay),
+            4 => Some(BlendMode::Darken),
+            5 => Some(BlendMode::Lighten),
+            6 => Some(BlendMode::ColorDodge),
+            7 => Some(BlendMode::ColorBurn),
+            8 => Some(BlendMode::HardLight),
+            9 => Some(BlendMode::SoftLight),
+            10 => Some(BlendMode::Difference),
+            11 => Some(BlendMode::Exclusion),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Transform {
     #[serde(rename = "p")]
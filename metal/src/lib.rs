@@ -44,7 +44,7 @@ use objc::runtime::{Class, Object};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_gpu::resources::ResourceLoader;
-use pathfinder_gpu::{BlendFunc, BlendOp, BufferData, BufferTarget, BufferUploadMode, DepthFunc};
+use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode, DepthFunc};
 use pathfinder_gpu::{Device, Primitive, RenderState, RenderTarget, ShaderKind, StencilFunc};
 use pathfinder_gpu::{TextureData, TextureDataRef, TextureFormat, UniformData, VertexAttrClass};
 use pathfinder_gpu::{VertexAttrDescriptor, VertexAttrType};
@@ -978,36 +978,14 @@ impl MetalDevice {
             None => pipeline_color_attachment.set_blending_enabled(false),
             Some(ref blend) => {
                 pipeline_color_attachment.set_blending_enabled(true);
-                match blend.func {
-                    BlendFunc::RGBOneAlphaOne => {
-                        pipeline_color_attachment.set_source_rgb_blend_factor(MTLBlendFactor::One);
-                        pipeline_color_attachment.set_destination_rgb_blend_factor(
-                            MTLBlendFactor::One);
-                        pipeline_color_attachment.set_source_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                        pipeline_color_attachment.set_destination_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                    }
-                    BlendFunc::RGBOneAlphaOneMinusSrcAlpha => {
-                        pipeline_color_attachment.set_source_rgb_blend_factor(MTLBlendFactor::One);
-                        pipeline_color_attachment.set_destination_rgb_blend_factor(
-                            MTLBlendFactor::OneMinusSourceAlpha);
-                        pipeline_color_attachment.set_source_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                        pipeline_color_attachment.set_destination_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                    }
-                    BlendFunc::RGBSrcAlphaAlphaOneMinusSrcAlpha => {
-                        pipeline_color_attachment.set_source_rgb_blend_factor(
-                            MTLBlendFactor::SourceAlpha);
-                        pipeline_color_attachment.set_destination_rgb_blend_factor(
-                            MTLBlendFactor::OneMinusSourceAlpha);
-                        pipeline_color_attachment.set_source_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                        pipeline_color_attachment.set_destination_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                    }
-                }
+                pipeline_color_attachment.set_source_rgb_blend_factor(
+                    blend.src_rgb_factor.to_metal_blend_factor());
+                pipeline_color_attachment.set_destination_rgb_blend_factor(
+                    blend.dest_rgb_factor.to_metal_blend_factor());
+                pipeline_color_attachment.set_source_alpha_blend_factor(
+                    blend.src_alpha_factor.to_metal_blend_factor());
+                pipeline_color_attachment.set_destination_alpha_blend_factor(
+                    blend.dest_alpha_factor.to_metal_blend_factor());
                 match blend.op {
                     BlendOp::Add => {
                         pipeline_color_attachment.set_rgb_blend_operation(MTLBlendOperation::Add);
@@ -1173,6 +1151,23 @@ impl DeviceExtra for metal::Device {
 
 // Conversion helpers
 
+trait BlendFactorExt {
+    fn to_metal_blend_factor(self) -> MTLBlendFactor;
+}
+
+impl BlendFactorExt for BlendFactor {
+    fn to_metal_blend_factor(self) -> MTLBlendFactor {
+        match self {
+            BlendFactor::Zero => MTLBlendFactor::Zero,
+            BlendFactor::One => MTLBlendFactor::One,
+            BlendFactor::SrcAlpha => MTLBlendFactor::SourceAlpha,
+            BlendFactor::OneMinusSrcAlpha => MTLBlendFactor::OneMinusSourceAlpha,
+            BlendFactor::DestAlpha => MTLBlendFactor::DestinationAlpha,
+            BlendFactor::OneMinusDestAlpha => MTLBlendFactor::OneMinusDestinationAlpha,
+        }
+    }
+}
+
 trait DepthFuncExt {
     fn to_metal_compare_function(self) -> MTLCompareFunction;
 }
@@ -12,27 +12,42 @@
 
 use crate::concurrent::executor::Executor;
 use crate::gpu_data::{AlphaTileBatchPrimitive, BuiltObject, FillBatchPrimitive, RenderCommand};
+use crate::gpu_data::TileObjectPrimitive;
+use crate::mask::{accumulate_fill, coverage};
 use crate::options::{PreparedBuildOptions, RenderCommandListener};
-use crate::scene::Scene;
+use crate::paint::{self, PaintInfo, PaintMetadata};
+use crate::scene::{GroupId, PathObject, Scene};
+use crate::tile_cache::{CachedObject, PathCacheId, TileCache, TileCacheKey, UsedCacheEntries};
 use crate::tile_map::DenseTileMap;
-use crate::tiles::{self, TILE_HEIGHT, TILE_WIDTH, Tiler};
+use crate::tiles::{self, Tiler};
 use crate::z_buffer::ZBuffer;
+use hashbrown::HashMap;
+use pathfinder_content::fill::FillRule;
 use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU4, LineSegmentU8};
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::util;
 use pathfinder_simd::default::{F32x4, I32x4};
+use std::mem;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use std::u16;
 
 pub(crate) struct SceneBuilder<'a> {
     scene: &'a Scene,
     built_options: &'a PreparedBuildOptions,
+    cache: Option<(&'a TileCache, TileCacheKey)>,
 
     pub(crate) next_alpha_tile_index: AtomicUsize,
     pub(crate) z_buffer: ZBuffer,
     pub(crate) listener: Box<dyn RenderCommandListener>,
+    pub(crate) paint_metadata: Vec<PaintMetadata>,
+    // The groups around each path that are composited as layers, outermost first.
+    path_layers: Vec<Vec<GroupId>>,
+    // The index of the first path in a blurred layer, or the path count if there is none.
+    first_blurred_path_index: usize,
+    used_cache_entries: UsedCacheEntries,
 }
 
 impl<'a> SceneBuilder<'a> {
@@ -42,13 +57,47 @@ impl<'a> SceneBuilder<'a> {
         listener: Box<dyn RenderCommandListener>,
     ) -> SceneBuilder<'a> {
         let effective_view_box = scene.effective_view_box(built_options);
+        let path_layers: Vec<Vec<GroupId>> =
+            (0..scene.paths.len()).map(|index| scene.path_layers(index)).collect();
+        let first_blurred_path_index = path_layers.iter().position(|layers| {
+            layers.iter().any(|&group_id| scene.group(group_id).unwrap().blur() > 0.0)
+        }).unwrap_or(path_layers.len());
         SceneBuilder {
             scene,
             built_options,
+            cache: None,
 
             next_alpha_tile_index: AtomicUsize::new(0),
-            z_buffer: ZBuffer::new(effective_view_box),
+            z_buffer: ZBuffer::new(effective_view_box, built_options.tile_size),
             listener,
+            paint_metadata: vec![],
+            path_layers,
+            first_blurred_path_index,
+            used_cache_entries: Mutex::new(vec![]),
+        }
+    }
+
+    /// Creates a builder that reuses tiled paths from the cache where possible.
+    pub(crate) fn with_cache(
+        scene: &'a Scene,
+        built_options: &'a PreparedBuildOptions,
+        listener: Box<dyn RenderCommandListener>,
+        cache: &'a TileCache,
+    ) -> SceneBuilder<'a> {
+        let mut builder = SceneBuilder::new(scene, built_options, listener);
+        let effective_view_box = scene.effective_view_box(built_options);
+        builder.cache = TileCacheKey::new(built_options, effective_view_box).map(|key| {
+            (cache, key)
+        });
+        builder
+    }
+
+    /// Returns the tiled paths used in the build, which should replace the contents of the
+    /// cache, or `None` if the build couldn't use the cache.
+    pub(crate) fn into_used_cache_entries(self) -> Option<Vec<(PathCacheId, CachedObject)>> {
+        match self.cache {
+            None => None,
+            Some(_) => Some(self.used_cache_entries.into_inner().unwrap()),
         }
     }
 
@@ -57,13 +106,30 @@ impl<'a> SceneBuilder<'a> {
 
         let bounding_quad = self.built_options.bounding_quad();
         let path_count = self.scene.paths.len();
-        self.listener.send(RenderCommand::Start { bounding_quad, path_count });
+        let needs_readable_framebuffer = self.scene.needs_readable_framebuffer();
+        self.listener.send(RenderCommand::Start {
+            bounding_quad,
+            path_count,
+            needs_readable_framebuffer,
+            tile_size: self.built_options.tile_size,
+        });
 
-        self.listener.send(RenderCommand::AddPaintData(self.scene.build_paint_data()));
+        let PaintInfo { data: paint_data, metadata } =
+            self.scene.build_paint_info(self.built_options);
+        self.paint_metadata = metadata;
+        self.listener.send(RenderCommand::AddPaintData(paint_data));
 
         let effective_view_box = self.scene.effective_view_box(self.built_options);
+        let built_objects = executor.flatten_into_vector(path_count, |path_index| {
+            vec![self.build_path(path_index, effective_view_box, &self.built_options, &self.scene)]
+        });
+
+        // Pack only once every opaque path is in the Z-buffer, so that tiles hidden by paths
+        // above them can be dropped along with their fills.
         let alpha_tiles = executor.flatten_into_vector(path_count, |path_index| {
-            self.build_path(path_index, effective_view_box, &self.built_options, &self.scene)
+            self.pack_path(&built_objects[path_index],
+                           &self.scene.paths[path_index],
+                           path_index as u16)
         });
 
         self.finish_building(alpha_tiles);
@@ -78,60 +144,215 @@ impl<'a> SceneBuilder<'a> {
         view_box: RectF,
         built_options: &PreparedBuildOptions,
         scene: &Scene,
-    ) -> Vec<AlphaTileBatchPrimitive> {
-        let path_object = &scene.paths[path_index];
-        let outline = scene.apply_render_options(path_object.outline(), built_options);
-        let paint_id = path_object.paint();
-        let object_is_opaque = scene.paints[paint_id.0 as usize].is_opaque();
+    ) -> Arc<BuiltObject> {
+        let built_object = self.tile_path(path_index, view_box, built_options, scene);
+        if self.path_is_opaque(path_index) {
+            self.occlude(&built_object, path_index as u16);
+        }
+        built_object
+    }
 
-        let mut tiler = Tiler::new(self,
-                                   &outline,
-                                   view_box,
-                                   path_index as u16,
-                                   paint_id,
-                                   object_is_opaque);
+    fn tile_path(
+        &self,
+        path_index: usize,
+        view_box: RectF,
+        built_options: &PreparedBuildOptions,
+        scene: &Scene,
+    ) -> Arc<BuiltObject> {
+        let path_cache_id = scene.path_cache_id(path_index);
+        let cached_object = self.cache.and_then(|(cache, ref key)| cache.get(path_cache_id, key));
+
+        let built_object = match cached_object {
+            Some(built_object) => built_object,
+            None => {
+                let outline = scene.apply_render_options(&scene.path_outline(path_index),
+                                                         built_options);
+                let mut tiler = Tiler::new(&outline, view_box, built_options.tile_size);
+                tiler.generate_tiles();
+                Arc::new(tiler.built_object)
+            }
+        };
 
-        tiler.generate_tiles();
+        if let Some((_, key)) = self.cache {
+            let cached_object = CachedObject::new(key, built_object.clone());
+            self.used_cache_entries.lock().unwrap().push((path_cache_id, cached_object));
+        }
+        built_object
+    }
 
-        self.listener.send(RenderCommand::AddFills(tiler.built_object.fills));
-        tiler.built_object.alpha_tiles
+    // Whether a path hides everything beneath its solid tiles. Paths in layers can't go into the
+    // Z-buffer, since their solid tiles would be drawn outside the layer. Nor can paths above a
+    // blurred layer, since the blur spreads what they cover back into view.
+    fn path_is_opaque(&self, path_index: usize) -> bool {
+        let path_object = &self.scene.paths[path_index];
+        self.scene.paints[path_object.paint().0 as usize].is_opaque() &&
+            path_object.blend_mode().occludes_backdrop() &&
+            self.path_layers[path_index].is_empty() &&
+            path_index < self.first_blurred_path_index
     }
 
-    fn cull_alpha_tiles(&self, alpha_tiles: &mut Vec<AlphaTileBatchPrimitive>) {
-        for alpha_tile in alpha_tiles {
-            let alpha_tile_coords = alpha_tile.tile_coords();
-            if self
-                .z_buffer
-                .test(alpha_tile_coords, alpha_tile.object_index as u32)
-            {
-                continue;
+    // Pokes the tiles of an opaque path that it covers entirely into the Z-buffer. Those are its
+    // solid tiles, plus any alpha tiles whose fills add up to full coverage anyway, such as
+    // those along edges that run exactly on tile boundaries.
+    fn occlude(&self, built_object: &BuiltObject, object_index: u16) {
+        let fill_rule = self.scene.paths[object_index as usize].fill_rule();
+        let covered_alpha_tiles = find_covered_alpha_tiles(built_object, fill_rule);
+        for (tile_index, tile) in built_object.tiles.data.iter().enumerate() {
+            let covered = if tile.is_solid() {
+                fill_rule.winding_is_inside(tile.backdrop as i32)
+            } else {
+                covered_alpha_tiles[tile.alpha_tile_index as usize]
+            };
+            if covered {
+                let tile_coords = built_object.local_tile_index_to_coords(tile_index as u32);
+                self.z_buffer.update(tile_coords, object_index);
             }
+        }
+    }
 
-            // FIXME(pcwalton): Clean this up.
-            alpha_tile.tile_x_lo = 0xff;
-            alpha_tile.tile_y_lo = 0xff;
-            alpha_tile.tile_hi = 0xff;
+    // Sends the fills of the visible alpha tiles of a tiled path and returns its visible tiles,
+    // assigning them places in the mask framebuffer. Tiles that are hidden by an opaque path
+    // above, including any that this path occludes itself, are dropped; the Z-buffer draws the
+    // latter as solid tiles.
+    fn pack_path(&self, built_object: &BuiltObject, path_object: &PathObject, object_index: u16)
+                 -> Vec<AlphaTileBatchPrimitive> {
+        let fill_rule = path_object.fill_rule();
+        let visible_tiles: Vec<(Vector2I, &TileObjectPrimitive)> =
+            built_object.tiles.data.iter().enumerate().filter_map(|(tile_index, tile)| {
+                // Blank tiles are always skipped.
+                if tile.is_solid() && !fill_rule.winding_is_inside(tile.backdrop as i32) {
+                    return None;
+                }
+                let tile_coords = built_object.local_tile_index_to_coords(tile_index as u32);
+                if !self.z_buffer.test(tile_coords, object_index as u32) {
+                    return None;
+                }
+                Some((tile_coords, tile))
+            }).collect();
+
+        // Renumber the visible alpha tiles so that hidden ones take no room in the mask
+        // framebuffer.
+        let mut alpha_tile_indices: Vec<u16> = vec![!0; built_object.alpha_tile_count as usize];
+        let mut visible_alpha_tile_count = 0;
+        for &(_, tile) in &visible_tiles {
+            if !tile.is_solid() {
+                alpha_tile_indices[tile.alpha_tile_index as usize] = visible_alpha_tile_count;
+                visible_alpha_tile_count += 1;
+            }
+        }
+        let first_alpha_tile_index =
+            self.next_alpha_tile_index
+                .fetch_add(visible_alpha_tile_count as usize, Ordering::Relaxed) as u16;
+
+        let fills: Vec<_> = built_object.fills.iter().filter_map(|fill| {
+            let alpha_tile_index = alpha_tile_indices[fill.alpha_tile_index as usize];
+            if alpha_tile_index == !0 {
+                return None;
+            }
+            Some(FillBatchPrimitive {
+                alpha_tile_index: alpha_tile_index.wrapping_add(first_alpha_tile_index),
+                ..*fill
+            })
+        }).collect();
+        if !fills.is_empty() {
+            self.listener.send(RenderCommand::AddFills(fills));
         }
+
+        let paint_id = path_object.paint();
+        let paint_metadata = &self.paint_metadata[paint_id.0 as usize];
+        visible_tiles.into_iter().map(|(tile_coords, tile)| {
+            let alpha_tile_index = if tile.is_solid() {
+                tile.alpha_tile_index
+            } else {
+                alpha_tile_indices[tile.alpha_tile_index as usize]
+                    .wrapping_add(first_alpha_tile_index)
+            };
+            AlphaTileBatchPrimitive::new(tile_coords,
+                                         tile.backdrop,
+                                         object_index,
+                                         alpha_tile_index,
+                                         paint_metadata.texel,
+                                         paint_metadata.kind,
+                                         fill_rule)
+        }).collect()
     }
 
     fn pack_alpha_tiles(&mut self, alpha_tiles: Vec<AlphaTileBatchPrimitive>) {
         let path_count = self.scene.paths.len() as u32;
-        let solid_tiles = self.z_buffer.build_solid_tiles(&self.scene.paths, 0..path_count);
+        let solid_tiles = self.z_buffer.build_solid_tiles(&self.scene.paths,
+                                                          &self.paint_metadata,
+                                                          0..path_count);
         if !solid_tiles.is_empty() {
             self.listener.send(RenderCommand::SolidTile(solid_tiles));
         }
-        if !alpha_tiles.is_empty() {
-            self.listener.send(RenderCommand::AlphaTile(alpha_tiles));
+
+        // Split the alpha tiles into runs that share a blend mode. Blend modes that read the
+        // destination see a snapshot of it taken before the batch, so those get one batch per
+        // path to keep overlapping paths from missing each other.
+        let mut batch: Vec<AlphaTileBatchPrimitive> = vec![];
+        let mut batch_key = None;
+        let mut current_layers: &[GroupId] = &[];
+        for alpha_tile in alpha_tiles {
+            let object_index = alpha_tile.object_index as usize;
+            let blend_mode = self.scene.paths[object_index].blend_mode();
+            let key = if blend_mode.needs_readable_framebuffer() {
+                (blend_mode, Some(alpha_tile.object_index))
+            } else {
+                (blend_mode, None)
+            };
+            let layers = &self.path_layers[object_index];
+            if batch_key != Some(key) || current_layers != &layers[..] {
+                if let Some((blend_mode, _)) = batch_key {
+                    self.listener.send(RenderCommand::AlphaTile(mem::take(&mut batch),
+                                                                blend_mode));
+                }
+                batch_key = Some(key);
+            }
+            if current_layers != &layers[..] {
+                self.switch_layers(current_layers, layers);
+                current_layers = layers;
+            }
+            batch.push(alpha_tile);
+        }
+        if let Some((blend_mode, _)) = batch_key {
+            self.listener.send(RenderCommand::AlphaTile(batch, blend_mode));
         }
+        self.switch_layers(current_layers, &[]);
     }
 
-    fn finish_building(&mut self, mut alpha_tiles: Vec<AlphaTileBatchPrimitive>) {
+    // Pops the layers that the next path isn't in and pushes the ones it's in that aren't open.
+    fn switch_layers(&self, old_layers: &[GroupId], new_layers: &[GroupId]) {
+        let common_count = old_layers.iter()
+                                     .zip(new_layers.iter())
+                                     .take_while(|(old_layer, new_layer)| old_layer == new_layer)
+                                     .count();
+        for &group_id in old_layers[common_count..].iter().rev() {
+            let group = self.scene.group(group_id).unwrap();
+            let blur = device_blur(group.blur(), self.built_options);
+            self.listener.send(RenderCommand::PopLayer {
+                opacity: group.opacity(),
+                blur,
+                blend_mode: group.blend_mode(),
+            });
+        }
+        for _ in &new_layers[common_count..] {
+            self.listener.send(RenderCommand::PushLayer);
+        }
+    }
+
+    fn finish_building(&mut self, alpha_tiles: Vec<AlphaTileBatchPrimitive>) {
         self.listener.send(RenderCommand::FlushFills);
-        self.cull_alpha_tiles(&mut alpha_tiles);
         self.pack_alpha_tiles(alpha_tiles);
     }
 }
 
+// Converts the standard deviation of a blur in scene units to device pixels along each axis.
+fn device_blur(blur: f32, built_options: &PreparedBuildOptions) -> Vector2F {
+    let transform = paint::scene_to_device_transform(built_options);
+    Vector2F::new(Vector2F::new(transform.m11(), transform.m12()).length(),
+                  Vector2F::new(transform.m21(), transform.m22()).length()).scale(blur)
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct TileStats {
     pub solid_tile_count: u32,
@@ -141,14 +362,15 @@ pub struct TileStats {
 // Utilities for built objects
 
 impl BuiltObject {
-    pub(crate) fn new(bounds: RectF) -> BuiltObject {
-        let tile_rect = tiles::round_rect_out_to_tile_bounds(bounds);
+    pub(crate) fn new(bounds: RectF, tile_size: Vector2I) -> BuiltObject {
+        let tile_rect = tiles::round_rect_out_to_tile_bounds(bounds, tile_size);
         let tiles = DenseTileMap::new(tile_rect);
         BuiltObject {
             bounds,
+            tile_size,
             fills: vec![],
-            alpha_tiles: vec![],
             tiles,
+            alpha_tile_count: 0,
         }
     }
 
@@ -159,7 +381,6 @@ impl BuiltObject {
 
     fn add_fill(
         &mut self,
-        builder: &SceneBuilder,
         segment: LineSegment2F,
         tile_coords: Vector2I,
     ) {
@@ -170,15 +391,16 @@ impl BuiltObject {
             return;
         };
 
-        debug_assert_eq!(TILE_WIDTH, TILE_HEIGHT);
-
         // Compute the upper left corner of the tile.
-        let tile_size = F32x4::splat(TILE_WIDTH as f32);
+        let tile_size = self.tile_size.to_f32().0.to_f32x4().xyxy();
         let tile_upper_left = tile_coords.to_f32().0.to_f32x4().xyxy() * tile_size;
 
-        // Convert to 4.8 fixed point.
-        let segment = (segment.0 - tile_upper_left) * F32x4::splat(256.0);
-        let (min, max) = (F32x4::default(), F32x4::splat((TILE_WIDTH * 256 - 1) as f32));
+        // Convert to 4.8 fixed point, in sixteenths of the tile size.
+        let scale = Vector2F::splat(16.0 * 256.0).scale_xy(Vector2F::new(
+            1.0 / self.tile_size.x() as f32,
+            1.0 / self.tile_size.y() as f32));
+        let segment = (segment.0 - tile_upper_left) * scale.0.to_f32x4().xyxy();
+        let (min, max) = (F32x4::default(), F32x4::splat((16 * 256 - 1) as f32));
         let segment = segment.clamp(min, max).to_i32x4();
         let (from_x, from_y, to_x, to_y) = (segment[0], segment[1], segment[2], segment[3]);
 
@@ -189,7 +411,7 @@ impl BuiltObject {
         }
 
         // Allocate global tile if necessary.
-        let alpha_tile_index = self.get_or_allocate_alpha_tile_index(builder, tile_coords);
+        let alpha_tile_index = self.get_or_allocate_alpha_tile_index(tile_coords);
 
         // Pack whole pixels.
         let px = (segment & I32x4::splat(0xf00)).to_u32x4();
@@ -209,33 +431,27 @@ impl BuiltObject {
         });
     }
 
-    fn get_or_allocate_alpha_tile_index(
-        &mut self,
-        builder: &SceneBuilder,
-        tile_coords: Vector2I,
-    ) -> u16 {
+    fn get_or_allocate_alpha_tile_index(&mut self, tile_coords: Vector2I) -> u16 {
         let local_tile_index = self.tiles.coords_to_index_unchecked(tile_coords);
         let alpha_tile_index = self.tiles.data[local_tile_index].alpha_tile_index;
         if alpha_tile_index != !0 {
             return alpha_tile_index;
         }
 
-        let alpha_tile_index = builder
-            .next_alpha_tile_index
-            .fetch_add(1, Ordering::Relaxed) as u16;
+        let alpha_tile_index = self.alpha_tile_count;
+        self.alpha_tile_count += 1;
         self.tiles.data[local_tile_index].alpha_tile_index = alpha_tile_index;
         alpha_tile_index
     }
 
     pub(crate) fn add_active_fill(
         &mut self,
-        builder: &SceneBuilder,
         left: f32,
         right: f32,
         mut winding: i32,
         tile_coords: Vector2I,
     ) {
-        let tile_origin_y = (tile_coords.y() * TILE_HEIGHT as i32) as f32;
+        let tile_origin_y = (tile_coords.y() * self.tile_size.y()) as f32;
         let left = Vector2F::new(left, tile_origin_y);
         let right = Vector2F::new(right, tile_origin_y);
 
@@ -254,7 +470,7 @@ impl BuiltObject {
         );
 
         while winding != 0 {
-            self.add_fill(builder, segment, tile_coords);
+            self.add_fill(segment, tile_coords);
             if winding < 0 {
                 winding += 1
             } else {
@@ -265,7 +481,6 @@ impl BuiltObject {
 
     pub(crate) fn generate_fill_primitives_for_line(
         &mut self,
-        builder: &SceneBuilder,
         mut segment: LineSegment2F,
         tile_y: i32,
     ) {
@@ -273,8 +488,8 @@ impl BuiltObject {
             "... generate_fill_primitives_for_line(): segment={:?} tile_y={} ({}-{})",
             segment,
             tile_y,
-            tile_y as f32 * TILE_HEIGHT as f32,
-            (tile_y + 1) as f32 * TILE_HEIGHT as f32
+            tile_y * self.tile_size.y(),
+            (tile_y + 1) * self.tile_size.y()
         );
 
         let winding = segment.from_x() > segment.to_x();
@@ -285,9 +500,9 @@ impl BuiltObject {
         };
 
         // FIXME(pcwalton): Optimize this.
-        let segment_tile_left = f32::floor(segment_left) as i32 / TILE_WIDTH as i32;
+        let segment_tile_left = f32::floor(segment_left) as i32 / self.tile_size.x();
         let segment_tile_right =
-            util::alignup_i32(f32::ceil(segment_right) as i32, TILE_WIDTH as i32);
+            util::alignup_i32(f32::ceil(segment_right) as i32, self.tile_size.x());
         debug!(
             "segment_tile_left={} segment_tile_right={} tile_rect={:?}",
             segment_tile_left,
@@ -298,7 +513,7 @@ impl BuiltObject {
         for subsegment_tile_x in segment_tile_left..segment_tile_right {
             let (mut fill_from, mut fill_to) = (segment.from(), segment.to());
             let subsegment_tile_right =
-                ((i32::from(subsegment_tile_x) + 1) * TILE_HEIGHT as i32) as f32;
+                ((i32::from(subsegment_tile_x) + 1) * self.tile_size.x()) as f32;
             if subsegment_tile_right < segment_right {
                 let x = subsegment_tile_right;
                 let point = Vector2F::new(x, segment.solve_y_for_x(x));
@@ -313,7 +528,7 @@ impl BuiltObject {
 
             let fill_segment = LineSegment2F::new(fill_from, fill_to);
             let fill_tile_coords = Vector2I::new(subsegment_tile_x, tile_y);
-            self.add_fill(builder, fill_segment, fill_tile_coords);
+            self.add_fill(fill_segment, fill_tile_coords);
         }
     }
 
@@ -327,3 +542,118 @@ impl BuiltObject {
         self.tiles.index_to_coords(tile_index as usize)
     }
 }
+
+// Returns, for each alpha tile of a path, whether its fills cover every pixel of it.
+//
+// Computing coverage is expensive, so only tiles whose fills are all horizontal or vertical are
+// considered; any other edge almost always crosses the tile.
+fn find_covered_alpha_tiles(built_object: &BuiltObject, fill_rule: FillRule) -> Vec<bool> {
+    let mut candidates = vec![true; built_object.alpha_tile_count as usize];
+    for fill in &built_object.fills {
+        let (px, subpx) = (fill.px, fill.subpx);
+        let horizontal = px.from >> 4 == px.to >> 4 && subpx.from_y == subpx.to_y;
+        let vertical = px.from & 0xf == px.to & 0xf && subpx.from_x == subpx.to_x;
+        if !horizontal && !vertical {
+            candidates[fill.alpha_tile_index as usize] = false;
+        }
+    }
+
+    let tile_size = built_object.tile_size;
+    let tile_area = tile_size.x() as usize * tile_size.y() as usize;
+    let mut masks: HashMap<u16, Vec<f32>> = HashMap::new();
+    for fill in &built_object.fills {
+        if candidates[fill.alpha_tile_index as usize] {
+            let mask = masks.entry(fill.alpha_tile_index).or_insert_with(|| vec![0.0; tile_area]);
+            accumulate_fill(mask, fill, tile_size);
+        }
+    }
+
+    // Fills are quantized to 1/4096 of the tile size, so edges on the right and bottom boundaries
+    // of a tile fall just short of them. Allow for that.
+    let min_coverage = 1.0 - 1.5 * tile_size.x().max(tile_size.y()) as f32 / 4096.0;
+    let even_odd = fill_rule == FillRule::EvenOdd;
+    for tile in &built_object.tiles.data {
+        if tile.is_solid() || !candidates[tile.alpha_tile_index as usize] {
+            continue;
+        }
+        let backdrop = tile.backdrop as f32;
+        let covered = match masks.get(&tile.alpha_tile_index) {
+            None => coverage(backdrop, even_odd) == 1.0,
+            Some(mask) => {
+                mask.iter().all(|&winding| {
+                    coverage(winding + backdrop, even_odd) >= min_coverage
+                })
+            }
+        };
+        candidates[tile.alpha_tile_index as usize] = covered;
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod test {
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu_data::RenderCommand;
+    use crate::options::{BuildOptions, RasterizeOptions};
+    use crate::paint::Paint;
+    use crate::scene::{PathObject, Scene};
+    use pathfinder_content::color::ColorU;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use std::sync::{Arc, Mutex};
+
+    // Returns the number of fills and alpha tiles the scene builds into.
+    fn count_fills_and_alpha_tiles(scene: &Scene) -> (usize, usize) {
+        let counts = Arc::new(Mutex::new((0, 0)));
+        let listener_counts = counts.clone();
+        scene.build(BuildOptions::default(),
+                    Box::new(move |command| {
+                        let mut counts = listener_counts.lock().unwrap();
+                        match command {
+                            RenderCommand::AddFills(fills) => counts.0 += fills.len(),
+                            RenderCommand::AlphaTile(tiles, _) => counts.1 += tiles.len(),
+                            _ => {}
+                        }
+                    }),
+                    &SequentialExecutor);
+        let counts = *counts.lock().unwrap();
+        counts
+    }
+
+    #[test]
+    fn test_cull_hidden_fills() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(64.0)));
+        let red = scene.push_paint(&Paint::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+        let blue = scene.push_paint(&Paint::Color(ColorU { r: 0, g: 0, b: 255, a: 255 }));
+
+        let mut ellipse = Contour::new();
+        ellipse.push_ellipse(&Transform2F::from_uniform_scale(10.0)
+                                          .translate(Vector2F::splat(24.0)));
+        let mut outline = Outline::new();
+        outline.push_contour(ellipse);
+        scene.push_path(PathObject::new(outline, red, String::new()));
+        assert_ne!(count_fills_and_alpha_tiles(&scene), (0, 0));
+
+        // The edges of this rectangle lie on tile boundaries, so its alpha tiles are fully
+        // covered and occlude the ellipse along with its solid tiles.
+        let mut rect = Contour::new();
+        rect.push_endpoint(Vector2F::new(0.0, 0.0));
+        rect.push_endpoint(Vector2F::new(48.0, 0.0));
+        rect.push_endpoint(Vector2F::new(48.0, 48.0));
+        rect.push_endpoint(Vector2F::new(0.0, 48.0));
+        rect.close();
+        let mut outline = Outline::new();
+        outline.push_contour(rect);
+        scene.push_path(PathObject::new(outline, blue, String::new()));
+        assert_eq!(count_fills_and_alpha_tiles(&scene), (0, 0));
+
+        let pixels = scene.rasterize_to_pixels(Vector2I::splat(64), RasterizeOptions::default());
+        let pixel = |x: usize, y: usize| &pixels[(y * 64 + x) * 4..(y * 64 + x) * 4 + 4];
+        assert_eq!(pixel(24, 24), &[0, 0, 255, 255]);
+        assert_eq!(pixel(47, 47), &[0, 0, 255, 255]);
+        assert_eq!(pixel(50, 50), &[0, 0, 0, 0]);
+    }
+}
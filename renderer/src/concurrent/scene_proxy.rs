@@ -24,8 +24,15 @@ use crate::gpu::renderer::Renderer;
 use crate::gpu_data::RenderCommand;
 use crate::options::{BuildOptions, RenderCommandListener};
 use crate::scene::Scene;
+use crate::tile_cache::TileCache;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_gpu::Device;
+#[cfg(feature = "serde")]
+use crate::serialization::SceneFormatError;
+#[cfg(feature = "serde")]
+use crate::serialization::recording::RenderCommandRecorder;
+#[cfg(feature = "serde")]
+use std::io::Write;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 
@@ -49,7 +56,7 @@ impl SceneProxy {
 
     #[inline]
     pub fn replace_scene(&self, new_scene: Scene) {
-        self.sender.send(MainToWorkerMsg::ReplaceScene(new_scene)).unwrap();
+        self.sender.send(MainToWorkerMsg::ReplaceScene(Box::new(new_scene))).unwrap();
     }
 
     #[inline]
@@ -72,6 +79,19 @@ impl SceneProxy {
         RenderCommandStream::new(receiver)
     }
 
+    /// Like `build_with_stream()`, but also records the commands to the given writer, so that
+    /// they can be replayed later with a `RenderCommandReader`.
+    #[cfg(feature = "serde")]
+    pub fn build_with_recording_stream<W>(&self, options: BuildOptions, writer: W)
+                                          -> Result<RenderCommandStream, SceneFormatError>
+                                          where W: Write + Send + 'static {
+        let (sender, receiver) = mpsc::sync_channel(MAX_MESSAGES_IN_FLIGHT);
+        let listener = Box::new(move |command| drop(sender.send(command)));
+        let recorder = RenderCommandRecorder::with_listener(writer, Some(listener))?;
+        self.build_with_listener(options, Box::new(recorder));
+        Ok(RenderCommandStream::new(receiver))
+    }
+
     /// A convenience method to build a scene and send the resulting commands
     /// to the given renderer.
     ///
@@ -104,18 +124,23 @@ fn scene_thread<E>(mut scene: Scene,
                    executor: E,
                    main_to_worker_receiver: Receiver<MainToWorkerMsg>)
                    where E: Executor {
+    // Paths that survive from one build to the next, even across scene replacements, keep their
+    // tiles.
+    let mut tile_cache = TileCache::new();
     while let Ok(msg) = main_to_worker_receiver.recv() {
         match msg {
-            MainToWorkerMsg::ReplaceScene(new_scene) => scene = new_scene,
+            MainToWorkerMsg::ReplaceScene(new_scene) => scene = *new_scene,
             MainToWorkerMsg::CopyScene(sender) => sender.send(scene.clone()).unwrap(),
             MainToWorkerMsg::SetViewBox(new_view_box) => scene.set_view_box(new_view_box),
-            MainToWorkerMsg::Build(options, listener) => scene.build(options, listener, &executor)
+            MainToWorkerMsg::Build(options, listener) => {
+                scene.build_with_cache(options, listener, &executor, &mut tile_cache)
+            }
         }
     }
 }
 
 enum MainToWorkerMsg {
-    ReplaceScene(Scene),
+    ReplaceScene(Box<Scene>),
     CopyScene(Sender<Scene>),
     SetViewBox(RectF),
     Build(BuildOptions, Box<dyn RenderCommandListener>),
@@ -0,0 +1,698 @@
+// pathfinder/renderer/src/cpu/renderer.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders the commands produced by the scene builder into a framebuffer in memory.
+//!
+//! This follows the same steps as the GPU renderer: fills are accumulated into per-tile coverage
+//! masks, and then solid and alpha tiles are composited onto the framebuffer. Coverage is computed
+//! exactly rather than with a lookup table, so the output can serve as a reference for the GPU.
+
+use crate::gpu::options::RendererOptions;
+use crate::gpu_data::{AlphaTileBatchPrimitive, FillBatchPrimitive, PaintData, RenderCommand};
+use crate::gpu_data::{PAINT_KIND_GRADIENT, PAINT_KIND_PATTERN, SolidTileBatchPrimitive};
+use crate::mask::{accumulate_fill, coverage};
+use crate::paint::PAINT_METADATA_HEADER_LENGTH;
+use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
+use pathfinder_content::color::ColorF;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_content::gradient::GradientGeometry;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_simd::default::F32x4;
+use std::mem;
+
+/// A renderer that rasterizes on the CPU.
+///
+/// Subpixel antialiasing and its postprocessing passes aren't supported.
+pub struct Renderer {
+    size: Vector2I,
+    options: RendererOptions,
+    /// Premultiplied colors, in rows from top to bottom.
+    framebuffer: Vec<ColorF>,
+    /// The framebuffers beneath the current layer, innermost last.
+    layer_stack: Vec<Vec<ColorF>>,
+    paint_data: Option<PaintData>,
+    /// The size of tiles in the current scene.
+    tile_size: Vector2I,
+    /// The accumulated winding numbers of each alpha tile, indexed by alpha tile index.
+    masks: Vec<Vec<f32>>,
+}
+
+impl Renderer {
+    pub fn new(size: Vector2I, options: RendererOptions) -> Renderer {
+        let mut renderer = Renderer {
+            size,
+            options,
+            framebuffer: vec![],
+            layer_stack: vec![],
+            paint_data: None,
+            tile_size: Vector2I::new(TILE_WIDTH as i32, TILE_HEIGHT as i32),
+            masks: vec![],
+        };
+        renderer.clear();
+        renderer
+    }
+
+    pub fn render_command(&mut self, command: &RenderCommand) {
+        match *command {
+            RenderCommand::Start { tile_size, .. } => {
+                self.tile_size = tile_size;
+                self.clear();
+                self.layer_stack.clear();
+                self.masks.clear();
+            }
+            RenderCommand::AddPaintData(ref paint_data) => {
+                self.paint_data = Some(paint_data.clone())
+            }
+            RenderCommand::AddFills(ref fills) => {
+                for fill in fills {
+                    self.add_fill(fill);
+                }
+            }
+            RenderCommand::FlushFills | RenderCommand::Finish { .. } => {}
+            RenderCommand::SolidTile(ref solid_tiles) => {
+                for solid_tile in solid_tiles {
+                    self.draw_solid_tile(solid_tile);
+                }
+            }
+            RenderCommand::AlphaTile(ref alpha_tiles, blend_mode) => {
+                for alpha_tile in alpha_tiles {
+                    self.draw_alpha_tile(alpha_tile, blend_mode);
+                }
+            }
+            RenderCommand::PushLayer => {
+                let layer = vec![ColorF::transparent_black(); self.framebuffer.len()];
+                self.layer_stack.push(mem::replace(&mut self.framebuffer, layer));
+            }
+            RenderCommand::PopLayer { opacity, blur, blend_mode } => {
+                let mut layer = match self.layer_stack.pop() {
+                    None => return,
+                    Some(parent) => mem::replace(&mut self.framebuffer, parent),
+                };
+                if blur.x() > 0.0 {
+                    layer = self.blur(&layer, blur.x(), Vector2I::new(1, 0));
+                }
+                if blur.y() > 0.0 {
+                    layer = self.blur(&layer, blur.y(), Vector2I::new(0, 1));
+                }
+                for (dest, src) in self.framebuffer.iter_mut().zip(layer) {
+                    *dest = blend(blend_mode, ColorF(src.0 * F32x4::splat(opacity)), *dest);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vector2I {
+        self.size
+    }
+
+    /// Returns the contents of the framebuffer as RGBA8 pixels with straight alpha, in rows from
+    /// top to bottom.
+    pub fn pixels(&self) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(self.framebuffer.len() * 4);
+        for color in &self.framebuffer {
+            let color = if color.a() > 0.0 {
+                ColorF(color.0 * F32x4::new(color.a(), color.a(), color.a(), 1.0).approx_recip())
+            } else {
+                ColorF::transparent_black()
+            };
+            let color = color.to_u8();
+            pixels.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+        pixels
+    }
+
+    fn clear(&mut self) {
+        let background_color = self.options.background_color.unwrap_or_default();
+        let background_color = premultiply(background_color);
+        let pixel_count = self.size.x().max(0) as usize * self.size.y().max(0) as usize;
+        self.framebuffer.clear();
+        self.framebuffer.resize(pixel_count, background_color);
+    }
+
+    fn add_fill(&mut self, fill: &FillBatchPrimitive) {
+        let tile_index = fill.alpha_tile_index as usize;
+        if self.masks.len() <= tile_index {
+            let tile_area = self.tile_size.x() as usize * self.tile_size.y() as usize;
+            self.masks.resize(tile_index + 1, vec![0.0; tile_area]);
+        }
+        accumulate_fill(&mut self.masks[tile_index], fill, self.tile_size);
+    }
+
+    fn draw_solid_tile(&mut self, solid_tile: &SolidTileBatchPrimitive) {
+        let tile_origin = Vector2I::new(solid_tile.tile_x as i32, solid_tile.tile_y as i32)
+            .scale_xy(self.tile_size);
+        let paint_texel = Vector2I::new(solid_tile.paint_x as i32, solid_tile.paint_y as i32);
+
+        for y in 0..self.tile_size.y() {
+            for x in 0..self.tile_size.x() {
+                let position = tile_origin + Vector2I::new(x, y);
+                let framebuffer_index = match self.framebuffer_index(position) {
+                    None => continue,
+                    Some(framebuffer_index) => framebuffer_index,
+                };
+                let color = self.sample_paint(paint_texel, solid_tile.paint_kind, position);
+                self.framebuffer[framebuffer_index] = color;
+            }
+        }
+    }
+
+    fn draw_alpha_tile(&mut self, alpha_tile: &AlphaTileBatchPrimitive, blend_mode: BlendMode) {
+        let tile_origin = alpha_tile.tile_coords().scale_xy(self.tile_size);
+        let paint_texel = Vector2I::new(alpha_tile.paint_x as i32, alpha_tile.paint_y as i32);
+        let backdrop = alpha_tile.backdrop as f32;
+        let even_odd = alpha_tile.fill_rule != 0;
+
+        for y in 0..self.tile_size.y() {
+            for x in 0..self.tile_size.x() {
+                let position = tile_origin + Vector2I::new(x, y);
+                let framebuffer_index = match self.framebuffer_index(position) {
+                    None => continue,
+                    Some(framebuffer_index) => framebuffer_index,
+                };
+
+                let mask_index = (y * self.tile_size.x() + x) as usize;
+                let winding = match self.masks.get(alpha_tile.tile_index as usize) {
+                    Some(mask) => mask[mask_index] + backdrop,
+                    None => backdrop,
+                };
+                // As in the GPU renderer, the blend mode applies in proportion to coverage, so
+                // uncovered pixels are left alone even by modes like `SrcIn`.
+                let coverage = coverage(winding, even_odd);
+                if coverage == 0.0 {
+                    continue;
+                }
+
+                let src = self.sample_paint(paint_texel, alpha_tile.paint_kind, position);
+                let dest = self.framebuffer[framebuffer_index];
+                self.framebuffer[framebuffer_index] =
+                    dest.lerp(blend(blend_mode, src, dest), coverage);
+            }
+        }
+    }
+
+    fn framebuffer_index(&self, position: Vector2I) -> Option<usize> {
+        if position.x() < 0 || position.y() < 0 || position.x() >= self.size.x() ||
+                position.y() >= self.size.y() {
+            return None;
+        }
+        Some((position.y() * self.size.x() + position.x()) as usize)
+    }
+
+    // Blurs `pixels` along `direction` with a Gaussian of standard deviation `sigma`, as the blur
+    // shader does. Everything outside the framebuffer counts as transparent.
+    fn blur(&self, pixels: &[ColorF], sigma: f32, direction: Vector2I) -> Vec<ColorF> {
+        let radius = (sigma * 3.0).ceil() as i32;
+        let weights: Vec<f32> = (0..=radius).map(|offset| {
+            f32::exp(-(offset * offset) as f32 / (2.0 * sigma * sigma))
+        }).collect();
+        let weight_sum = weights[0] + weights[1..].iter().sum::<f32>() * 2.0;
+
+        let mut blurred = Vec::with_capacity(pixels.len());
+        for y in 0..self.size.y() {
+            for x in 0..self.size.x() {
+                let position = Vector2I::new(x, y);
+                let mut color = F32x4::default();
+                for offset in -radius..=radius {
+                    let weight = weights[offset.unsigned_abs() as usize];
+                    let sample_position = position + direction.scale(offset);
+                    if let Some(index) = self.framebuffer_index(sample_position) {
+                        color += pixels[index].0 * F32x4::splat(weight);
+                    }
+                }
+                blurred.push(ColorF(color * F32x4::splat(1.0 / weight_sum)));
+            }
+        }
+        blurred
+    }
+
+    // Returns the premultiplied color of a paint at the center of the given pixel, computed the
+    // same way as in the tile shaders.
+    fn sample_paint(&self, paint_texel: Vector2I, paint_kind: u8, position: Vector2I) -> ColorF {
+        let paint_data = match self.paint_data {
+            None => return ColorF::transparent_black(),
+            Some(ref paint_data) => paint_data,
+        };
+
+        let position = position.to_f32() + Vector2F::splat(0.5);
+        let metadata_index = paint_texel.y() * paint_data.metadata_size.x() + paint_texel.x();
+        match paint_kind {
+            PAINT_KIND_GRADIENT => sample_gradient(paint_data, metadata_index, position),
+            PAINT_KIND_PATTERN => sample_pattern(paint_data, metadata_index, position),
+            _ => premultiply(fetch_texel(paint_data, paint_texel)),
+        }
+    }
+}
+
+// See `paint.rs` for the layout of the metadata.
+fn sample_gradient(paint_data: &PaintData, index: i32, position: Vector2F) -> ColorF {
+    let point = transform_to_paint(paint_data, index, position);
+    let radii = fetch_metadata(paint_data, index + 1);
+    let line = fetch_metadata(paint_data, index + 2);
+    let info = fetch_metadata(paint_data, index + 3);
+
+    let line = LineSegment2F::new(Vector2F::new(line[0], line[1]), Vector2F::new(line[2], line[3]));
+    let geometry = if info[0] == 0.0 {
+        GradientGeometry::Linear(line)
+    } else {
+        GradientGeometry::Radial { line, start_radius: radii[2], end_radius: radii[3] }
+    };
+    let t = match geometry.t_at(point) {
+        None => return ColorF::transparent_black(),
+        Some(t) => t,
+    };
+
+    // Interpolate between the color stops around `t`, clamping to the end stops.
+    let stop_count = info[1] as i32;
+    if stop_count == 0 {
+        return ColorF::transparent_black();
+    }
+    let stop = |stop_index: i32| {
+        let stop_index = index + PAINT_METADATA_HEADER_LENGTH as i32 + stop_index * 2;
+        let color = fetch_metadata(paint_data, stop_index + 1);
+        (fetch_metadata(paint_data, stop_index)[0],
+         ColorF::new(color[0], color[1], color[2], color[3]))
+    };
+    let (mut prev_offset, mut prev_color) = stop(0);
+    if t <= prev_offset {
+        return premultiply(prev_color);
+    }
+    for stop_index in 1..stop_count {
+        let (offset, color) = stop(stop_index);
+        if offset > t {
+            let color = prev_color.lerp(color, (t - prev_offset) / (offset - prev_offset));
+            return premultiply(color);
+        }
+        prev_offset = offset;
+        prev_color = color;
+    }
+    premultiply(prev_color)
+}
+
+// Filters the pattern's image bilinearly, in premultiplied space, as `Pattern::color_at()` does.
+fn sample_pattern(paint_data: &PaintData, index: i32, position: Vector2F) -> ColorF {
+    let point = transform_to_paint(paint_data, index, position);
+    let size = fetch_metadata(paint_data, index + 1);
+    let image = fetch_metadata(paint_data, index + 2);
+    let opacity = fetch_metadata(paint_data, index + 3)[0];
+
+    let size = Vector2I::new(size[2] as i32, size[3] as i32);
+    if size.x() <= 0 || size.y() <= 0 {
+        return ColorF::transparent_black();
+    }
+    let image_origin = Vector2I::new(image[0] as i32, image[1] as i32);
+    let (repeat_x, repeat_y) = (image[2] != 0.0, image[3] != 0.0);
+    if (!repeat_x && (point.x() < 0.0 || point.x() >= size.x() as f32)) ||
+            (!repeat_y && (point.y() < 0.0 || point.y() >= size.y() as f32)) {
+        return ColorF::transparent_black();
+    }
+
+    // Sample relative to pixel centers.
+    let point = point - Vector2F::splat(0.5);
+    let origin = point.floor();
+    let fract = point - origin;
+    let origin = origin.to_i32();
+
+    let mut color = F32x4::default();
+    for &(offset, weight) in &[
+        (Vector2I::new(0, 0), (1.0 - fract.x()) * (1.0 - fract.y())),
+        (Vector2I::new(1, 0), fract.x() * (1.0 - fract.y())),
+        (Vector2I::new(0, 1), (1.0 - fract.x()) * fract.y()),
+        (Vector2I::new(1, 1), fract.x() * fract.y()),
+    ] {
+        let texel = origin + offset;
+        let texel = Vector2I::new(wrap_axis(texel.x(), size.x(), repeat_x),
+                                  wrap_axis(texel.y(), size.y(), repeat_y));
+        let texel = premultiply(fetch_texel(paint_data, image_origin + texel));
+        color += texel.0 * F32x4::splat(weight);
+    }
+    ColorF(color * F32x4::splat(opacity))
+}
+
+fn wrap_axis(value: i32, length: i32, repeat: bool) -> i32 {
+    if repeat {
+        value.rem_euclid(length)
+    } else {
+        value.max(0).min(length - 1)
+    }
+}
+
+// Applies the transform from device space to the paint's space at the start of its metadata.
+fn transform_to_paint(paint_data: &PaintData, index: i32, position: Vector2F) -> Vector2F {
+    let axes = fetch_metadata(paint_data, index);
+    let origin = fetch_metadata(paint_data, index + 1);
+    Vector2F::new(axes[0], axes[1]).scale(position.x()) +
+        Vector2F::new(axes[2], axes[3]).scale(position.y()) +
+        Vector2F::new(origin[0], origin[1])
+}
+
+fn fetch_metadata(paint_data: &PaintData, index: i32) -> [f32; 4] {
+    let offset = index as usize * 4;
+    let mut texel = [0.0; 4];
+    texel.copy_from_slice(&paint_data.metadata[offset..(offset + 4)]);
+    texel
+}
+
+// Returns the straight color of a texel of the paint texture.
+fn fetch_texel(paint_data: &PaintData, texel: Vector2I) -> ColorF {
+    let offset = (texel.y() * paint_data.size.x() + texel.x()) as usize * 4;
+    let texels = &paint_data.texels[offset..(offset + 4)];
+    ColorF(F32x4::new(texels[0] as f32,
+                      texels[1] as f32,
+                      texels[2] as f32,
+                      texels[3] as f32) * F32x4::splat(1.0 / 255.0))
+}
+
+fn premultiply(color: ColorF) -> ColorF {
+    ColorF(color.0 * F32x4::new(color.a(), color.a(), color.a(), 1.0))
+}
+
+// Composites premultiplied `src` onto premultiplied `dest`, as the GPU renderer does.
+fn blend(blend_mode: BlendMode, src: ColorF, dest: ColorF) -> ColorF {
+    let (src_alpha, dest_alpha) = (src.a(), dest.a());
+    let (src_factor, dest_factor) = match blend_mode {
+        BlendMode::SrcOver => (1.0, 1.0 - src_alpha),
+        BlendMode::DestOver => (1.0 - dest_alpha, 1.0),
+        BlendMode::SrcIn => (dest_alpha, 0.0),
+        BlendMode::DestIn => (0.0, src_alpha),
+        BlendMode::SrcOut => (1.0 - dest_alpha, 0.0),
+        BlendMode::DestOut => (0.0, 1.0 - src_alpha),
+        BlendMode::SrcAtop => (dest_alpha, 1.0 - src_alpha),
+        BlendMode::DestAtop => (1.0 - dest_alpha, src_alpha),
+        BlendMode::Xor => (1.0 - dest_alpha, 1.0 - src_alpha),
+        BlendMode::Lighter => (1.0, 1.0),
+        BlendMode::Copy => (1.0, 0.0),
+        BlendMode::Multiply |
+        BlendMode::Screen |
+        BlendMode::Overlay |
+        BlendMode::Darken |
+        BlendMode::Lighten |
+        BlendMode::ColorDodge |
+        BlendMode::ColorBurn |
+        BlendMode::HardLight |
+        BlendMode::SoftLight |
+        BlendMode::Difference |
+        BlendMode::Exclusion => return blend_separable(blend_mode, src, dest),
+    };
+    let color = src.0 * F32x4::splat(src_factor) + dest.0 * F32x4::splat(dest_factor);
+    ColorF(color.min(F32x4::splat(1.0)))
+}
+
+// Composites with source-over after mixing colors with a separable blend mode, per the W3C
+// "Compositing and Blending" specification.
+fn blend_separable(blend_mode: BlendMode, src: ColorF, dest: ColorF) -> ColorF {
+    let (src_alpha, dest_alpha) = (src.a(), dest.a());
+    let unpremultiply = |color: ColorF, channel: f32| {
+        if color.a() > 0.0 { channel / color.a() } else { 0.0 }
+    };
+
+    let mut channels = [0.0; 4];
+    for (index, &(src_channel, dest_channel)) in [
+        (src.r(), dest.r()),
+        (src.g(), dest.g()),
+        (src.b(), dest.b()),
+    ].iter().enumerate() {
+        let blended = blend_channel(blend_mode,
+                                    unpremultiply(dest, dest_channel),
+                                    unpremultiply(src, src_channel));
+        channels[index] = src_channel * (1.0 - dest_alpha) + dest_channel * (1.0 - src_alpha) +
+            blended * src_alpha * dest_alpha;
+    }
+    channels[3] = src_alpha + dest_alpha * (1.0 - src_alpha);
+    ColorF::new(channels[0], channels[1], channels[2], channels[3])
+}
+
+fn blend_channel(blend_mode: BlendMode, dest: f32, src: f32) -> f32 {
+    fn screen(dest: f32, src: f32) -> f32 {
+        dest + src - dest * src
+    }
+    fn hard_light(dest: f32, src: f32) -> f32 {
+        if src <= 0.5 { dest * 2.0 * src } else { screen(dest, 2.0 * src - 1.0) }
+    }
+
+    match blend_mode {
+        BlendMode::Multiply => dest * src,
+        BlendMode::Screen => screen(dest, src),
+        BlendMode::Overlay => hard_light(src, dest),
+        BlendMode::Darken => dest.min(src),
+        BlendMode::Lighten => dest.max(src),
+        BlendMode::ColorDodge => {
+            if dest == 0.0 {
+                0.0
+            } else if src >= 1.0 {
+                1.0
+            } else {
+                (dest / (1.0 - src)).min(1.0)
+            }
+        }
+        BlendMode::ColorBurn => {
+            if dest >= 1.0 {
+                1.0
+            } else if src <= 0.0 {
+                0.0
+            } else {
+                1.0 - ((1.0 - dest) / src).min(1.0)
+            }
+        }
+        BlendMode::HardLight => hard_light(dest, src),
+        BlendMode::SoftLight => {
+            if src <= 0.5 {
+                dest - (1.0 - 2.0 * src) * dest * (1.0 - dest)
+            } else {
+                let d = if dest <= 0.25 {
+                    ((16.0 * dest - 12.0) * dest + 4.0) * dest
+                } else {
+                    dest.sqrt()
+                };
+                dest + (2.0 * src - 1.0) * (d - dest)
+            }
+        }
+        BlendMode::Difference => (dest - src).abs(),
+        BlendMode::Exclusion => dest + src - 2.0 * dest * src,
+        BlendMode::SrcOver |
+        BlendMode::SrcIn |
+        BlendMode::SrcOut |
+        BlendMode::SrcAtop |
+        BlendMode::DestOver |
+        BlendMode::DestIn |
+        BlendMode::DestOut |
+        BlendMode::DestAtop |
+        BlendMode::Xor |
+        BlendMode::Lighter |
+        BlendMode::Copy => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Renderer;
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu::options::RendererOptions;
+    use crate::options::{BuildOptions, RasterizeOptions};
+    use crate::paint::Paint;
+    use crate::scene::{PathObject, Scene};
+    use pathfinder_content::color::{ColorF, ColorU};
+    use pathfinder_content::effects::BlendMode;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use pathfinder_simd::default::F32x4;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_render_rect() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(32.0)));
+        let paint = scene.push_paint(&Paint::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+
+        // Cover half of the pixels in the column at x = 4.
+        let mut contour = Contour::new();
+        for &(x, y) in &[(4.5, 2.0), (20.0, 2.0), (20.0, 30.0), (4.5, 30.0)] {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        scene.push_path(PathObject::new(outline, paint, String::new()));
+
+        // A translucent rectangle that doesn't overlap the first one.
+        let paint = scene.push_paint(&Paint::Color(ColorU { r: 0, g: 255, b: 0, a: 128 }));
+        let mut contour = Contour::new();
+        for &(x, y) in &[(24.0, 2.0), (30.0, 2.0), (30.0, 30.0), (24.0, 30.0)] {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        scene.push_path(PathObject::new(outline, paint, String::new()));
+
+        let commands = Arc::new(Mutex::new(vec![]));
+        let listener_commands = commands.clone();
+        scene.build(BuildOptions::default(),
+                    Box::new(move |command| listener_commands.lock().unwrap().push(command)),
+                    &SequentialExecutor);
+
+        let options = RendererOptions { background_color: Some(ColorF::white()) };
+        let mut renderer = Renderer::new(Vector2I::splat(32), options);
+        for command in commands.lock().unwrap().iter() {
+            renderer.render_command(command);
+        }
+
+        let pixels = renderer.pixels();
+        let pixel = |x: usize, y: usize| &pixels[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+        assert_eq!(pixel(10, 10), &[255, 0, 0, 255]);
+        assert_eq!(pixel(2, 10), &[255, 255, 255, 255]);
+        assert_eq!(pixel(10, 31), &[255, 255, 255, 255]);
+        assert!((pixel(4, 10)[1] as i32 - 128).abs() <= 1);
+        assert!((pixel(26, 10)[0] as i32 - 127).abs() <= 1);
+
+        // Without a background, translucent colors come out unpremultiplied.
+        let mut renderer = Renderer::new(Vector2I::splat(32), RendererOptions::default());
+        for command in commands.lock().unwrap().iter() {
+            renderer.render_command(command);
+        }
+        let pixels = renderer.pixels();
+        let pixel = |x: usize, y: usize| &pixels[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+        assert_eq!(pixel(10, 10), &[255, 0, 0, 255]);
+        assert_eq!(pixel(2, 10), &[0, 0, 0, 0]);
+        for (&actual, &expected) in pixel(26, 10).iter().zip(&[0, 255, 0, 128]) {
+            assert!((actual as i32 - expected).abs() <= 1);
+        }
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_rasterize() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(32.0)));
+        let paint = scene.push_paint(&Paint::Color(ColorU { r: 0, g: 0, b: 255, a: 255 }));
+        let mut contour = Contour::new();
+        for &(x, y) in &[(8.0, 8.0), (24.0, 8.0), (24.0, 24.0), (8.0, 24.0)] {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        scene.push_path(PathObject::new(outline, paint, String::new()));
+
+        let options = RasterizeOptions {
+            background_color: Some(ColorF::white()),
+            ..RasterizeOptions::default()
+        };
+        let image = scene.rasterize(Vector2I::new(32, 16), options.clone());
+        assert_eq!(image.dimensions(), (32, 16));
+        assert_eq!(image.get_pixel(16, 12).data, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(4, 12).data, [255, 255, 255, 255]);
+        assert_eq!(image.into_raw(), scene.rasterize_to_pixels(Vector2I::new(32, 16), options));
+    }
+
+    #[test]
+    fn test_tile_sizes() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(64.0)));
+        let paint = scene.push_paint(&Paint::Color(ColorU { r: 0, g: 0, b: 0, a: 255 }));
+        let mut contour = Contour::new();
+        contour.push_ellipse(&Transform2F::from_scale(Vector2F::new(25.0, 18.0))
+                                          .translate(Vector2F::new(30.0, 34.0)));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        scene.push_path(PathObject::new(outline, paint, String::new()));
+
+        let render = |tile_size: Vector2I| {
+            let options = RasterizeOptions {
+                build_options: BuildOptions { tile_size, ..BuildOptions::default() },
+                ..RasterizeOptions::default()
+            };
+            scene.rasterize_to_pixels(Vector2I::splat(64), options)
+        };
+        let expected = render(Vector2I::splat(16));
+        for &tile_size in &[Vector2I::splat(8), Vector2I::splat(32), Vector2I::new(32, 8)] {
+            // Fills are quantized more coarsely in larger tiles.
+            for (&actual, &expected) in render(tile_size).iter().zip(expected.iter()) {
+                assert!((actual as i32 - expected as i32).abs() <= 2);
+            }
+        }
+
+        // Out-of-range tile sizes are clamped.
+        assert_eq!(render(Vector2I::new(1, 1000)), render(Vector2I::new(4, 32)));
+    }
+
+    #[test]
+    fn test_porter_duff_coverage() {
+        fn rect_outline(left: f32, right: f32) -> Outline {
+            let mut contour = Contour::new();
+            for &(x, y) in &[(left, 0.0), (right, 0.0), (right, 16.0), (left, 16.0)] {
+                contour.push_endpoint(Vector2F::new(x, y));
+            }
+            contour.close();
+            let mut outline = Outline::new();
+            outline.push_contour(contour);
+            outline
+        }
+
+        let dest = ColorU { r: 255, g: 0, b: 0, a: 204 }.to_f32();
+        let src = ColorU { r: 0, g: 0, b: 255, a: 128 }.to_f32();
+        let premultiply = |color: ColorF| {
+            ColorF(color.0 * F32x4::new(color.a(), color.a(), color.a(), 1.0))
+        };
+        let (premultiplied_dest, premultiplied_src) = (premultiply(dest), premultiply(src));
+
+        for &(blend_mode, src_factor, dest_factor) in &[
+            (BlendMode::SrcIn, dest.a(), 0.0),
+            (BlendMode::SrcOut, 1.0 - dest.a(), 0.0),
+            (BlendMode::SrcAtop, dest.a(), 1.0 - src.a()),
+            (BlendMode::DestOver, 1.0 - dest.a(), 1.0),
+            (BlendMode::DestIn, 0.0, src.a()),
+            (BlendMode::DestOut, 0.0, 1.0 - src.a()),
+            (BlendMode::DestAtop, 1.0 - dest.a(), src.a()),
+            (BlendMode::Xor, 1.0 - dest.a(), 1.0 - src.a()),
+            (BlendMode::Lighter, 1.0, 1.0),
+            (BlendMode::Copy, 1.0, 0.0),
+        ] {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(16.0)));
+            let dest_paint = scene.push_paint(&Paint::Color(dest.to_u8()));
+            let src_paint = scene.push_paint(&Paint::Color(src.to_u8()));
+            scene.push_path(PathObject::new(rect_outline(0.0, 16.0), dest_paint, String::new()));
+            // The source covers half of the pixels in the column at x = 4.
+            let mut path = PathObject::new(rect_outline(4.5, 12.0), src_paint, String::new());
+            path.set_blend_mode(blend_mode);
+            scene.push_path(path);
+
+            let pixels = scene.rasterize_to_pixels(Vector2I::splat(16),
+                                                   RasterizeOptions::default());
+            let blended = premultiplied_src.0 * F32x4::splat(src_factor) +
+                premultiplied_dest.0 * F32x4::splat(dest_factor);
+            let blended = ColorF(blended.min(F32x4::splat(1.0)));
+            for &(x, coverage) in &[(2, 0.0), (4, 0.5), (8, 1.0)] {
+                // The rasterized pixels are unpremultiplied.
+                let expected = premultiplied_dest.lerp(blended, coverage);
+                let alpha = expected.a();
+                let expected = if alpha > 0.0 {
+                    ColorF(expected.0 * F32x4::new(1.0 / alpha, 1.0 / alpha, 1.0 / alpha, 1.0))
+                } else {
+                    ColorF::transparent_black()
+                }.to_u8();
+                let expected = [expected.r, expected.g, expected.b, expected.a];
+                let actual = &pixels[(8 * 16 + x) * 4..(8 * 16 + x) * 4 + 4];
+                assert!(actual.iter().zip(&expected).all(|(&actual, &expected)| {
+                            (actual as i32 - expected as i32).abs() <= 2
+                        }),
+                        "{:?} at x = {}: expected {:?} but got {:?}",
+                        blend_mode,
+                        x,
+                        expected,
+                        actual);
+            }
+        }
+    }
+}
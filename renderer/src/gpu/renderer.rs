@@ -14,16 +14,18 @@ use crate::gpu_data::{AlphaTileBatchPrimitive, FillBatchPrimitive, PaintData};
 use crate::gpu_data::{RenderCommand, SolidTileBatchPrimitive};
 use crate::post::DefringingKernel;
 use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
-use pathfinder_geometry::vector::{Vector2I, Vector4F};
+use pathfinder_geometry::vector::{Vector2F, Vector2I, Vector4F};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_content::color::ColorF;
+use pathfinder_content::effects::BlendMode;
 use pathfinder_gpu::resources::ResourceLoader;
-use pathfinder_gpu::{BlendFunc, BlendState, BufferData, BufferTarget, BufferUploadMode, ClearOps};
-use pathfinder_gpu::{DepthFunc, DepthState, Device, Primitive, RenderOptions, RenderState};
+use pathfinder_gpu::{BlendFactor, BlendOp, BlendState, BufferData, BufferTarget};
+use pathfinder_gpu::{BufferUploadMode, ClearOps, DepthFunc, DepthState, Device, Primitive};
+use pathfinder_gpu::{RenderOptions, RenderState};
 use pathfinder_gpu::{RenderTarget, StencilFunc, StencilState, TextureDataRef, TextureFormat};
 use pathfinder_gpu::{UniformData, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
-use pathfinder_simd::default::{F32x2, F32x4};
+use pathfinder_simd::default::F32x4;
 use std::cmp;
 use std::collections::VecDeque;
 use std::mem;
@@ -34,14 +36,15 @@ use std::u32;
 static QUAD_VERTEX_POSITIONS: [u16; 8] = [0, 0, 1, 0, 1, 1, 0, 1];
 static QUAD_VERTEX_INDICES: [u32; 6] = [0, 1, 3, 1, 2, 3];
 
+// The mask framebuffer is at most this many pixels in each direction, holding as many whole tiles
+// as fit. With the default 16×16 tiles, that's 256 tiles each way.
 // FIXME(pcwalton): Shrink this again!
-const MASK_FRAMEBUFFER_WIDTH: i32 = TILE_WIDTH as i32 * 256;
-const MASK_FRAMEBUFFER_HEIGHT: i32 = TILE_HEIGHT as i32 * 256;
+const MASK_FRAMEBUFFER_LENGTH: i32 = 4096;
 
 // TODO(pcwalton): Replace with `mem::size_of` calls?
 const FILL_INSTANCE_SIZE: usize = 8;
 const SOLID_TILE_INSTANCE_SIZE: usize = 12;
-const MASK_TILE_INSTANCE_SIZE: usize = 12;
+const MASK_TILE_INSTANCE_SIZE: usize = 16;
 
 const MAX_FILLS_PER_BATCH: usize = 0x4000;
 
@@ -60,16 +63,20 @@ where
     alpha_multicolor_tile_program: AlphaTileMulticolorProgram<D>,
     solid_monochrome_tile_program: SolidTileMonochromeProgram<D>,
     alpha_monochrome_tile_program: AlphaTileMonochromeProgram<D>,
+    alpha_blend_tile_program: AlphaTileBlendProgram<D>,
     solid_multicolor_tile_vertex_array: SolidTileVertexArray<D>,
     alpha_multicolor_tile_vertex_array: AlphaTileVertexArray<D>,
     solid_monochrome_tile_vertex_array: SolidTileVertexArray<D>,
     alpha_monochrome_tile_vertex_array: AlphaTileVertexArray<D>,
+    alpha_blend_tile_vertex_array: AlphaTileVertexArray<D>,
     area_lut_texture: D::Texture,
     quad_vertex_positions_buffer: D::Buffer,
     quad_vertex_indices_buffer: D::Buffer,
     fill_vertex_array: FillVertexArray<D>,
     mask_framebuffer: D::Framebuffer,
+    tile_size: Vector2I,
     paint_texture: Option<D::Texture>,
+    paint_metadata_texture: Option<D::Texture>,
 
     // Postprocessing shader
     postprocess_source_framebuffer: Option<D::Framebuffer>,
@@ -77,6 +84,20 @@ where
     postprocess_vertex_array: PostprocessVertexArray<D>,
     gamma_lut_texture: D::Texture,
 
+    // Blend mode support
+    intermediate_dest_framebuffer: Option<D::Framebuffer>,
+    dest_blend_framebuffer: Option<D::Framebuffer>,
+    blit_program: BlitProgram<D>,
+    blit_vertex_array: BlitVertexArray<D>,
+
+    // Layers
+    layer_framebuffers: Vec<D::Framebuffer>,
+    free_layer_framebuffers: Vec<D::Framebuffer>,
+    blur_program: BlurProgram<D>,
+    blur_vertex_array: BlurVertexArray<D>,
+    blit_blend_program: BlitBlendProgram<D>,
+    blit_blend_vertex_array: BlitBlendVertexArray<D>,
+
     // Stencil shader
     stencil_program: StencilProgram<D>,
     stencil_vertex_array: StencilVertexArray<D>,
@@ -116,7 +137,11 @@ where
         let alpha_multicolor_tile_program = AlphaTileMulticolorProgram::new(&device, resources);
         let solid_monochrome_tile_program = SolidTileMonochromeProgram::new(&device, resources);
         let alpha_monochrome_tile_program = AlphaTileMonochromeProgram::new(&device, resources);
+        let alpha_blend_tile_program = AlphaTileBlendProgram::new(&device, resources);
 
+        let blit_program = BlitProgram::new(&device, resources);
+        let blur_program = BlurProgram::new(&device, resources);
+        let blit_blend_program = BlitBlendProgram::new(&device, resources);
         let postprocess_program = PostprocessProgram::new(&device, resources);
         let stencil_program = StencilProgram::new(&device, resources);
         let reprojection_program = ReprojectionProgram::new(&device, resources);
@@ -169,6 +194,30 @@ where
             &quad_vertex_positions_buffer,
             &quad_vertex_indices_buffer,
         );
+        let alpha_blend_tile_vertex_array = AlphaTileVertexArray::new(
+            &device,
+            &alpha_blend_tile_program.alpha_tile_program,
+            &quad_vertex_positions_buffer,
+            &quad_vertex_indices_buffer,
+        );
+        let blit_vertex_array = BlitVertexArray::new(
+            &device,
+            &blit_program,
+            &quad_vertex_positions_buffer,
+            &quad_vertex_indices_buffer,
+        );
+        let blur_vertex_array = BlurVertexArray::new(
+            &device,
+            &blur_program,
+            &quad_vertex_positions_buffer,
+            &quad_vertex_indices_buffer,
+        );
+        let blit_blend_vertex_array = BlitBlendVertexArray::new(
+            &device,
+            &blit_blend_program,
+            &quad_vertex_positions_buffer,
+            &quad_vertex_indices_buffer,
+        );
         let postprocess_vertex_array = PostprocessVertexArray::new(
             &device,
             &postprocess_program,
@@ -183,11 +232,8 @@ where
             &quad_vertex_indices_buffer,
         );
 
-        let mask_framebuffer_size =
-            Vector2I::new(MASK_FRAMEBUFFER_WIDTH, MASK_FRAMEBUFFER_HEIGHT);
-        let mask_framebuffer_texture =
-            device.create_texture(TextureFormat::R16F, mask_framebuffer_size);
-        let mask_framebuffer = device.create_framebuffer(mask_framebuffer_texture);
+        let tile_size = Vector2I::new(TILE_WIDTH as i32, TILE_HEIGHT as i32);
+        let mask_framebuffer = create_mask_framebuffer(&device, tile_size);
 
         let window_size = dest_framebuffer.window_size(&device);
         let debug_ui_presenter = DebugUIPresenter::new(&device, resources, window_size);
@@ -202,22 +248,38 @@ where
             alpha_monochrome_tile_program,
             solid_multicolor_tile_program,
             alpha_multicolor_tile_program,
+            alpha_blend_tile_program,
             solid_monochrome_tile_vertex_array,
             alpha_monochrome_tile_vertex_array,
             solid_multicolor_tile_vertex_array,
             alpha_multicolor_tile_vertex_array,
+            alpha_blend_tile_vertex_array,
             area_lut_texture,
             quad_vertex_positions_buffer,
             quad_vertex_indices_buffer,
             fill_vertex_array,
             mask_framebuffer,
+            tile_size,
             paint_texture: None,
+            paint_metadata_texture: None,
 
             postprocess_source_framebuffer: None,
             postprocess_program,
             postprocess_vertex_array,
             gamma_lut_texture,
 
+            intermediate_dest_framebuffer: None,
+            dest_blend_framebuffer: None,
+            blit_program,
+            blit_vertex_array,
+
+            layer_framebuffers: vec![],
+            free_layer_framebuffers: vec![],
+            blur_program,
+            blur_vertex_array,
+            blit_blend_program,
+            blit_blend_vertex_array,
+
             stencil_program,
             stencil_vertex_array,
 
@@ -247,7 +309,17 @@ where
 
     pub fn render_command(&mut self, command: &RenderCommand) {
         match *command {
-            RenderCommand::Start { bounding_quad, path_count } => {
+            RenderCommand::Start {
+                bounding_quad,
+                path_count,
+                needs_readable_framebuffer,
+                tile_size,
+            } => {
+                if tile_size != self.tile_size {
+                    self.mask_framebuffer = create_mask_framebuffer(&self.device, tile_size);
+                    self.tile_size = tile_size;
+                }
+                self.init_intermediate_dest_framebuffer(needs_readable_framebuffer);
                 if self.use_depth {
                     self.draw_stencil(&bounding_quad);
                 }
@@ -265,17 +337,28 @@ where
                 self.upload_solid_tiles(solid_tiles);
                 self.draw_solid_tiles(count as u32);
             }
-            RenderCommand::AlphaTile(ref alpha_tiles) => {
+            RenderCommand::AlphaTile(ref alpha_tiles, blend_mode) => {
                 let count = alpha_tiles.len();
                 self.stats.alpha_tile_count += count;
-                self.upload_alpha_tiles(alpha_tiles);
-                self.draw_alpha_tiles(count as u32);
+                self.upload_alpha_tiles(alpha_tiles, blend_mode);
+                if self.blend_mode_reads_dest_framebuffer(blend_mode) {
+                    self.draw_alpha_tiles_with_dest_texture(count as u32, blend_mode);
+                } else {
+                    self.draw_alpha_tiles(count as u32, blend_mode);
+                }
+            }
+            RenderCommand::PushLayer => self.push_layer(),
+            RenderCommand::PopLayer { opacity, blur, blend_mode } => {
+                self.pop_layer(opacity, blur, blend_mode)
             }
             RenderCommand::Finish { .. } => {}
         }
     }
 
     pub fn end_scene(&mut self) {
+        if self.intermediate_dest_framebuffer.is_some() {
+            self.composite_intermediate_dest_framebuffer();
+        }
         if self.postprocessing_needed() {
             self.postprocess();
         }
@@ -376,10 +459,22 @@ where
                 self.paint_texture = Some(texture)
             }
         }
+        match self.paint_metadata_texture {
+            Some(ref paint_metadata_texture) if
+                self.device.texture_size(paint_metadata_texture) == paint_data.metadata_size => {}
+            _ => {
+                let texture = self.device.create_texture(TextureFormat::RGBA32F,
+                                                         paint_data.metadata_size);
+                self.paint_metadata_texture = Some(texture)
+            }
+        }
 
         self.device.upload_to_texture(self.paint_texture.as_ref().unwrap(),
                                       RectI::new(Vector2I::default(), paint_data.size),
                                       TextureDataRef::U8(&paint_data.texels));
+        self.device.upload_to_texture(self.paint_metadata_texture.as_ref().unwrap(),
+                                      RectI::new(Vector2I::default(), paint_data.metadata_size),
+                                      TextureDataRef::F32(&paint_data.metadata));
     }
 
     fn upload_solid_tiles(&mut self, solid_tiles: &[SolidTileBatchPrimitive]) {
@@ -391,9 +486,16 @@ where
         );
     }
 
-    fn upload_alpha_tiles(&mut self, alpha_tiles: &[AlphaTileBatchPrimitive]) {
+    fn upload_alpha_tiles(&mut self,
+                          alpha_tiles: &[AlphaTileBatchPrimitive],
+                          blend_mode: BlendMode) {
+        let alpha_tile_vertex_array = if self.blend_mode_reads_dest_framebuffer(blend_mode) {
+            &self.alpha_blend_tile_vertex_array
+        } else {
+            self.alpha_tile_vertex_array()
+        };
         self.device.allocate_buffer(
-            &self.alpha_tile_vertex_array().vertex_buffer,
+            &alpha_tile_vertex_array.vertex_buffer,
             BufferData::Memory(&alpha_tiles),
             BufferTarget::Vertex,
             BufferUploadMode::Dynamic,
@@ -447,16 +549,18 @@ where
             textures: &[&self.area_lut_texture],
             uniforms: &[
                 (&self.fill_program.framebuffer_size_uniform,
-                 UniformData::Vec2(F32x2::new(MASK_FRAMEBUFFER_WIDTH as f32,
-                                              MASK_FRAMEBUFFER_HEIGHT as f32))),
+                 UniformData::Vec2(self.mask_framebuffer_size().to_f32().0)),
                 (&self.fill_program.tile_size_uniform,
-                 UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
+                 UniformData::Vec2(self.tile_size.to_f32().0)),
                 (&self.fill_program.area_lut_uniform, UniformData::TextureUnit(0)),
             ],
             viewport: self.mask_viewport(),
             options: RenderOptions {
                 blend: Some(BlendState {
-                    func: BlendFunc::RGBOneAlphaOne,
+                    src_rgb_factor: BlendFactor::One,
+                    dest_rgb_factor: BlendFactor::One,
+                    src_alpha_factor: BlendFactor::One,
+                    dest_alpha_factor: BlendFactor::One,
                     ..BlendState::default()
                 }),
                 clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
@@ -471,13 +575,18 @@ where
         self.buffered_fills.clear();
     }
 
+    #[inline]
+    fn mask_framebuffer_size(&self) -> Vector2I {
+        mask_framebuffer_size(self.tile_size)
+    }
+
     fn tile_transform(&self) -> Transform4F {
         let draw_viewport = self.draw_viewport().size().to_f32();
         let scale = Vector4F::new(2.0 / draw_viewport.x(), -2.0 / draw_viewport.y(), 1.0, 1.0);
         Transform4F::from_scale(scale).translate(Vector4F::new(-1.0, 1.0, 0.0, 1.0))
     }
 
-    fn draw_alpha_tiles(&mut self, count: u32) {
+    fn draw_alpha_tiles(&mut self, count: u32, blend_mode: BlendMode) {
         let clear_color = self.clear_color_for_draw_operation();
 
         let alpha_tile_vertex_array = self.alpha_tile_vertex_array();
@@ -488,24 +597,20 @@ where
             (&alpha_tile_program.transform_uniform,
              UniformData::Mat4(self.tile_transform().to_columns())),
             (&alpha_tile_program.tile_size_uniform,
-             UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
+             UniformData::Vec2(self.tile_size.to_f32().0)),
             (&alpha_tile_program.stencil_texture_uniform, UniformData::TextureUnit(0)),
             (&alpha_tile_program.stencil_texture_size_uniform,
-             UniformData::Vec2(F32x2::new(MASK_FRAMEBUFFER_WIDTH as f32,
-                                          MASK_FRAMEBUFFER_HEIGHT as f32))),
+             UniformData::Vec2(self.mask_framebuffer_size().to_f32().0)),
         ];
 
         match self.render_mode {
             RenderMode::Multicolor => {
-                let paint_texture = self.paint_texture.as_ref().unwrap();
-                textures.push(paint_texture);
+                textures.push(self.paint_texture.as_ref().unwrap());
+                textures.push(self.paint_metadata_texture.as_ref().unwrap());
                 uniforms.push((&self.alpha_multicolor_tile_program.paint_texture_uniform,
                                UniformData::TextureUnit(1)));
-                uniforms.push((&self.alpha_multicolor_tile_program.paint_texture_size_uniform,
-                               UniformData::Vec2(self.device
-                                                     .texture_size(paint_texture)
-                                                     .0
-                                                     .to_f32x2())));
+                uniforms.push((&self.alpha_multicolor_tile_program.paint_metadata_texture_uniform,
+                               UniformData::TextureUnit(2)));
             }
             RenderMode::Monochrome { .. } if self.postprocessing_needed() => {
                 uniforms.push((&self.alpha_monochrome_tile_program.color_uniform,
@@ -526,19 +631,296 @@ where
             uniforms: &uniforms,
             viewport: self.draw_viewport(),
             options: RenderOptions {
-                blend: Some(BlendState {
-                    func: BlendFunc::RGBSrcAlphaAlphaOneMinusSrcAlpha,
-                    ..BlendState::default()
-                }),
+                blend: Some(blend_mode.to_blend_state()),
+                stencil: self.stencil_state(),
+                clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                ..RenderOptions::default()
+            },
+        });
+
+        self.preserve_draw_framebuffer();
+    }
+
+    // Draws alpha tiles with a blend mode that the blending hardware can't express. The shader
+    // reads a copy of the destination and does all of the compositing itself.
+    fn draw_alpha_tiles_with_dest_texture(&mut self, count: u32, blend_mode: BlendMode) {
+        self.copy_intermediate_dest_framebuffer();
+
+        let alpha_blend_tile_program = &self.alpha_blend_tile_program;
+        let alpha_tile_program = &alpha_blend_tile_program.alpha_tile_program;
+        let dest_blend_framebuffer = self.dest_blend_framebuffer.as_ref().unwrap();
+        let draw_viewport = self.draw_viewport();
+
+        self.device.draw_elements_instanced(6, count, &RenderState {
+            target: &self.draw_render_target(),
+            program: &alpha_tile_program.program,
+            vertex_array: &self.alpha_blend_tile_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[
+                self.device.framebuffer_texture(&self.mask_framebuffer),
+                self.paint_texture.as_ref().unwrap(),
+                self.device.framebuffer_texture(dest_blend_framebuffer),
+                self.paint_metadata_texture.as_ref().unwrap(),
+            ],
+            uniforms: &[
+                (&alpha_tile_program.transform_uniform,
+                 UniformData::Mat4(self.tile_transform().to_columns())),
+                (&alpha_tile_program.tile_size_uniform,
+                 UniformData::Vec2(self.tile_size.to_f32().0)),
+                (&alpha_tile_program.stencil_texture_uniform, UniformData::TextureUnit(0)),
+                (&alpha_tile_program.stencil_texture_size_uniform,
+                 UniformData::Vec2(self.mask_framebuffer_size().to_f32().0)),
+                (&alpha_blend_tile_program.paint_texture_uniform, UniformData::TextureUnit(1)),
+                (&alpha_blend_tile_program.dest_texture_uniform, UniformData::TextureUnit(2)),
+                (&alpha_blend_tile_program.paint_metadata_texture_uniform,
+                 UniformData::TextureUnit(3)),
+                (&alpha_blend_tile_program.framebuffer_size_uniform,
+                 UniformData::Vec2(draw_viewport.size().to_f32().0)),
+                (&alpha_blend_tile_program.blend_mode_uniform,
+                 UniformData::Int(blend_mode.to_shader_blend_mode())),
+            ],
+            viewport: draw_viewport,
+            options: RenderOptions {
+                stencil: self.stencil_state(),
+                ..RenderOptions::default()
+            },
+        });
+
+        self.preserve_draw_framebuffer();
+    }
+
+    // Copies the framebuffer being drawn to, which is either the current layer or the
+    // intermediate destination framebuffer, so that a shader can read it while drawing to it.
+    fn copy_intermediate_dest_framebuffer(&mut self) {
+        let size = self.draw_viewport().size();
+        match self.dest_blend_framebuffer {
+            Some(ref framebuffer)
+                if self.device.texture_size(self.device.framebuffer_texture(framebuffer)) ==
+                    size => {}
+            _ => {
+                let texture = self.device.create_texture(TextureFormat::RGBA8, size);
+                self.dest_blend_framebuffer = Some(self.device.create_framebuffer(texture));
+            }
+        }
+
+        // If nothing has been drawn yet, clear to the background first so that there's something
+        // to copy.
+        if let Some(clear_color) = self.clear_color_for_draw_operation() {
+            let dest_blend_framebuffer = self.dest_blend_framebuffer.as_ref().unwrap();
+            self.device.draw_elements(6, &RenderState {
+                target: &self.draw_render_target(),
+                program: &self.blit_program.program,
+                vertex_array: &self.blit_vertex_array.vertex_array,
+                primitive: Primitive::Triangles,
+                textures: &[self.device.framebuffer_texture(dest_blend_framebuffer)],
+                uniforms: &[
+                    (&self.blit_program.source_uniform, UniformData::TextureUnit(0)),
+                    (&self.blit_program.opacity_uniform, UniformData::Float(1.0)),
+                ],
+                viewport: self.draw_viewport(),
+                options: RenderOptions {
+                    color_mask: false,
+                    clear_ops: ClearOps { color: Some(clear_color), ..ClearOps::default() },
+                    ..RenderOptions::default()
+                },
+            });
+            self.preserve_draw_framebuffer();
+        }
+
+        let source_framebuffer = match self.layer_framebuffers.last() {
+            Some(layer_framebuffer) => layer_framebuffer,
+            None => self.intermediate_dest_framebuffer.as_ref().unwrap(),
+        };
+        let dest_blend_framebuffer = self.dest_blend_framebuffer.as_ref().unwrap();
+        self.device.draw_elements(6, &RenderState {
+            target: &RenderTarget::Framebuffer(dest_blend_framebuffer),
+            program: &self.blit_program.program,
+            vertex_array: &self.blit_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[self.device.framebuffer_texture(source_framebuffer)],
+            uniforms: &[
+                (&self.blit_program.source_uniform, UniformData::TextureUnit(0)),
+                (&self.blit_program.opacity_uniform, UniformData::Float(1.0)),
+            ],
+            viewport: RectI::new(Vector2I::default(), size),
+            options: RenderOptions::default(),
+        });
+    }
+
+    // Composites the finished scene in the intermediate framebuffer onto the real destination.
+    fn composite_intermediate_dest_framebuffer(&mut self) {
+        let intermediate_dest_framebuffer = self.intermediate_dest_framebuffer.as_ref().unwrap();
+        self.device.draw_elements(6, &RenderState {
+            target: &self.dest_render_target(),
+            program: &self.blit_program.program,
+            vertex_array: &self.blit_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[self.device.framebuffer_texture(intermediate_dest_framebuffer)],
+            uniforms: &[
+                (&self.blit_program.source_uniform, UniformData::TextureUnit(0)),
+                (&self.blit_program.opacity_uniform, UniformData::Float(1.0)),
+            ],
+            viewport: self.main_viewport(),
+            options: RenderOptions {
+                blend: Some(BlendMode::SrcOver.to_blend_state()),
+                ..RenderOptions::default()
+            },
+        });
+
+        self.framebuffer_flags.insert(FramebufferFlags::MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS);
+    }
+
+    // Returns a framebuffer the size of the draw viewport for a layer, reusing a free one if
+    // possible.
+    fn allocate_layer_framebuffer(&mut self) -> D::Framebuffer {
+        let size = self.draw_viewport().size();
+        match self.free_layer_framebuffers.pop() {
+            Some(framebuffer)
+                if self.device.texture_size(self.device.framebuffer_texture(&framebuffer)) ==
+                    size => framebuffer,
+            _ => {
+                let texture = self.device.create_texture(TextureFormat::RGBA8, size);
+                self.device.create_framebuffer(texture)
+            }
+        }
+    }
+
+    // Redirects drawing into a new layer, cleared to transparent.
+    fn push_layer(&mut self) {
+        let layer_framebuffer = self.allocate_layer_framebuffer();
+        self.layer_framebuffers.push(layer_framebuffer);
+
+        // Nothing is drawn here; this just clears the layer.
+        self.device.draw_elements(6, &RenderState {
+            target: &self.draw_render_target(),
+            program: &self.blit_program.program,
+            vertex_array: &self.blit_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[&self.area_lut_texture],
+            uniforms: &[
+                (&self.blit_program.source_uniform, UniformData::TextureUnit(0)),
+                (&self.blit_program.opacity_uniform, UniformData::Float(1.0)),
+            ],
+            viewport: self.draw_viewport(),
+            options: RenderOptions {
+                color_mask: false,
+                clear_ops: ClearOps { color: Some(ColorF::default()), ..ClearOps::default() },
+                ..RenderOptions::default()
+            },
+        });
+    }
+
+    // Blurs the current layer and composites it onto the one beneath it, or onto the scene if
+    // there is none. The layer covers the whole viewport, so the blending hardware handles every
+    // Porter-Duff operator exactly, even those that affect the destination outside the source.
+    fn pop_layer(&mut self, opacity: f32, blur: Vector2F, blend_mode: BlendMode) {
+        let mut layer_framebuffer = match self.layer_framebuffers.pop() {
+            None => return,
+            Some(layer_framebuffer) => layer_framebuffer,
+        };
+        if blur.x() > 0.0 {
+            layer_framebuffer =
+                self.blur_layer(layer_framebuffer, blur.x(), Vector2F::new(1.0, 0.0));
+        }
+        if blur.y() > 0.0 {
+            layer_framebuffer =
+                self.blur_layer(layer_framebuffer, blur.y(), Vector2F::new(0.0, 1.0));
+        }
+
+        if !blend_mode.is_porter_duff() && self.blend_mode_reads_dest_framebuffer(blend_mode) {
+            self.blend_layer_with_dest_texture(&layer_framebuffer, opacity, blend_mode);
+            self.free_layer_framebuffers.push(layer_framebuffer);
+            return;
+        }
+
+        let clear_color = self.clear_color_for_draw_operation();
+        self.device.draw_elements(6, &RenderState {
+            target: &self.draw_render_target(),
+            program: &self.blit_program.program,
+            vertex_array: &self.blit_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[self.device.framebuffer_texture(&layer_framebuffer)],
+            uniforms: &[
+                (&self.blit_program.source_uniform, UniformData::TextureUnit(0)),
+                (&self.blit_program.opacity_uniform, UniformData::Float(opacity)),
+            ],
+            viewport: self.draw_viewport(),
+            options: RenderOptions {
+                blend: Some(blend_mode.to_blend_state()),
                 stencil: self.stencil_state(),
                 clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
                 ..RenderOptions::default()
             },
         });
 
+        self.preserve_draw_framebuffer();
+        self.free_layer_framebuffers.push(layer_framebuffer);
+    }
+
+    // Composites a layer with a separable blend mode, which the shader does itself from a copy of
+    // the destination.
+    fn blend_layer_with_dest_texture(&mut self,
+                                     layer_framebuffer: &D::Framebuffer,
+                                     opacity: f32,
+                                     blend_mode: BlendMode) {
+        self.copy_intermediate_dest_framebuffer();
+
+        let dest_blend_framebuffer = self.dest_blend_framebuffer.as_ref().unwrap();
+        let draw_viewport = self.draw_viewport();
+        self.device.draw_elements(6, &RenderState {
+            target: &self.draw_render_target(),
+            program: &self.blit_blend_program.program,
+            vertex_array: &self.blit_blend_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[
+                self.device.framebuffer_texture(layer_framebuffer),
+                self.device.framebuffer_texture(dest_blend_framebuffer),
+            ],
+            uniforms: &[
+                (&self.blit_blend_program.source_uniform, UniformData::TextureUnit(0)),
+                (&self.blit_blend_program.dest_texture_uniform, UniformData::TextureUnit(1)),
+                (&self.blit_blend_program.framebuffer_size_uniform,
+                 UniformData::Vec2(draw_viewport.size().to_f32().0)),
+                (&self.blit_blend_program.opacity_uniform, UniformData::Float(opacity)),
+                (&self.blit_blend_program.blend_mode_uniform,
+                 UniformData::Int(blend_mode.to_shader_blend_mode())),
+            ],
+            viewport: draw_viewport,
+            options: RenderOptions {
+                stencil: self.stencil_state(),
+                ..RenderOptions::default()
+            },
+        });
+
         self.preserve_draw_framebuffer();
     }
 
+    // Runs one pass of the separable Gaussian blur over a layer, returning the framebuffer that
+    // holds the result.
+    fn blur_layer(&mut self, source_framebuffer: D::Framebuffer, sigma: f32, direction: Vector2F)
+                  -> D::Framebuffer {
+        let dest_framebuffer = self.allocate_layer_framebuffer();
+        let source_texture = self.device.framebuffer_texture(&source_framebuffer);
+        let size = self.device.texture_size(source_texture);
+        self.device.draw_elements(6, &RenderState {
+            target: &RenderTarget::Framebuffer(&dest_framebuffer),
+            program: &self.blur_program.program,
+            vertex_array: &self.blur_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[source_texture],
+            uniforms: &[
+                (&self.blur_program.source_uniform, UniformData::TextureUnit(0)),
+                (&self.blur_program.source_size_uniform, UniformData::Vec2(size.to_f32().0)),
+                (&self.blur_program.sigma_uniform, UniformData::Float(sigma)),
+                (&self.blur_program.direction_uniform, UniformData::Vec2(direction.0)),
+            ],
+            viewport: RectI::new(Vector2I::default(), size),
+            options: RenderOptions::default(),
+        });
+        self.free_layer_framebuffers.push(source_framebuffer);
+        dest_framebuffer
+    }
+
     fn draw_solid_tiles(&mut self, count: u32) {
         let clear_color = self.clear_color_for_draw_operation();
 
@@ -550,20 +932,17 @@ where
             (&solid_tile_program.transform_uniform,
              UniformData::Mat4(self.tile_transform().to_columns())),
             (&solid_tile_program.tile_size_uniform,
-             UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
+             UniformData::Vec2(self.tile_size.to_f32().0)),
         ];
 
         match self.render_mode {
             RenderMode::Multicolor => {
-                let paint_texture = self.paint_texture.as_ref().unwrap();
-                textures.push(paint_texture);
+                textures.push(self.paint_texture.as_ref().unwrap());
+                textures.push(self.paint_metadata_texture.as_ref().unwrap());
                 uniforms.push((&self.solid_multicolor_tile_program.paint_texture_uniform,
                                UniformData::TextureUnit(0)));
-                uniforms.push((&self.solid_multicolor_tile_program.paint_texture_size_uniform,
-                               UniformData::Vec2(self.device
-                                                     .texture_size(paint_texture)
-                                                     .0
-                                                     .to_f32x2())));
+                uniforms.push((&self.solid_multicolor_tile_program.paint_metadata_texture_uniform,
+                               UniformData::TextureUnit(1)));
             }
             RenderMode::Monochrome { .. } if self.postprocessing_needed() => {
                 uniforms.push((&self.solid_monochrome_tile_program.color_uniform,
@@ -761,7 +1140,10 @@ where
             viewport: self.draw_viewport(),
             options: RenderOptions {
                 blend: Some(BlendState {
-                    func: BlendFunc::RGBSrcAlphaAlphaOneMinusSrcAlpha,
+                    src_rgb_factor: BlendFactor::SrcAlpha,
+                    dest_rgb_factor: BlendFactor::OneMinusSrcAlpha,
+                    src_alpha_factor: BlendFactor::One,
+                    dest_alpha_factor: BlendFactor::One,
                     ..BlendState::default()
                 }),
                 depth: Some(DepthState { func: DepthFunc::Less, write: false, }),
@@ -774,8 +1156,12 @@ where
     }
 
     pub fn draw_render_target(&self) -> RenderTarget<D> {
-        if self.postprocessing_needed() {
+        if let Some(layer_framebuffer) = self.layer_framebuffers.last() {
+            RenderTarget::Framebuffer(layer_framebuffer)
+        } else if self.postprocessing_needed() {
             RenderTarget::Framebuffer(self.postprocess_source_framebuffer.as_ref().unwrap())
+        } else if let Some(ref framebuffer) = self.intermediate_dest_framebuffer {
+            RenderTarget::Framebuffer(framebuffer)
         } else {
             self.dest_render_target()
         }
@@ -822,6 +1208,34 @@ where
         */
     }
 
+    // Blend modes that read the destination can't read the default framebuffer, so scenes that
+    // use them are drawn into an intermediate framebuffer first. Monochrome scenes never need it.
+    fn init_intermediate_dest_framebuffer(&mut self, needs_readable_framebuffer: bool) {
+        if let RenderMode::Monochrome { .. } = self.render_mode {
+            self.intermediate_dest_framebuffer = None;
+            return;
+        }
+        if !needs_readable_framebuffer {
+            self.intermediate_dest_framebuffer = None;
+            return;
+        }
+
+        let size = self.main_viewport().size();
+        match self.intermediate_dest_framebuffer {
+            Some(ref framebuffer)
+                if self.device.texture_size(self.device.framebuffer_texture(framebuffer)) ==
+                    size => {}
+            _ => {
+                let texture = self.device.create_texture(TextureFormat::RGBA8, size);
+                self.intermediate_dest_framebuffer = Some(self.device.create_framebuffer(texture));
+            }
+        }
+    }
+
+    fn blend_mode_reads_dest_framebuffer(&self, blend_mode: BlendMode) -> bool {
+        blend_mode.needs_readable_framebuffer() && self.intermediate_dest_framebuffer.is_some()
+    }
+
     fn postprocessing_needed(&self) -> bool {
         match self.render_mode {
             RenderMode::Monochrome {
@@ -847,29 +1261,37 @@ where
     }
 
     fn clear_color_for_draw_operation(&mut self) -> Option<ColorF> {
-        let postprocessing_needed = self.postprocessing_needed();
-        let flag = if postprocessing_needed {
-            FramebufferFlags::MUST_PRESERVE_POSTPROCESS_FRAMEBUFFER_CONTENTS
-        } else {
-            FramebufferFlags::MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS
-        };
-
-        if self.framebuffer_flags.contains(flag) {
+        // Layers are cleared when they're pushed.
+        if !self.layer_framebuffers.is_empty() ||
+                self.framebuffer_flags.contains(self.draw_framebuffer_flag()) {
             None
-        } else if !postprocessing_needed {
-            self.options.background_color
-        } else {
+        } else if self.postprocessing_needed() {
             Some(ColorF::default())
+        } else if self.intermediate_dest_framebuffer.is_some() {
+            // The intermediate framebuffer is composited over the destination at the end, so
+            // without a background color it starts out transparent.
+            Some(self.options.background_color.unwrap_or_default())
+        } else {
+            self.options.background_color
         }
     }
 
     fn preserve_draw_framebuffer(&mut self) {
-        let flag = if self.postprocessing_needed() {
+        if !self.layer_framebuffers.is_empty() {
+            return;
+        }
+        let flag = self.draw_framebuffer_flag();
+        self.framebuffer_flags.insert(flag);
+    }
+
+    fn draw_framebuffer_flag(&self) -> FramebufferFlags {
+        if self.postprocessing_needed() {
             FramebufferFlags::MUST_PRESERVE_POSTPROCESS_FRAMEBUFFER_CONTENTS
+        } else if self.intermediate_dest_framebuffer.is_some() {
+            FramebufferFlags::MUST_PRESERVE_INTERMEDIATE_FRAMEBUFFER_CONTENTS
         } else {
             FramebufferFlags::MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS
-        };
-        self.framebuffer_flags.insert(flag);
+        }
     }
 
     pub fn draw_viewport(&self) -> RectI {
@@ -882,6 +1304,10 @@ where
                 let scale = Vector2I::new(3, 1);
                 RectI::new(Vector2I::default(), main_viewport.size().scale_xy(scale))
             }
+            _ if self.intermediate_dest_framebuffer.is_some() ||
+                    !self.layer_framebuffers.is_empty() => {
+                RectI::new(Vector2I::default(), main_viewport.size())
+            }
             _ => main_viewport,
         }
     }
@@ -923,6 +1349,22 @@ where
     }
 }
 
+fn create_mask_framebuffer<D>(device: &D, tile_size: Vector2I) -> D::Framebuffer
+where
+    D: Device,
+{
+    let texture = device.create_texture(TextureFormat::R16F, mask_framebuffer_size(tile_size));
+    device.create_framebuffer(texture)
+}
+
+// Rounds the mask framebuffer down to a whole number of tiles, so the shaders can locate each
+// alpha tile from its index alone.
+fn mask_framebuffer_size(tile_size: Vector2I) -> Vector2I {
+    let tile_count = Vector2I::new(MASK_FRAMEBUFFER_LENGTH / tile_size.x(),
+                                   MASK_FRAMEBUFFER_LENGTH / tile_size.y());
+    tile_count.scale_xy(tile_size)
+}
+
 struct FillVertexArray<D>
 where
     D: Device,
@@ -1050,8 +1492,10 @@ where
                                   .unwrap();
         let tile_index_attr = device.get_vertex_attr(&alpha_tile_program.program, "TileIndex")
                                     .unwrap();
-        let color_tex_coord_attr = device.get_vertex_attr(&alpha_tile_program.program,
-                                                          "ColorTexCoord");
+        let fill_rule_attr = device.get_vertex_attr(&alpha_tile_program.program, "FillRule")
+                                   .unwrap();
+        let paint_texel_attr = device.get_vertex_attr(&alpha_tile_program.program, "PaintTexel");
+        let paint_kind_attr = device.get_vertex_attr(&alpha_tile_program.program, "PaintKind");
 
         // NB: The object must be of type `I16`, not `U16`, to work around a macOS Radeon
         // driver bug.
@@ -1093,12 +1537,21 @@ where
             divisor: 1,
             buffer_index: 1,
         });
-        if let Some(color_tex_coord_attr) = color_tex_coord_attr {
+        device.configure_vertex_attr(&vertex_array, &fill_rule_attr, &VertexAttrDescriptor {
+            size: 1,
+            class: VertexAttrClass::Int,
+            attr_type: VertexAttrType::U8,
+            stride: MASK_TILE_INSTANCE_SIZE,
+            offset: 12,
+            divisor: 1,
+            buffer_index: 1,
+        });
+        if let Some(paint_texel_attr) = paint_texel_attr {
             device.configure_vertex_attr(&vertex_array,
-                                         &color_tex_coord_attr,
+                                         &paint_texel_attr,
                                          &VertexAttrDescriptor {
                                             size: 2,
-                                            class: VertexAttrClass::FloatNorm,
+                                            class: VertexAttrClass::Int,
                                             attr_type: VertexAttrType::U16,
                                             stride: MASK_TILE_INSTANCE_SIZE,
                                             offset: 8,
@@ -1106,6 +1559,19 @@ where
                                             buffer_index: 1,
                                          });
         }
+        if let Some(paint_kind_attr) = paint_kind_attr {
+            device.configure_vertex_attr(&vertex_array,
+                                         &paint_kind_attr,
+                                         &VertexAttrDescriptor {
+                                            size: 1,
+                                            class: VertexAttrClass::Int,
+                                            attr_type: VertexAttrType::U8,
+                                            stride: MASK_TILE_INSTANCE_SIZE,
+                                            offset: 13,
+                                            divisor: 1,
+                                            buffer_index: 1,
+                                         });
+        }
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
         AlphaTileVertexArray { vertex_array, vertex_buffer }
@@ -1136,8 +1602,8 @@ where
                                     .unwrap();
         let tile_origin_attr = device.get_vertex_attr(&solid_tile_program.program, "TileOrigin")
                                      .unwrap();
-        let color_tex_coord_attr = device.get_vertex_attr(&solid_tile_program.program,
-                                                          "ColorTexCoord");
+        let paint_texel_attr = device.get_vertex_attr(&solid_tile_program.program, "PaintTexel");
+        let paint_kind_attr = device.get_vertex_attr(&solid_tile_program.program, "PaintKind");
 
         // NB: The object must be of type short, not unsigned short, to work around a macOS
         // Radeon driver bug.
@@ -1161,12 +1627,12 @@ where
             divisor: 1,
             buffer_index: 1,
         });
-        if let Some(color_tex_coord_attr) = color_tex_coord_attr {
+        if let Some(paint_texel_attr) = paint_texel_attr {
             device.configure_vertex_attr(&vertex_array,
-                                         &color_tex_coord_attr,
+                                         &paint_texel_attr,
                                          &VertexAttrDescriptor {
                                             size: 2,
-                                            class: VertexAttrClass::FloatNorm,
+                                            class: VertexAttrClass::Int,
                                             attr_type: VertexAttrType::U16,
                                             stride: SOLID_TILE_INSTANCE_SIZE,
                                             offset: 4,
@@ -1174,6 +1640,19 @@ where
                                             buffer_index: 1,
                                          });
         }
+        if let Some(paint_kind_attr) = paint_kind_attr {
+            device.configure_vertex_attr(&vertex_array,
+                                         &paint_kind_attr,
+                                         &VertexAttrDescriptor {
+                                            size: 1,
+                                            class: VertexAttrClass::Int,
+                                            attr_type: VertexAttrType::U8,
+                                            stride: SOLID_TILE_INSTANCE_SIZE,
+                                            offset: 10,
+                                            divisor: 1,
+                                            buffer_index: 1,
+                                         });
+        }
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
         SolidTileVertexArray { vertex_array, vertex_buffer }
@@ -1222,12 +1701,7 @@ where
     D: Device,
 {
     fn new(device: &D, program_name: &str, resources: &dyn ResourceLoader) -> SolidTileProgram<D> {
-        let program = device.create_program_from_shader_names(
-            resources,
-            program_name,
-            program_name,
-            "tile_solid",
-        );
+        let program = device.create_program(resources, program_name);
         let transform_uniform = device.get_uniform(&program, "Transform");
         let tile_size_uniform = device.get_uniform(&program, "TileSize");
         SolidTileProgram { program, transform_uniform, tile_size_uniform }
@@ -1240,7 +1714,7 @@ where
 {
     solid_tile_program: SolidTileProgram<D>,
     paint_texture_uniform: D::Uniform,
-    paint_texture_size_uniform: D::Uniform,
+    paint_metadata_texture_uniform: D::Uniform,
 }
 
 impl<D> SolidTileMulticolorProgram<D>
@@ -1251,12 +1725,12 @@ where
         let solid_tile_program = SolidTileProgram::new(device, "tile_solid_multicolor", resources);
         let paint_texture_uniform = device.get_uniform(&solid_tile_program.program,
                                                        "PaintTexture");
-        let paint_texture_size_uniform = device.get_uniform(&solid_tile_program.program,
-                                                            "PaintTextureSize");
+        let paint_metadata_texture_uniform = device.get_uniform(&solid_tile_program.program,
+                                                                "PaintMetadataTexture");
         SolidTileMulticolorProgram {
             solid_tile_program,
             paint_texture_uniform,
-            paint_texture_size_uniform,
+            paint_metadata_texture_uniform,
         }
     }
 }
@@ -1299,12 +1773,7 @@ where
     D: Device,
 {
     fn new(device: &D, program_name: &str, resources: &dyn ResourceLoader) -> AlphaTileProgram<D> {
-        let program = device.create_program_from_shader_names(
-            resources,
-            program_name,
-            program_name,
-            "tile_alpha",
-        );
+        let program = device.create_program(resources, program_name);
         let transform_uniform = device.get_uniform(&program, "Transform");
         let tile_size_uniform = device.get_uniform(&program, "TileSize");
         let stencil_texture_uniform = device.get_uniform(&program, "StencilTexture");
@@ -1325,7 +1794,7 @@ where
 {
     alpha_tile_program: AlphaTileProgram<D>,
     paint_texture_uniform: D::Uniform,
-    paint_texture_size_uniform: D::Uniform,
+    paint_metadata_texture_uniform: D::Uniform,
 }
 
 impl<D> AlphaTileMulticolorProgram<D>
@@ -1336,12 +1805,12 @@ where
         let alpha_tile_program = AlphaTileProgram::new(device, "tile_alpha_multicolor", resources);
         let paint_texture_uniform =
             device.get_uniform(&alpha_tile_program.program, "PaintTexture");
-        let paint_texture_size_uniform =
-            device.get_uniform(&alpha_tile_program.program, "PaintTextureSize");
+        let paint_metadata_texture_uniform =
+            device.get_uniform(&alpha_tile_program.program, "PaintMetadataTexture");
         AlphaTileMulticolorProgram {
             alpha_tile_program,
             paint_texture_uniform,
-            paint_texture_size_uniform,
+            paint_metadata_texture_uniform,
         }
     }
 }
@@ -1368,6 +1837,239 @@ where
     }
 }
 
+struct AlphaTileBlendProgram<D>
+where
+    D: Device,
+{
+    alpha_tile_program: AlphaTileProgram<D>,
+    paint_texture_uniform: D::Uniform,
+    paint_metadata_texture_uniform: D::Uniform,
+    dest_texture_uniform: D::Uniform,
+    framebuffer_size_uniform: D::Uniform,
+    blend_mode_uniform: D::Uniform,
+}
+
+impl<D> AlphaTileBlendProgram<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> AlphaTileBlendProgram<D> {
+        let alpha_tile_program = AlphaTileProgram::new(device, "tile_alpha_blend", resources);
+        let paint_texture_uniform =
+            device.get_uniform(&alpha_tile_program.program, "PaintTexture");
+        let paint_metadata_texture_uniform =
+            device.get_uniform(&alpha_tile_program.program, "PaintMetadataTexture");
+        let dest_texture_uniform = device.get_uniform(&alpha_tile_program.program, "DestTexture");
+        let framebuffer_size_uniform =
+            device.get_uniform(&alpha_tile_program.program, "FramebufferSize");
+        let blend_mode_uniform = device.get_uniform(&alpha_tile_program.program, "BlendMode");
+        AlphaTileBlendProgram {
+            alpha_tile_program,
+            paint_texture_uniform,
+            paint_metadata_texture_uniform,
+            dest_texture_uniform,
+            framebuffer_size_uniform,
+            blend_mode_uniform,
+        }
+    }
+}
+
+struct BlitProgram<D>
+where
+    D: Device,
+{
+    program: D::Program,
+    source_uniform: D::Uniform,
+    opacity_uniform: D::Uniform,
+}
+
+impl<D> BlitProgram<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> BlitProgram<D> {
+        let program = device.create_program(resources, "blit");
+        let source_uniform = device.get_uniform(&program, "Source");
+        let opacity_uniform = device.get_uniform(&program, "Opacity");
+        BlitProgram { program, source_uniform, opacity_uniform }
+    }
+}
+
+struct BlurProgram<D>
+where
+    D: Device,
+{
+    program: D::Program,
+    source_uniform: D::Uniform,
+    source_size_uniform: D::Uniform,
+    sigma_uniform: D::Uniform,
+    direction_uniform: D::Uniform,
+}
+
+impl<D> BlurProgram<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> BlurProgram<D> {
+        let program = device.create_program_from_shader_names(resources, "blur", "blit", "blur");
+        let source_uniform = device.get_uniform(&program, "Source");
+        let source_size_uniform = device.get_uniform(&program, "SourceSize");
+        let sigma_uniform = device.get_uniform(&program, "Sigma");
+        let direction_uniform = device.get_uniform(&program, "Direction");
+        BlurProgram {
+            program,
+            source_uniform,
+            source_size_uniform,
+            sigma_uniform,
+            direction_uniform,
+        }
+    }
+}
+
+struct BlitBlendProgram<D>
+where
+    D: Device,
+{
+    program: D::Program,
+    source_uniform: D::Uniform,
+    dest_texture_uniform: D::Uniform,
+    framebuffer_size_uniform: D::Uniform,
+    opacity_uniform: D::Uniform,
+    blend_mode_uniform: D::Uniform,
+}
+
+impl<D> BlitBlendProgram<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> BlitBlendProgram<D> {
+        let program =
+            device.create_program_from_shader_names(resources, "blit_blend", "blit", "blit_blend");
+        let source_uniform = device.get_uniform(&program, "Source");
+        let dest_texture_uniform = device.get_uniform(&program, "DestTexture");
+        let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
+        let opacity_uniform = device.get_uniform(&program, "Opacity");
+        let blend_mode_uniform = device.get_uniform(&program, "BlendMode");
+        BlitBlendProgram {
+            program,
+            source_uniform,
+            dest_texture_uniform,
+            framebuffer_size_uniform,
+            opacity_uniform,
+            blend_mode_uniform,
+        }
+    }
+}
+
+struct BlurVertexArray<D>
+where
+    D: Device,
+{
+    vertex_array: D::VertexArray,
+}
+
+impl<D> BlurVertexArray<D>
+where
+    D: Device,
+{
+    fn new(
+        device: &D,
+        blur_program: &BlurProgram<D>,
+        quad_vertex_positions_buffer: &D::Buffer,
+        quad_vertex_indices_buffer: &D::Buffer,
+    ) -> BlurVertexArray<D> {
+        let vertex_array = device.create_vertex_array();
+        let position_attr = device.get_vertex_attr(&blur_program.program, "Position").unwrap();
+
+        device.bind_buffer(&vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
+        device.configure_vertex_attr(&vertex_array, &position_attr, &VertexAttrDescriptor {
+            size: 2,
+            class: VertexAttrClass::Int,
+            attr_type: VertexAttrType::I16,
+            stride: 4,
+            offset: 0,
+            divisor: 0,
+            buffer_index: 0,
+        });
+        device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
+
+        BlurVertexArray { vertex_array }
+    }
+}
+
+struct BlitBlendVertexArray<D>
+where
+    D: Device,
+{
+    vertex_array: D::VertexArray,
+}
+
+impl<D> BlitBlendVertexArray<D>
+where
+    D: Device,
+{
+    fn new(
+        device: &D,
+        blit_blend_program: &BlitBlendProgram<D>,
+        quad_vertex_positions_buffer: &D::Buffer,
+        quad_vertex_indices_buffer: &D::Buffer,
+    ) -> BlitBlendVertexArray<D> {
+        let vertex_array = device.create_vertex_array();
+        let position_attr =
+            device.get_vertex_attr(&blit_blend_program.program, "Position").unwrap();
+
+        device.bind_buffer(&vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
+        device.configure_vertex_attr(&vertex_array, &position_attr, &VertexAttrDescriptor {
+            size: 2,
+            class: VertexAttrClass::Int,
+            attr_type: VertexAttrType::I16,
+            stride: 4,
+            offset: 0,
+            divisor: 0,
+            buffer_index: 0,
+        });
+        device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
+
+        BlitBlendVertexArray { vertex_array }
+    }
+}
+
+struct BlitVertexArray<D>
+where
+    D: Device,
+{
+    vertex_array: D::VertexArray,
+}
+
+impl<D> BlitVertexArray<D>
+where
+    D: Device,
+{
+    fn new(
+        device: &D,
+        blit_program: &BlitProgram<D>,
+        quad_vertex_positions_buffer: &D::Buffer,
+        quad_vertex_indices_buffer: &D::Buffer,
+    ) -> BlitVertexArray<D> {
+        let vertex_array = device.create_vertex_array();
+        let position_attr = device.get_vertex_attr(&blit_program.program, "Position").unwrap();
+
+        device.bind_buffer(&vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
+        device.configure_vertex_attr(&vertex_array, &position_attr, &VertexAttrDescriptor {
+            size: 2,
+            class: VertexAttrClass::Int,
+            attr_type: VertexAttrType::I16,
+            stride: 4,
+            offset: 0,
+            divisor: 0,
+            buffer_index: 0,
+        });
+        device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
+
+        BlitVertexArray { vertex_array }
+    }
+}
+
 struct PostprocessProgram<D>
 where
     D: Device,
@@ -1658,5 +2360,79 @@ bitflags! {
         const MUST_PRESERVE_MASK_FRAMEBUFFER_CONTENTS = 0x01;
         const MUST_PRESERVE_POSTPROCESS_FRAMEBUFFER_CONTENTS = 0x02;
         const MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS = 0x04;
+        const MUST_PRESERVE_INTERMEDIATE_FRAMEBUFFER_CONTENTS = 0x08;
+    }
+}
+
+trait BlendModeExt {
+    fn to_blend_state(self) -> BlendState;
+    fn to_shader_blend_mode(self) -> i32;
+}
+
+impl BlendModeExt for BlendMode {
+    // Tile and layer shaders output premultiplied alpha, so the Porter-Duff operators map directly
+    // onto blend factors. Tiles with unbounded operators are only drawn this way when the
+    // destination can't be read, in which case they affect the whole of each tile. Separable blend
+    // modes fall back to source-over.
+    fn to_blend_state(self) -> BlendState {
+        let (src_factor, dest_factor) = match self {
+            BlendMode::SrcOver => (BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+            BlendMode::DestOver => (BlendFactor::OneMinusDestAlpha, BlendFactor::One),
+            BlendMode::SrcIn => (BlendFactor::DestAlpha, BlendFactor::Zero),
+            BlendMode::DestIn => (BlendFactor::Zero, BlendFactor::SrcAlpha),
+            BlendMode::SrcOut => (BlendFactor::OneMinusDestAlpha, BlendFactor::Zero),
+            BlendMode::DestOut => (BlendFactor::Zero, BlendFactor::OneMinusSrcAlpha),
+            BlendMode::SrcAtop => (BlendFactor::DestAlpha, BlendFactor::OneMinusSrcAlpha),
+            BlendMode::DestAtop => (BlendFactor::OneMinusDestAlpha, BlendFactor::SrcAlpha),
+            BlendMode::Xor => (BlendFactor::OneMinusDestAlpha, BlendFactor::OneMinusSrcAlpha),
+            BlendMode::Lighter => (BlendFactor::One, BlendFactor::One),
+            BlendMode::Copy => (BlendFactor::One, BlendFactor::Zero),
+            BlendMode::Multiply |
+            BlendMode::Screen |
+            BlendMode::Overlay |
+            BlendMode::Darken |
+            BlendMode::Lighten |
+            BlendMode::ColorDodge |
+            BlendMode::ColorBurn |
+            BlendMode::HardLight |
+            BlendMode::SoftLight |
+            BlendMode::Difference |
+            BlendMode::Exclusion => (BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+        };
+        BlendState {
+            src_rgb_factor: src_factor,
+            dest_rgb_factor: dest_factor,
+            src_alpha_factor: src_factor,
+            dest_alpha_factor: dest_factor,
+            op: BlendOp::Add,
+        }
+    }
+
+    // Must match the `BLEND_MODE_*` constants in `blend.inc.glsl`.
+    fn to_shader_blend_mode(self) -> i32 {
+        match self {
+            BlendMode::Multiply => 0,
+            BlendMode::Screen => 1,
+            BlendMode::Overlay => 2,
+            BlendMode::Darken => 3,
+            BlendMode::Lighten => 4,
+            BlendMode::ColorDodge => 5,
+            BlendMode::ColorBurn => 6,
+            BlendMode::HardLight => 7,
+            BlendMode::SoftLight => 8,
+            BlendMode::Difference => 9,
+            BlendMode::Exclusion => 10,
+            BlendMode::SrcIn => 11,
+            BlendMode::DestIn => 12,
+            BlendMode::SrcOut => 13,
+            BlendMode::DestAtop => 14,
+            BlendMode::Copy => 15,
+            BlendMode::SrcOver |
+            BlendMode::SrcAtop |
+            BlendMode::DestOver |
+            BlendMode::DestOut |
+            BlendMode::Xor |
+            BlendMode::Lighter => unreachable!(),
+        }
     }
 }
@@ -12,36 +12,71 @@
 
 use crate::options::BoundingQuad;
 use crate::tile_map::DenseTileMap;
+use pathfinder_content::effects::BlendMode;
 use pathfinder_geometry::line_segment::{LineSegmentU4, LineSegmentU8};
-use pathfinder_geometry::vector::Vector2I;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_geometry::rect::RectF;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Formatter, Result as DebugResult};
 use std::time::Duration;
 
+/// The tiles of a single path.
+///
+/// This doesn't depend on the path's paint, position in the scene, or fill rule, so it can be
+/// cached across builds. Alpha tile indices in the fills and tiles are local to the object.
 #[derive(Debug)]
 pub(crate) struct BuiltObject {
     pub bounds: RectF,
+    pub tile_size: Vector2I,
     pub fills: Vec<FillBatchPrimitive>,
-    pub alpha_tiles: Vec<AlphaTileBatchPrimitive>,
     pub tiles: DenseTileMap<TileObjectPrimitive>,
+    pub alpha_tile_count: u16,
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RenderCommand {
-    Start { path_count: usize, bounding_quad: BoundingQuad },
+    /// Begins a scene. `tile_size` is the size of the tiles in device pixels, which the
+    /// positions of fills within their tiles are relative to.
+    Start {
+        path_count: usize,
+        bounding_quad: BoundingQuad,
+        needs_readable_framebuffer: bool,
+        tile_size: Vector2I,
+    },
     AddPaintData(PaintData),
     AddFills(Vec<FillBatchPrimitive>),
     FlushFills,
-    AlphaTile(Vec<AlphaTileBatchPrimitive>),
+    AlphaTile(Vec<AlphaTileBatchPrimitive>, BlendMode),
     SolidTile(Vec<SolidTileBatchPrimitive>),
+    /// Starts drawing into a new transparent layer.
+    PushLayer,
+    /// Blurs the current layer and composites it onto the one beneath it with the given opacity
+    /// and blend mode. The blur is the standard deviation of a Gaussian along each axis, in device
+    /// pixels.
+    PopLayer { opacity: f32, blur: Vector2F, blend_mode: BlendMode },
     Finish { build_time: Duration },
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PaintData {
+    /// The size of the RGBA8 paint texture, which holds solid colors and images.
     pub size: Vector2I,
     pub texels: Vec<u8>,
+    /// The size of the RGBA32F metadata texture, which describes gradients and patterns.
+    pub metadata_size: Vector2I,
+    pub metadata: Vec<f32>,
 }
 
+/// Values of `paint_kind` in tiles. These must match the values in the shaders.
+///
+/// For solid colors, the paint coordinates of a tile locate the color in the paint texture. For
+/// gradients and patterns, they locate the first texel of the paint's metadata.
+pub const PAINT_KIND_COLOR: u8 = 0;
+pub const PAINT_KIND_GRADIENT: u8 = 1;
+pub const PAINT_KIND_PATTERN: u8 = 2;
+
 #[derive(Clone, Copy, Debug)]
 pub struct FillObjectPrimitive {
     pub px: LineSegmentU4,
@@ -59,7 +94,11 @@ pub struct TileObjectPrimitive {
 }
 
 // FIXME(pcwalton): Move `subpx` before `px` and remove `repr(packed)`.
+/// An edge within an alpha tile. Positions are in units of 1/16 of the tile's width and height,
+/// as whole units in `px` and 1/256ths of a unit in `subpx`, so that they fit whatever the tile
+/// size is.
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(packed)]
 pub struct FillBatchPrimitive {
     pub px: LineSegmentU4,
@@ -68,17 +107,20 @@ pub struct FillBatchPrimitive {
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 pub struct SolidTileBatchPrimitive {
     pub tile_x: i16,
     pub tile_y: i16,
-    pub origin_u: u16,
-    pub origin_v: u16,
+    pub paint_x: u16,
+    pub paint_y: u16,
     pub object_index: u16,
-    pub pad: u16,
+    pub paint_kind: u8,
+    pub pad: u8,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 pub struct AlphaTileBatchPrimitive {
     pub tile_x_lo: u8,
@@ -87,8 +129,12 @@ pub struct AlphaTileBatchPrimitive {
     pub backdrop: i8,
     pub object_index: u16,
     pub tile_index: u16,
-    pub origin_u: u16,
-    pub origin_v: u16,
+    pub paint_x: u16,
+    pub paint_y: u16,
+    /// 0 for the nonzero winding rule, 1 for the even-odd rule.
+    pub fill_rule: u8,
+    pub paint_kind: u8,
+    pub pad: [u8; 2],
 }
 
 impl Debug for RenderCommand {
@@ -100,12 +146,16 @@ impl Debug for RenderCommand {
             }
             RenderCommand::AddFills(ref fills) => write!(formatter, "AddFills(x{})", fills.len()),
             RenderCommand::FlushFills => write!(formatter, "FlushFills"),
-            RenderCommand::AlphaTile(ref tiles) => {
-                write!(formatter, "AlphaTile(x{})", tiles.len())
+            RenderCommand::AlphaTile(ref tiles, blend_mode) => {
+                write!(formatter, "AlphaTile(x{}, {:?})", tiles.len(), blend_mode)
             }
             RenderCommand::SolidTile(ref tiles) => {
                 write!(formatter, "SolidTile(x{})", tiles.len())
             }
+            RenderCommand::PushLayer => write!(formatter, "PushLayer"),
+            RenderCommand::PopLayer { opacity, blur, blend_mode } => {
+                write!(formatter, "PopLayer({}, {:?}, {:?})", opacity, blur, blend_mode)
+            }
             RenderCommand::Finish { .. } => write!(formatter, "Finish"),
         }
     }
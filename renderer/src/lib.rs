@@ -16,14 +16,19 @@ extern crate bitflags;
 extern crate log;
 
 pub mod concurrent;
+pub mod cpu;
 pub mod gpu;
 pub mod gpu_data;
 pub mod options;
 pub mod paint;
 pub mod post;
 pub mod scene;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod tile_cache;
 
 mod builder;
+mod mask;
 mod sorted_vector;
 mod tile_map;
 mod tiles;
@@ -0,0 +1,85 @@
+// pathfinder/renderer/src/mask.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Exact coverage masks for alpha tiles, computed from their fills.
+//!
+//! The scene builder uses these to find alpha tiles that are fully covered, and the CPU renderer
+//! uses them to rasterize.
+
+use crate::gpu_data::FillBatchPrimitive;
+use pathfinder_geometry::util;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+
+// Adds the signed area between the fill's line segment and the bottom of its tile to each pixel
+// of a tile's mask, which holds rows of `tile_size.x()` pixels.
+pub(crate) fn accumulate_fill(mask: &mut [f32], fill: &FillBatchPrimitive, tile_size: Vector2I) {
+    let (px, subpx) = (fill.px, fill.subpx);
+    let scale = tile_size.to_f32().scale(1.0 / 16.0);
+    let from = Vector2F::new((px.from & 0xf) as f32 + subpx.from_x as f32 / 256.0,
+                             (px.from >> 4) as f32 + subpx.from_y as f32 / 256.0).scale_xy(scale);
+    let to = Vector2F::new((px.to & 0xf) as f32 + subpx.to_x as f32 / 256.0,
+                           (px.to >> 4) as f32 + subpx.to_y as f32 / 256.0).scale_xy(scale);
+
+    // Lines running right to left add coverage; lines running left to right remove it.
+    let (left, right, sign) = if from.x() > to.x() {
+        (to, from, 1.0)
+    } else {
+        (from, to, -1.0)
+    };
+    if left.x() == right.x() {
+        return;
+    }
+    let slope = (right.y() - left.y()) / (right.x() - left.x());
+
+    let first_row = f32::min(left.y(), right.y()).floor().max(0.0) as usize;
+    let first_column = left.x().floor() as usize;
+    let last_column = (right.x().ceil() as usize).min(tile_size.x() as usize);
+    for column in first_column..last_column {
+        let x0 = left.x().max(column as f32);
+        let x1 = right.x().min((column + 1) as f32);
+        let y0 = left.y() + (x0 - left.x()) * slope;
+        let y1 = left.y() + (x1 - left.x()) * slope;
+        for row in first_row..(tile_size.y() as usize) {
+            let bottom = (row + 1) as f32;
+            let area = integrate_clamped_linear(bottom - y0, bottom - y1, x1 - x0);
+            mask[row * tile_size.x() as usize + column] += sign * area;
+        }
+    }
+}
+
+// Returns the coverage of a pixel with the given accumulated winding number.
+pub(crate) fn coverage(winding: f32, even_odd: bool) -> f32 {
+    if even_odd {
+        1.0 - (1.0 - 2.0 * (winding.abs() * 0.5).fract()).abs()
+    } else {
+        winding.abs().min(1.0)
+    }
+}
+
+// Returns the integral, over an interval of length `width`, of a linear function running from
+// `start` to `end` and clamped to [0, 1].
+fn integrate_clamped_linear(start: f32, end: f32, width: f32) -> f32 {
+    // The antiderivative of the clamped function.
+    fn antiderivative(value: f32) -> f32 {
+        if value <= 0.0 {
+            0.0
+        } else if value < 1.0 {
+            value * value * 0.5
+        } else {
+            value - 0.5
+        }
+    }
+
+    if (end - start).abs() < 1e-6 {
+        let value = (start + end) * 0.5;
+        return util::clamp(value, 0.0, 1.0) * width;
+    }
+    (antiderivative(end) - antiderivative(start)) / (end - start) * width
+}
@@ -11,11 +11,13 @@
 //! Options that control how rendering is to be performed.
 
 use crate::gpu_data::RenderCommand;
+use crate::tiles::{MAX_TILE_SIZE, MIN_TILE_SIZE, TILE_HEIGHT, TILE_WIDTH};
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::transform3d::Perspective;
-use pathfinder_geometry::vector::{Vector2F, Vector4F};
+use pathfinder_geometry::vector::{Vector2F, Vector2I, Vector4F};
 use pathfinder_content::clip::PolygonClipper3D;
+use pathfinder_content::color::ColorF;
 
 pub trait RenderCommandListener: Send + Sync {
     fn send(&self, command: RenderCommand);
@@ -32,23 +34,53 @@ where
 }
 
 /// Options that influence scene building.
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct BuildOptions {
     pub transform: RenderTransform,
     pub dilation: Vector2F,
     pub subpixel_aa_enabled: bool,
+    /// The size of the tiles the scene is cut into, in device pixels. Each dimension is
+    /// clamped to between `MIN_TILE_SIZE` and `MAX_TILE_SIZE`; the default is 16×16.
+    ///
+    /// Smaller tiles waste less work on partially covered areas, while larger ones mean fewer
+    /// tiles to draw where paths are large and flat.
+    pub tile_size: Vector2I,
+}
+
+impl Default for BuildOptions {
+    #[inline]
+    fn default() -> BuildOptions {
+        BuildOptions {
+            transform: RenderTransform::default(),
+            dilation: Vector2F::default(),
+            subpixel_aa_enabled: false,
+            tile_size: Vector2I::new(TILE_WIDTH as i32, TILE_HEIGHT as i32),
+        }
+    }
 }
 
 impl BuildOptions {
     pub(crate) fn prepare(self, bounds: RectF) -> PreparedBuildOptions {
+        let clamp_tile_length = |length: i32| length.clamp(MIN_TILE_SIZE, MAX_TILE_SIZE);
+        let tile_size = Vector2I::new(clamp_tile_length(self.tile_size.x()),
+                                      clamp_tile_length(self.tile_size.y()));
         PreparedBuildOptions {
             transform: self.transform.prepare(bounds),
             dilation: self.dilation,
             subpixel_aa_enabled: self.subpixel_aa_enabled,
+            tile_size,
         }
     }
 }
 
+/// Options that influence rendering a scene into an image on the CPU.
+#[derive(Clone, Default)]
+pub struct RasterizeOptions {
+    pub build_options: BuildOptions,
+    /// The color to clear the image to. If `None`, the image starts out transparent.
+    pub background_color: Option<ColorF>,
+}
+
 #[derive(Clone)]
 pub enum RenderTransform {
     Transform2D(Transform2F),
@@ -118,6 +150,7 @@ pub(crate) struct PreparedBuildOptions {
     pub(crate) transform: PreparedRenderTransform,
     pub(crate) dilation: Vector2F,
     pub(crate) subpixel_aa_enabled: bool,
+    pub(crate) tile_size: Vector2I,
 }
 
 impl PreparedBuildOptions {
@@ -8,50 +8,525 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::gpu_data::PaintData;
+use crate::gpu_data::{PAINT_KIND_COLOR, PAINT_KIND_GRADIENT, PAINT_KIND_PATTERN, PaintData};
+use crate::options::{PreparedBuildOptions, PreparedRenderTransform};
 use crate::scene::Scene;
-use pathfinder_geometry::vector::Vector2I;
 use pathfinder_content::color::ColorU;
+use pathfinder_content::gradient::{Gradient, GradientGeometry};
+use pathfinder_content::pattern::{Image, Pattern};
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-const PAINT_TEXTURE_WIDTH: i32 = 256;
-const PAINT_TEXTURE_HEIGHT: i32 = 256;
+// The maximum width and height of the paint texture, into which solid colors and pattern images
+// are packed. Tiles address its texels with 16-bit coordinates.
+const MAX_PAINT_TEXTURE_LENGTH: i32 = 4096;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct Paint {
-    pub color: ColorU,
+/// The maximum number of distinct paints in a scene, if they're all solid colors.
+///
+/// Each solid color takes up one texel of the paint texture. Pattern images take up room there
+/// too, and gradients and patterns are described in the metadata texture, which has its own limit,
+/// so a scene that uses them can hold fewer paints. `Scene::try_push_paint()` returns `None` for a
+/// paint that doesn't fit.
+pub const MAX_PAINT_COUNT: u32 = 1 << 24;
+
+// The metadata texture is addressed linearly, wrapping from one row to the next.
+const PAINT_METADATA_TEXTURE_WIDTH: i32 = 1024;
+// The maximum number of texels of metadata, which fill this many rows of the metadata texture.
+const MAX_PAINT_METADATA_LENGTH: u32 = PAINT_METADATA_TEXTURE_WIDTH as u32 * 4096;
+// The number of texels of metadata that start the description of each gradient and pattern.
+pub(crate) const PAINT_METADATA_HEADER_LENGTH: u32 = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Paint {
+    Color(ColorU),
+    Gradient(Gradient),
+    Pattern(Pattern),
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
-pub struct PaintId(pub u16);
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PaintId(pub u32);
 
 impl Paint {
     #[inline]
-    pub fn is_opaque(&self) -> bool {
-        self.color.a == 255
+    pub fn black() -> Paint {
+        Paint::Color(ColorU::black())
     }
 
     #[inline]
+    pub fn transparent_black() -> Paint {
+        Paint::Color(ColorU::transparent_black())
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        match *self {
+            Paint::Color(color) => color.a == 255,
+            Paint::Gradient(ref gradient) => gradient.is_opaque(),
+            Paint::Pattern(ref pattern) => pattern.is_opaque(),
+        }
+    }
+
     pub fn is_fully_transparent(&self) -> bool {
-        self.color.is_fully_transparent()
+        match *self {
+            Paint::Color(color) => color.is_fully_transparent(),
+            Paint::Gradient(ref gradient) => gradient.is_fully_transparent(),
+            Paint::Pattern(ref pattern) => pattern.is_fully_transparent(),
+        }
+    }
+
+    /// Transforms the paint along with the path it fills. Solid colors are unaffected.
+    pub fn apply_transform(&mut self, transform: &Transform2F) {
+        match *self {
+            Paint::Color(_) => {}
+            Paint::Gradient(ref mut gradient) => gradient.apply_transform(transform),
+            Paint::Pattern(ref mut pattern) => pattern.apply_transform(transform),
+        }
+    }
+
+    /// Multiplies the alpha of every color in this paint by `alpha`.
+    pub fn apply_opacity(&mut self, alpha: f32) {
+        match *self {
+            Paint::Color(ref mut color) => color.a = (color.a as f32 * alpha).round() as u8,
+            Paint::Gradient(ref mut gradient) => gradient.apply_opacity(alpha),
+            Paint::Pattern(ref mut pattern) => pattern.apply_opacity(alpha),
+        }
+    }
+}
+
+/// The paint textures, plus the information needed to find each paint in them.
+pub(crate) struct PaintInfo {
+    pub(crate) data: PaintData,
+    /// One entry per paint, indexed by `PaintId`.
+    pub(crate) metadata: Vec<PaintMetadata>,
+}
+
+/// Where the tiles filled with a paint find it.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PaintMetadata {
+    /// The texel of the paint texture holding a solid color, or the first texel of the metadata
+    /// texture describing a gradient or pattern.
+    pub(crate) texel: Vector2I,
+    /// One of the `PAINT_KIND_*` constants.
+    pub(crate) kind: u8,
+}
+
+/// Where each of a scene's paints is stored in the paint textures. Space is allocated as paints
+/// are added to the scene, so that a paint that doesn't fit can be refused.
+///
+/// Solid colors occupy one texel each of the paint texture, and each distinct pattern image is
+/// stored once at its native resolution. Gradients and patterns are evaluated in the shaders from
+/// their descriptions in the metadata texture, so they stay sharp at any scale.
+#[derive(Clone, Default)]
+pub(crate) struct PaintAtlas {
+    allocator: ShelfAllocator,
+    image_origins: HashMap<Image, Vector2I>,
+    metadata_length: u32,
+    /// One entry per paint, indexed by `PaintId`.
+    layouts: Vec<PaintLayout>,
+}
+
+// Where a paint is stored in the paint textures.
+#[derive(Clone, Copy)]
+struct PaintLayout {
+    /// The texel holding a solid color, or the origin of a pattern's image.
+    origin: Vector2I,
+    metadata_index: u32,
+}
+
+impl PaintAtlas {
+    /// Makes room for a new paint. Returns false, leaving the atlas unchanged, if it doesn't fit.
+    pub(crate) fn allocate(&mut self, paint: &Paint) -> bool {
+        let metadata_length = match *paint {
+            Paint::Color(_) => 0,
+            Paint::Gradient(ref gradient) => gradient_metadata_length(gradient),
+            Paint::Pattern(_) => PAINT_METADATA_HEADER_LENGTH,
+        };
+        if metadata_length > MAX_PAINT_METADATA_LENGTH - self.metadata_length {
+            return false;
+        }
+
+        let origin = match *paint {
+            Paint::Color(_) => match self.allocator.allocate(Vector2I::splat(1)) {
+                Some(origin) => origin,
+                None => return false,
+            },
+            Paint::Gradient(_) => Vector2I::default(),
+            Paint::Pattern(ref pattern) => {
+                let image = pattern.image();
+                match self.image_origins.get(image) {
+                    Some(&origin) => origin,
+                    None => match self.allocator.allocate(image.size()) {
+                        Some(origin) => {
+                            self.image_origins.insert(image.clone(), origin);
+                            origin
+                        }
+                        None => return false,
+                    },
+                }
+            }
+        };
+
+        self.layouts.push(PaintLayout { origin, metadata_index: self.metadata_length });
+        self.metadata_length += metadata_length;
+        true
     }
 }
 
 impl Scene {
-    pub fn build_paint_data(&self) -> PaintData {
-        let size = Vector2I::new(PAINT_TEXTURE_WIDTH, PAINT_TEXTURE_HEIGHT);
+    /// Fills in the paint textures, following the layout in the paint atlas.
+    pub(crate) fn build_paint_info(&self, options: &PreparedBuildOptions) -> PaintInfo {
+        let device_to_scene = scene_to_device_transform(options).inverse();
+        let atlas = &self.paint_atlas;
+        let metadata_length = atlas.metadata_length;
+
+        let size = Vector2I::new(atlas.allocator.width.max(1), atlas.allocator.height.max(1));
+        let metadata_size = Vector2I::new(PAINT_METADATA_TEXTURE_WIDTH,
+                                          ((metadata_length as i32 + PAINT_METADATA_TEXTURE_WIDTH -
+                                            1) / PAINT_METADATA_TEXTURE_WIDTH).max(1));
         let mut texels = vec![0; size.x() as usize * size.y() as usize * 4];
-        for (paint_index, paint) in self.paints.iter().enumerate() {
-            texels[paint_index * 4 + 0] = paint.color.r;
-            texels[paint_index * 4 + 1] = paint.color.g;
-            texels[paint_index * 4 + 2] = paint.color.b;
-            texels[paint_index * 4 + 3] = paint.color.a;
+        let mut metadata = vec![0.0; metadata_size.x() as usize * metadata_size.y() as usize * 4];
+        let mut paint_metadata = Vec::with_capacity(self.paints.len());
+
+        for (image, &origin) in &atlas.image_origins {
+            put_image(&mut texels, size, origin, image);
+        }
+
+        for (paint, layout) in self.paints.iter().zip(&atlas.layouts) {
+            match *paint {
+                Paint::Color(color) => {
+                    put_texel(&mut texels, size, layout.origin, color);
+                    paint_metadata.push(PaintMetadata {
+                        texel: layout.origin,
+                        kind: PAINT_KIND_COLOR,
+                    });
+                }
+                Paint::Gradient(ref gradient) => {
+                    let transform = gradient.transform().inverse() * device_to_scene;
+                    put_gradient_metadata(&mut metadata, layout.metadata_index, gradient,
+                                          &transform);
+                    paint_metadata.push(PaintMetadata {
+                        texel: metadata_texel(layout.metadata_index),
+                        kind: PAINT_KIND_GRADIENT,
+                    });
+                }
+                Paint::Pattern(ref pattern) => {
+                    let transform = pattern.transform().inverse() * device_to_scene;
+                    put_pattern_metadata(&mut metadata, layout.metadata_index, &transform,
+                                         layout.origin, pattern.image().size(),
+                                         pattern.repeat_axes(), pattern.opacity());
+                    paint_metadata.push(PaintMetadata {
+                        texel: metadata_texel(layout.metadata_index),
+                        kind: PAINT_KIND_PATTERN,
+                    });
+                }
+            }
+        }
+
+        PaintInfo {
+            data: PaintData { size, texels, metadata_size, metadata },
+            metadata: paint_metadata,
         }
-        PaintData { size, texels }
     }
 }
 
-pub(crate) fn paint_id_to_tex_coords(paint_id: PaintId) -> Vector2I {
-    let tex_coords = Vector2I::new(paint_id.0 as i32 % PAINT_TEXTURE_WIDTH,
-                                     paint_id.0 as i32 / PAINT_TEXTURE_WIDTH);
-    tex_coords.scale(256) + Vector2I::new(128, 128)
+// Packs rectangles onto shelves running across the paint texture.
+#[derive(Clone, Default)]
+struct ShelfAllocator {
+    shelves: Vec<Shelf>,
+    /// The width of the widest shelf.
+    width: i32,
+    /// The height of all the shelves together.
+    height: i32,
+}
+
+#[derive(Clone)]
+struct Shelf {
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl ShelfAllocator {
+    // Returns the origin of the new rectangle, or `None` if there's no room for it.
+    fn allocate(&mut self, size: Vector2I) -> Option<Vector2I> {
+        if size.x() <= 0 || size.y() <= 0 {
+            return Some(Vector2I::default());
+        }
+        if size.x() > MAX_PAINT_TEXTURE_LENGTH {
+            return None;
+        }
+
+        // Only reuse a shelf that isn't much taller than the rectangle, to limit wasted space.
+        let shelf_index = self.shelves.iter().position(|shelf| {
+            shelf.height >= size.y() && shelf.height <= size.y() * 2 &&
+                shelf.width + size.x() <= MAX_PAINT_TEXTURE_LENGTH
+        });
+        let shelf = match shelf_index {
+            Some(shelf_index) => &mut self.shelves[shelf_index],
+            None => {
+                if size.y() > MAX_PAINT_TEXTURE_LENGTH - self.height {
+                    return None;
+                }
+                self.shelves.push(Shelf { y: self.height, width: 0, height: size.y() });
+                self.height += size.y();
+                self.shelves.last_mut().unwrap()
+            }
+        };
+
+        let origin = Vector2I::new(shelf.width, shelf.y);
+        shelf.width += size.x();
+        self.width = self.width.max(shelf.width);
+        Some(origin)
+    }
+}
+
+fn put_texel(texels: &mut [u8], size: Vector2I, texel: Vector2I, color: ColorU) {
+    let offset = (texel.y() * size.x() + texel.x()) as usize * 4;
+    texels[offset..(offset + 4)].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+}
+
+// Copies an image, with straight alpha, into the paint texture.
+fn put_image(texels: &mut [u8], size: Vector2I, origin: Vector2I, image: &Image) {
+    let image_width = image.size().x() as usize;
+    for (y, row) in image.pixels().chunks(image_width.max(1)).enumerate() {
+        let offset = ((origin.y() as usize + y) * size.x() as usize + origin.x() as usize) * 4;
+        for (x, pixel) in row.iter().enumerate() {
+            let offset = offset + x * 4;
+            texels[offset..(offset + 4)].copy_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+        }
+    }
+}
+
+// Each gradient and pattern is described in the metadata texture, starting with four texels:
+//
+// 0. The images of the x and y axes under the transform from device space to the paint's space.
+// 1. The image of the origin under that transform, then the start and end radii of a radial
+//    gradient or the size of a pattern's image.
+// 2. The gradient line, or the position of the pattern's image in the paint texture followed by
+//    whether the pattern repeats horizontally and vertically.
+// 3. Whether the gradient is radial and how many color stops it has, or the pattern's opacity.
+//
+// A gradient continues with two texels for each color stop: its offset, then its color.
+
+fn gradient_metadata_length(gradient: &Gradient) -> u32 {
+    PAINT_METADATA_HEADER_LENGTH + gradient.stops().len() as u32 * 2
+}
+
+fn put_gradient_metadata(metadata: &mut [f32],
+                         index: u32,
+                         gradient: &Gradient,
+                         device_to_gradient: &Transform2F) {
+    let (line, radii, radial) = match *gradient.geometry() {
+        GradientGeometry::Linear(line) => (line, Vector2F::default(), 0.0),
+        GradientGeometry::Radial { line, start_radius, end_radius } => {
+            (line, Vector2F::new(start_radius, end_radius), 1.0)
+        }
+    };
+    put_transform_metadata(metadata, index, device_to_gradient, radii);
+    put_metadata(metadata, index + 2, [line.from_x(), line.from_y(), line.to_x(), line.to_y()]);
+    put_metadata(metadata, index + 3, [radial, gradient.stops().len() as f32, 0.0, 0.0]);
+
+    for (stop_index, stop) in gradient.stops().iter().enumerate() {
+        let color = stop.color.to_f32();
+        let stop_index = index + PAINT_METADATA_HEADER_LENGTH + stop_index as u32 * 2;
+        put_metadata(metadata, stop_index, [stop.offset, 0.0, 0.0, 0.0]);
+        put_metadata(metadata, stop_index + 1, [color.r(), color.g(), color.b(), color.a()]);
+    }
+}
+
+fn put_pattern_metadata(metadata: &mut [f32],
+                        index: u32,
+                        device_to_image: &Transform2F,
+                        image_origin: Vector2I,
+                        image_size: Vector2I,
+                        (repeat_x, repeat_y): (bool, bool),
+                        opacity: f32) {
+    put_transform_metadata(metadata, index, device_to_image, image_size.to_f32());
+    put_metadata(metadata, index + 2, [
+        image_origin.x() as f32,
+        image_origin.y() as f32,
+        if repeat_x { 1.0 } else { 0.0 },
+        if repeat_y { 1.0 } else { 0.0 },
+    ]);
+    put_metadata(metadata, index + 3, [opacity, 0.0, 0.0, 0.0]);
+}
+
+fn put_transform_metadata(metadata: &mut [f32],
+                          index: u32,
+                          transform: &Transform2F,
+                          extra: Vector2F) {
+    let origin = *transform * Vector2F::default();
+    let x_axis = *transform * Vector2F::new(1.0, 0.0) - origin;
+    let y_axis = *transform * Vector2F::new(0.0, 1.0) - origin;
+    put_metadata(metadata, index, [x_axis.x(), x_axis.y(), y_axis.x(), y_axis.y()]);
+    put_metadata(metadata, index + 1, [origin.x(), origin.y(), extra.x(), extra.y()]);
+}
+
+fn put_metadata(metadata: &mut [f32], index: u32, texel: [f32; 4]) {
+    let offset = index as usize * 4;
+    metadata[offset..(offset + 4)].copy_from_slice(&texel);
+}
+
+// Converts an index into the metadata texture, which wraps from one row to the next, to a texel.
+fn metadata_texel(index: u32) -> Vector2I {
+    let index = index as i32;
+    Vector2I::new(index % PAINT_METADATA_TEXTURE_WIDTH, index / PAINT_METADATA_TEXTURE_WIDTH)
+}
+
+pub(crate) fn scene_to_device_transform(options: &PreparedBuildOptions) -> Transform2F {
+    let mut transform = match options.transform {
+        PreparedRenderTransform::Transform2D(transform) => transform,
+        // TODO(pcwalton): Support gradients and patterns in 3D.
+        PreparedRenderTransform::None | PreparedRenderTransform::Perspective { .. } => {
+            Transform2F::default()
+        }
+    };
+    if options.subpixel_aa_enabled {
+        transform *= Transform2F::from_scale(Vector2F::new(3.0, 1.0));
+    }
+    transform
+}
+
+#[cfg(test)]
+mod test {
+    use crate::options::{BuildOptions, RasterizeOptions};
+    use crate::paint::Paint;
+    use crate::scene::{PathObject, Scene};
+    use pathfinder_content::color::ColorU;
+    use pathfinder_content::gradient::Gradient;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_content::pattern::{Image, Pattern, RepeatMode};
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use std::sync::Arc;
+
+    fn rect_outline(rect: RectF) -> Outline {
+        let mut contour = Contour::new();
+        contour.push_endpoint(rect.origin());
+        contour.push_endpoint(rect.upper_right());
+        contour.push_endpoint(rect.lower_right());
+        contour.push_endpoint(rect.lower_left());
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    #[test]
+    fn test_large_palette() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::new(24.0, 16.0)));
+
+        // The gradient's color stops share the paint textures with the palette.
+        let mut gradient = Gradient::linear(LineSegment2F::new(Vector2F::new(16.0, 0.0),
+                                                               Vector2F::new(24.0, 0.0)));
+        gradient.add_color_stop(0.0, ColorU { r: 255, g: 0, b: 0, a: 255 });
+        gradient.add_color_stop(1.0, ColorU { r: 0, g: 0, b: 255, a: 255 });
+        let gradient_paint_id = scene.push_paint(&Paint::Gradient(gradient.clone()));
+
+        let colors: Vec<ColorU> = (0..70_000u32).map(|index| {
+            ColorU { r: (index >> 16) as u8, g: (index >> 8) as u8, b: index as u8, a: 255 }
+        }).collect();
+        let paint_ids: Vec<_> = colors.iter().map(|&color| {
+            scene.push_paint(&Paint::Color(color))
+        }).collect();
+        assert_eq!(paint_ids[69_999].0, 70_000);
+
+        for &(x, paint_id) in &[(0.0, paint_ids[1]),
+                                (8.0, paint_ids[69_999]),
+                                (16.0, gradient_paint_id)] {
+            let outline = rect_outline(RectF::new(Vector2F::new(x, 0.0), Vector2F::new(8.0, 16.0)));
+            scene.push_path(PathObject::new(outline, paint_id, String::new()));
+        }
+
+        let pixels = scene.rasterize_to_pixels(Vector2I::new(24, 16), RasterizeOptions::default());
+        for &(x, color) in &[(4, colors[1]),
+                             (12, colors[69_999]),
+                             (20, gradient.color_at(Vector2F::new(20.5, 8.5)))] {
+            let pixel = &pixels[(8 * 24 + x) * 4..(8 * 24 + x) * 4 + 4];
+            for (&actual, &expected) in pixel.iter().zip(&[color.r, color.g, color.b, color.a]) {
+                assert!((actual as i32 - expected as i32).abs() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_paint_too_large() {
+        let mut scene = Scene::new();
+        let image = |width| {
+            Image::new(Vector2I::new(width, 1), Arc::new(vec![ColorU::black(); width as usize]))
+        };
+        let too_wide = Paint::Pattern(Pattern::new(image(4097), RepeatMode::Repeat));
+        assert!(scene.try_push_paint(&too_wide).is_none());
+
+        // The scene is left as it was.
+        assert_eq!(scene.push_paint(&Paint::black()).0, 0);
+        let widest = Paint::Pattern(Pattern::new(image(4096), RepeatMode::Repeat));
+        assert_eq!(scene.try_push_paint(&widest).map(|paint_id| paint_id.0), Some(1));
+    }
+
+    #[test]
+    fn test_large_gradient_hard_stop() {
+        // A gradient much wider than any lookup table, changing abruptly halfway across.
+        let red = ColorU { r: 255, g: 0, b: 0, a: 255 };
+        let blue = ColorU { r: 0, g: 0, b: 255, a: 255 };
+        let mut gradient = Gradient::linear(LineSegment2F::new(Vector2F::default(),
+                                                               Vector2F::new(2048.0, 0.0)));
+        gradient.add_color_stop(0.0, red);
+        gradient.add_color_stop(0.5, red);
+        gradient.add_color_stop(0.5, blue);
+        gradient.add_color_stop(1.0, blue);
+
+        let mut scene = Scene::new();
+        let view_box = RectF::new(Vector2F::default(), Vector2F::new(2048.0, 2.0));
+        scene.set_view_box(view_box);
+        let paint_id = scene.push_paint(&Paint::Gradient(gradient));
+        scene.push_path(PathObject::new(rect_outline(view_box), paint_id, String::new()));
+
+        let pixels = scene.rasterize_to_pixels(Vector2I::new(2048, 2),
+                                               RasterizeOptions::default());
+        for &(x, color) in &[(1020, red), (1023, red), (1024, blue), (1027, blue)] {
+            let pixel = &pixels[x * 4..x * 4 + 4];
+            for (&actual, &expected) in pixel.iter().zip(&[color.r, color.g, color.b, color.a]) {
+                assert!((actual as i32 - expected as i32).abs() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_scaled_repeating_pattern() {
+        let red = ColorU { r: 255, g: 0, b: 0, a: 255 };
+        let blue = ColorU { r: 0, g: 0, b: 255, a: 255 };
+        let image = Image::new(Vector2I::new(2, 1), Arc::new(vec![red, blue]));
+        let mut pattern = Pattern::new(image, RepeatMode::Repeat);
+        // Put the centers of the image's pixels on the centers of device pixels.
+        pattern.set_transform(&(Transform2F::from_translation(Vector2F::new(0.5, 0.0)) *
+                                Transform2F::from_scale(Vector2F::splat(64.0))));
+
+        let mut scene = Scene::new();
+        let view_box = RectF::new(Vector2F::default(), Vector2F::new(256.0, 4.0));
+        scene.set_view_box(view_box);
+        let paint_id = scene.push_paint(&Paint::Pattern(pattern.clone()));
+        scene.push_path(PathObject::new(rect_outline(view_box), paint_id, String::new()));
+
+        // The image is stored once at its native size, however large the pattern is drawn.
+        pattern.apply_opacity(0.5);
+        scene.push_paint(&Paint::Pattern(pattern));
+        let options = BuildOptions::default().prepare(view_box);
+        assert_eq!(scene.build_paint_info(&options).data.size, Vector2I::new(2, 1));
+
+        let pixels = scene.rasterize_to_pixels(Vector2I::new(256, 4), RasterizeOptions::default());
+        for &(x, color) in &[(32, red), (96, blue), (160, red), (224, blue)] {
+            let pixel = &pixels[x * 4..x * 4 + 4];
+            for (&actual, &expected) in pixel.iter().zip(&[color.r, color.g, color.b, color.a]) {
+                assert!((actual as i32 - expected as i32).abs() <= 1);
+            }
+        }
+    }
 }
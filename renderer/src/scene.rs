@@ -11,22 +11,48 @@
 //! A set of paths to be rendered.
 
 use crate::builder::SceneBuilder;
-use crate::concurrent::executor::Executor;
-use crate::options::{BuildOptions, PreparedBuildOptions};
+use crate::concurrent::executor::{Executor, SequentialExecutor};
+use crate::cpu::renderer::Renderer as CPURenderer;
+use crate::gpu::options::RendererOptions;
+use crate::options::{BuildOptions, PreparedBuildOptions, RasterizeOptions};
 use crate::options::{PreparedRenderTransform, RenderCommandListener};
-use crate::paint::{Paint, PaintId};
+use crate::paint::{Paint, PaintAtlas, PaintId};
+use crate::tile_cache::TileCache;
 use hashbrown::HashMap;
-use pathfinder_geometry::vector::Vector2F;
+#[cfg(feature = "image")]
+use image::RgbaImage;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_content::color::ColorU;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::Outline;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::mem;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone)]
 pub struct Scene {
     pub(crate) paths: Vec<PathObject>,
+    // The ID of each path in `paths`, in the same order.
+    path_ids: Vec<PathId>,
+    // The index in `paths` of each path ID, kept in sync with `path_ids`.
+    path_indices: HashMap<PathId, usize>,
+    // The group that each path in `paths` belongs to, if any.
+    pub(crate) path_groups: Vec<Option<GroupId>>,
+    next_path_id: u32,
+    // Every group, indexed by ID. Parents always precede their children.
+    pub(crate) groups: Vec<GroupNode>,
+    // The groups that newly pushed paths and groups are added to, innermost last.
+    group_stack: Vec<GroupId>,
     pub(crate) paints: Vec<Paint>,
     paint_cache: HashMap<Paint, PaintId>,
+    pub(crate) paint_atlas: PaintAtlas,
     bounds: RectF,
     view_box: RectF,
 }
@@ -36,28 +62,291 @@ impl Scene {
     pub fn new() -> Scene {
         Scene {
             paths: vec![],
+            path_ids: vec![],
+            path_indices: HashMap::new(),
+            path_groups: vec![],
+            next_path_id: 0,
+            groups: vec![],
+            group_stack: vec![],
             paints: vec![],
             paint_cache: HashMap::new(),
+            paint_atlas: PaintAtlas::default(),
             bounds: RectF::default(),
             view_box: RectF::default(),
         }
     }
 
-    pub fn push_path(&mut self, path: PathObject) {
-        self.bounds = self.bounds.union_rect(path.outline.bounds());
+    /// Adds a path on top of all the others, in the current group, returning an ID that stays
+    /// valid until the path is removed.
+    pub fn push_path(&mut self, path: PathObject) -> PathId {
+        let path_id = PathId(self.next_path_id);
+        self.next_path_id += 1;
+        let group_id = self.group_stack.last().cloned();
+        if let Some(path_bounds) = self.path_bounds(&path, group_id) {
+            self.bounds = self.bounds.union_rect(path_bounds);
+        }
+        self.path_indices.insert(path_id, self.paths.len());
         self.paths.push(path);
+        self.path_ids.push(path_id);
+        self.path_groups.push(group_id);
+        path_id
+    }
+
+    /// Starts a new group inside the current one. Paths and groups pushed until the matching
+    /// `pop_group()` belong to it.
+    pub fn push_group(&mut self, group: SceneGroup) -> GroupId {
+        let group_id = GroupId(self.groups.len() as u32);
+        self.groups.push(GroupNode::new(group, self.group_stack.last().cloned()));
+        self.group_stack.push(group_id);
+        group_id
+    }
+
+    /// Ends the current group, so that subsequent paths go into its parent.
+    #[inline]
+    pub fn pop_group(&mut self) {
+        self.group_stack.pop();
+    }
+
+    #[inline]
+    pub fn group(&self, group_id: GroupId) -> Option<&SceneGroup> {
+        self.groups.get(group_id.0 as usize).map(|node| &node.group)
+    }
+
+    /// Returns the group that a path belongs to, or `None` if the path doesn't exist or is at the
+    /// top level of the scene.
+    pub fn path_group(&self, path_id: PathId) -> Option<GroupId> {
+        self.path_index(path_id).and_then(|index| self.path_groups[index])
+    }
+
+    /// Changes the transform, opacity, or clip of a group, keeping its contents, and returns the
+    /// old settings.
+    pub fn replace_group(&mut self, group_id: GroupId, new_group: SceneGroup)
+                         -> Option<SceneGroup> {
+        let index = group_id.0 as usize;
+        if index >= self.groups.len() {
+            return None;
+        }
+        let old_group = mem::replace(&mut self.groups[index].group, new_group);
+
+        // Tiles cached for paths in this group and the groups nested inside it are now stale.
+        let mut changed = vec![false; self.groups.len()];
+        changed[index] = true;
+        for descendant_index in index..self.groups.len() {
+            let node = &mut self.groups[descendant_index];
+            if let Some(parent) = node.parent {
+                changed[descendant_index] |= changed[parent.0 as usize];
+            }
+            if changed[descendant_index] {
+                node.state_id = next_cache_id();
+            }
+        }
+
+        self.recalculate_bounds();
+        Some(old_group)
+    }
+
+    /// Replaces a path in place, keeping its ID, group, and position, and returns the old path.
+    pub fn replace_path(&mut self, path_id: PathId, new_path: PathObject) -> Option<PathObject> {
+        let index = self.path_index(path_id)?;
+        let group_id = self.path_groups[index];
+        let old_bounds = self.path_bounds(&self.paths[index], group_id);
+        let new_bounds = self.path_bounds(&new_path, group_id);
+        let old_path = mem::replace(&mut self.paths[index], new_path);
+        if !self.update_bounds_for_removal(old_bounds) {
+            if let Some(new_bounds) = new_bounds {
+                self.bounds = self.bounds.union_rect(new_bounds);
+            }
+        }
+        Some(old_path)
+    }
+
+    pub fn remove_path(&mut self, path_id: PathId) -> Option<PathObject> {
+        let index = self.path_index(path_id)?;
+        let old_bounds = self.path_bounds(&self.paths[index], self.path_groups[index]);
+        self.path_indices.remove(&path_id);
+        self.path_ids.remove(index);
+        self.path_groups.remove(index);
+        let old_path = self.paths.remove(index);
+        self.reindex_paths(index..self.paths.len());
+        self.update_bounds_for_removal(old_bounds);
+        Some(old_path)
+    }
+
+    /// Moves a path so that it's drawn just beneath `before`, or on top of all the others if
+    /// `before` is `None`. Returns false, leaving the scene untouched, if either path doesn't
+    /// exist.
+    pub fn move_path(&mut self, path_id: PathId, before: Option<PathId>) -> bool {
+        let index = match self.path_index(path_id) {
+            None => return false,
+            Some(index) => index,
+        };
+        if let Some(before) = before {
+            if self.path_index(before).is_none() {
+                return false;
+            }
+        }
+        if before == Some(path_id) {
+            return true;
+        }
+
+        let path = self.paths.remove(index);
+        self.path_ids.remove(index);
+        let group_id = self.path_groups.remove(index);
+        // The map still holds the indices from before the removal.
+        let new_index = match before.map(|before| self.path_index(before).unwrap()) {
+            None => self.paths.len(),
+            Some(before_index) if before_index > index => before_index - 1,
+            Some(before_index) => before_index,
+        };
+        self.paths.insert(new_index, path);
+        self.path_ids.insert(new_index, path_id);
+        self.path_groups.insert(new_index, group_id);
+        self.reindex_paths(index.min(new_index)..(index.max(new_index) + 1));
+        true
+    }
+
+    #[inline]
+    pub fn path(&self, path_id: PathId) -> Option<&PathObject> {
+        self.path_index(path_id).map(|index| &self.paths[index])
+    }
+
+    /// Returns the ID of the bottommost path with the given name.
+    pub fn find_by_name(&self, name: &str) -> Option<PathId> {
+        self.paths
+            .iter()
+            .position(|path| path.name == name)
+            .map(|index| self.path_ids[index])
+    }
+
+    /// Returns the IDs of all paths, from bottom to top.
+    #[inline]
+    pub fn path_ids(&self) -> &[PathId] {
+        &self.path_ids
+    }
+
+    #[inline]
+    fn path_index(&self, path_id: PathId) -> Option<usize> {
+        self.path_indices.get(&path_id).cloned()
+    }
+
+    fn reindex_paths(&mut self, range: Range<usize>) {
+        for index in range {
+            self.path_indices.insert(self.path_ids[index], index);
+        }
+    }
+
+    // Recalculates the scene bounds after a path with the given bounds has been removed or
+    // replaced, unless the path was strictly inside them and so couldn't have affected them.
+    // Returns true if the bounds were recalculated.
+    fn update_bounds_for_removal(&mut self, old_bounds: Option<RectF>) -> bool {
+        let old_bounds = match old_bounds {
+            None => return false,
+            Some(old_bounds) => old_bounds,
+        };
+        if old_bounds.min_x() > self.bounds.min_x() && old_bounds.min_y() > self.bounds.min_y() &&
+                old_bounds.max_x() < self.bounds.max_x() &&
+                old_bounds.max_y() < self.bounds.max_y() {
+            return false;
+        }
+        self.recalculate_bounds();
+        true
+    }
+
+    pub(crate) fn recalculate_bounds(&mut self) {
+        let mut bounds = RectF::default();
+        for (path, &group_id) in self.paths.iter().zip(self.path_groups.iter()) {
+            if let Some(path_bounds) = self.path_bounds(path, group_id) {
+                bounds = bounds.union_rect(path_bounds);
+            }
+        }
+        self.bounds = bounds;
+    }
+
+    // Returns the bounds of a path in scene space, or `None` if its groups clip it out entirely.
+    fn path_bounds(&self, path: &PathObject, mut group_id: Option<GroupId>) -> Option<RectF> {
+        let mut bounds = path.outline.bounds();
+        while let Some(node) = group_id.map(|group_id| &self.groups[group_id.0 as usize]) {
+            if let Some((ref clip_path, _)) = node.group.clip_path {
+                bounds = bounds.intersection(clip_path.bounds())?;
+            }
+            bounds = node.group.transform * bounds;
+            group_id = node.parent;
+        }
+        Some(bounds)
+    }
+
+    /// Returns the outline of a path in scene space, with the transforms and clips of its groups
+    /// applied.
+    pub(crate) fn path_outline(&self, path_index: usize) -> Cow<'_, Outline> {
+        let path = &self.paths[path_index];
+        let mut group_id = self.path_groups[path_index];
+        if group_id.is_none() {
+            return Cow::Borrowed(&path.outline);
+        }
+
+        let mut outline = path.outline.clone();
+        while let Some(node) = group_id.map(|group_id| &self.groups[group_id.0 as usize]) {
+            if let Some((ref clip_path, clip_fill_rule)) = node.group.clip_path {
+                outline.clip_against_outline(path.fill_rule, clip_path, clip_fill_rule);
+            }
+            if !node.group.transform.is_identity() {
+                outline.transform(&node.group.transform);
+            }
+            group_id = node.parent;
+        }
+        Cow::Owned(outline)
+    }
+
+    /// Identifies a path's outline along with the state of its groups, for caching tiles.
+    pub(crate) fn path_cache_id(&self, path_index: usize) -> (u64, Option<u64>) {
+        let group_state_id = self.path_groups[path_index].map(|group_id| {
+            self.groups[group_id.0 as usize].state_id
+        });
+        (self.paths[path_index].outline_id, group_state_id)
+    }
+
+    /// Returns the groups around a path that must be composited as layers, outermost first.
+    pub(crate) fn path_layers(&self, path_index: usize) -> Vec<GroupId> {
+        let mut layers = vec![];
+        let mut group_id = self.path_groups[path_index];
+        while let Some(node_id) = group_id {
+            let node = &self.groups[node_id.0 as usize];
+            if node.group.is_layer() {
+                layers.push(node_id);
+            }
+            group_id = node.parent;
+        }
+        layers.reverse();
+        layers
     }
 
-    #[allow(clippy::trivially_copy_pass_by_ref)]
+    /// Adds a paint, returning the ID of an identical existing paint if there is one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no room left for the paint in the paint textures; see `MAX_PAINT_COUNT`.
+    /// Use `try_push_paint()` to handle that case instead.
     pub fn push_paint(&mut self, paint: &Paint) -> PaintId {
+        match self.try_push_paint(paint) {
+            Some(paint_id) => paint_id,
+            None => panic!("Scene has no room left for the paint in its paint textures!"),
+        }
+    }
+
+    /// Like `push_paint()`, but returns `None`, leaving the scene unchanged, if there's no room
+    /// left for the paint in the paint textures.
+    pub fn try_push_paint(&mut self, paint: &Paint) -> Option<PaintId> {
         if let Some(paint_id) = self.paint_cache.get(paint) {
-            return *paint_id;
+            return Some(*paint_id);
+        }
+        if !self.paint_atlas.allocate(paint) {
+            return None;
         }
 
-        let paint_id = PaintId(self.paints.len() as u16);
-        self.paint_cache.insert(*paint, paint_id);
-        self.paints.push(*paint);
-        paint_id
+        let paint_id = PaintId(self.paints.len() as u32);
+        self.paint_cache.insert(paint.clone(), paint_id);
+        self.paints.push(paint.clone());
+        Some(paint_id)
     }
 
     #[inline]
@@ -150,7 +439,29 @@ impl Scene {
             .any(|path_object| path_object.paint != first_paint_id) {
             return None;
         }
-        Some(self.paints[first_paint_id.0 as usize].color)
+        // Monochrome rendering can't read back the destination color or composite layers.
+        if self.needs_readable_framebuffer() || self.has_layers() {
+            return None;
+        }
+        match self.paints[first_paint_id.0 as usize] {
+            Paint::Color(color) => Some(color),
+            Paint::Gradient(_) | Paint::Pattern(_) => None,
+        }
+    }
+
+    /// Returns true if any path or group uses a blend mode that reads the color of the content
+    /// beneath.
+    ///
+    /// Layers are composited over the whole view box, so groups only need to read it for the
+    /// separable blend modes.
+    pub fn needs_readable_framebuffer(&self) -> bool {
+        self.paths.iter().any(|path_object| path_object.blend_mode.needs_readable_framebuffer()) ||
+            self.groups.iter().any(|node| !node.group.blend_mode.is_porter_duff())
+    }
+
+    #[inline]
+    fn has_layers(&self) -> bool {
+        self.groups.iter().any(|node| node.group.is_layer())
     }
 
     #[inline]
@@ -171,7 +482,144 @@ impl Scene {
         let prepared_options = options.prepare(self.bounds);
         SceneBuilder::new(self, &prepared_options, listener).build(executor)
     }
-    
+
+    /// Like `build()`, but reuses the tiles of paths that haven't changed since the last build
+    /// with the given cache, and then updates the cache with the tiles of this build.
+    pub fn build_with_cache<E>(&self,
+                               options: BuildOptions,
+                               listener: Box<dyn RenderCommandListener>,
+                               executor: &E,
+                               cache: &mut TileCache)
+                               where E: Executor {
+        let prepared_options = options.prepare(self.bounds);
+        let mut builder = SceneBuilder::with_cache(self, &prepared_options, listener, cache);
+        builder.build(executor);
+        if let Some(used_cache_entries) = builder.into_used_cache_entries() {
+            cache.replace_entries(used_cache_entries);
+        }
+    }
+
+    /// Builds and renders the scene on the CPU into an image of the given size, without needing
+    /// a GPU or a window. Call `save` on the result to write it out as a PNG.
+    ///
+    /// This requires the `image` feature.
+    #[cfg(feature = "image")]
+    pub fn rasterize(&self, size: Vector2I, options: RasterizeOptions) -> RgbaImage {
+        let pixels = self.rasterize_to_pixels(size, options);
+        RgbaImage::from_raw(size.x() as u32, size.y() as u32, pixels).unwrap()
+    }
+
+    /// Like `rasterize()`, but returns the rows of unpremultiplied RGBA8 pixels, top to bottom.
+    pub fn rasterize_to_pixels(&self, size: Vector2I, options: RasterizeOptions) -> Vec<u8> {
+        let renderer_options = RendererOptions { background_color: options.background_color };
+        let renderer = Arc::new(Mutex::new(CPURenderer::new(size, renderer_options)));
+        let listener_renderer = renderer.clone();
+        self.build(options.build_options,
+                   Box::new(move |command| {
+                       listener_renderer.lock().unwrap().render_command(&command)
+                   }),
+                   &SequentialExecutor);
+
+        let pixels = renderer.lock().unwrap().pixels();
+        pixels
+    }
+
+    /// Returns the topmost path whose filled area contains `point`, if any.
+    ///
+    /// The point is in device coordinates, as the scene would be built with `options`. With the
+    /// default options, those are scene coordinates. Paths are tested against their outlines,
+    /// including group transforms and clips, so the result matches what's drawn regardless of
+    /// paint.
+    pub fn hit_test(&self, point: Vector2F, options: BuildOptions) -> Option<PathId> {
+        let options = options.prepare(self.bounds);
+        let point = if options.subpixel_aa_enabled {
+            point.scale_xy(Vector2F::new(3.0, 1.0))
+        } else {
+            point
+        };
+
+        let transform = match options.transform {
+            PreparedRenderTransform::None => Some(Transform2F::default()),
+            PreparedRenderTransform::Transform2D(transform) => Some(transform),
+            PreparedRenderTransform::Perspective { .. } => None,
+        };
+
+        // Without perspective or dilation, device space is an affine image of scene space, so we
+        // can map the point back instead of building each outline in device space.
+        if let Some(mut transform) = transform {
+            if options.dilation.is_zero() {
+                if !self.effective_view_box(&options).contains_point(point) {
+                    return None;
+                }
+                if options.subpixel_aa_enabled {
+                    transform *= Transform2F::from_scale(Vector2F::new(3.0, 1.0));
+                }
+                return self.hit_test_scene(transform.inverse() * point);
+            }
+        }
+
+        let path_index = (0..self.paths.len()).rev().find(|&path_index| {
+            // Reject cheaply by bounds before transforming the whole outline, when we can.
+            if let Some(transform) = transform {
+                let bounds = match self.path_bounds(&self.paths[path_index],
+                                                    self.path_groups[path_index]) {
+                    None => return false,
+                    Some(bounds) => bounds,
+                };
+                let mut bounds = transform * bounds;
+                if options.subpixel_aa_enabled {
+                    bounds = bounds.scale_xy(Vector2F::new(3.0, 1.0));
+                }
+                if !bounds.dilate(options.dilation).contains_point(point) {
+                    return false;
+                }
+            }
+
+            let outline = self.apply_render_options(&self.path_outline(path_index), &options);
+            outline.contains_point(point, self.paths[path_index].fill_rule)
+        })?;
+        Some(self.path_ids[path_index])
+    }
+
+    /// Returns the topmost path whose filled area contains `point`, given in scene coordinates.
+    ///
+    /// Unlike `hit_test()`, this doesn't depend on build options. The point is mapped through the
+    /// inverse of each candidate's group transforms and tested against its clips and original
+    /// outline, so nothing is copied or transformed per path.
+    pub fn hit_test_scene(&self, point: Vector2F) -> Option<PathId> {
+        let path_index = (0..self.paths.len()).rev().find(|&path_index| {
+            let path = &self.paths[path_index];
+            let group_id = self.path_groups[path_index];
+            match self.path_bounds(path, group_id) {
+                Some(bounds) if bounds.contains_point(point) => {}
+                _ => return false,
+            }
+            match self.point_in_group_space(point, group_id) {
+                None => false,
+                Some(point) => path.outline.contains_point(point, path.fill_rule),
+            }
+        })?;
+        Some(self.path_ids[path_index])
+    }
+
+    // Maps a scene-space point into the space of the paths in a group, or returns `None` if the
+    // clip of the group or one of its ancestors excludes it.
+    fn point_in_group_space(&self, point: Vector2F, group_id: Option<GroupId>)
+                            -> Option<Vector2F> {
+        let node = match group_id {
+            None => return Some(point),
+            Some(group_id) => &self.groups[group_id.0 as usize],
+        };
+        let point = node.group.transform.inverse() *
+            self.point_in_group_space(point, node.parent)?;
+        if let Some((ref clip_path, clip_fill_rule)) = node.group.clip_path {
+            if !clip_path.contains_point(point, clip_fill_rule) {
+                return None;
+            }
+        }
+        Some(point)
+    }
+
     pub fn paths<'a>(&'a self) -> PathIter {
         PathIter {
             scene: self,
@@ -197,17 +645,183 @@ impl<'a> Iterator for PathIter<'a> {
         item
     }
 }
+/// Identifies a path within a scene. IDs aren't reused, and they aren't preserved when a scene is
+/// saved and loaded.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PathId(u32);
+
+/// Identifies a group within a scene.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GroupId(pub(crate) u32);
+
+/// A transform, opacity, blur, blend mode, and clip shared by the paths and groups inside a group.
+///
+/// The transform and clip are applied to the outlines of the contents, with the clip in the same
+/// coordinate space as the contents. A group that isn't fully opaque, is blurred, has a blend mode
+/// other than source-over, or is isolated is rendered as a layer: its contents are drawn on their
+/// own, blurred, and the result is composited with the group's opacity and blend mode, so
+/// overlapping contents don't show through one another. If paths are moved so that the contents of
+/// a group are no longer adjacent, each run of adjacent contents is a separate layer.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SceneGroup {
+    transform: Transform2F,
+    opacity: f32,
+    blur: f32,
+    blend_mode: BlendMode,
+    isolated: bool,
+    clip_path: Option<(Outline, FillRule)>,
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupNode {
+    pub(crate) group: SceneGroup,
+    pub(crate) parent: Option<GroupId>,
+    // Changes whenever this group or one of its ancestors is replaced.
+    pub(crate) state_id: u64,
+}
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PathObject {
     outline: Outline,
+    /// Uniquely identifies the outline, for caching tiles. Clones share it, since their outlines
+    /// are the same.
+    #[cfg_attr(feature = "serde", serde(skip, default = "next_cache_id"))]
+    outline_id: u64,
     paint: PaintId,
     name: String,
+    fill_rule: FillRule,
+    blend_mode: BlendMode,
+}
+
+// Hands out the outline and group state IDs that cached tiles are keyed on.
+static NEXT_CACHE_ID: AtomicU64 = AtomicU64::new(0);
+
+fn next_cache_id() -> u64 {
+    NEXT_CACHE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+impl GroupNode {
+    #[inline]
+    pub(crate) fn new(group: SceneGroup, parent: Option<GroupId>) -> GroupNode {
+        GroupNode { group, parent, state_id: next_cache_id() }
+    }
+}
+
+impl SceneGroup {
+    /// Creates a group with no transform, full opacity, no blur, source-over blending, and no
+    /// clip.
+    #[inline]
+    pub fn new() -> SceneGroup {
+        SceneGroup {
+            transform: Transform2F::default(),
+            opacity: 1.0,
+            blur: 0.0,
+            blend_mode: BlendMode::SrcOver,
+            isolated: false,
+            clip_path: None,
+        }
+    }
+
+    #[inline]
+    pub fn transform(&self) -> Transform2F {
+        self.transform
+    }
+
+    #[inline]
+    pub fn set_transform(&mut self, transform: Transform2F) {
+        self.transform = transform;
+    }
+
+    #[inline]
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    #[inline]
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    #[inline]
+    pub fn blur(&self) -> f32 {
+        self.blur
+    }
+
+    /// Sets the standard deviation of the Gaussian blur applied to the contents, in scene units.
+    /// Zero, the default, turns blurring off.
+    ///
+    /// The blur is applied after the contents are clipped, and only what lies within the view box
+    /// is blurred.
+    #[inline]
+    pub fn set_blur(&mut self, blur: f32) {
+        self.blur = blur;
+    }
+
+    #[inline]
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Sets how the layer holding the contents is composited onto the content beneath.
+    ///
+    /// The layer covers the whole view box, transparent wherever the contents draw nothing. So
+    /// unlike for a path, an operator such as `SrcIn` clears the content beneath everywhere the
+    /// group leaves empty.
+    #[inline]
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    #[inline]
+    pub fn is_isolated(&self) -> bool {
+        self.isolated
+    }
+
+    /// Sets whether the contents are drawn in a layer of their own even if nothing else calls for
+    /// one, so that the blend modes of the groups inside combine them only with one another.
+    #[inline]
+    pub fn set_isolated(&mut self, isolated: bool) {
+        self.isolated = isolated;
+    }
+
+    #[inline]
+    pub fn clip_path(&self) -> Option<&(Outline, FillRule)> {
+        self.clip_path.as_ref()
+    }
+
+    #[inline]
+    pub fn set_clip_path(&mut self, clip_path: Option<(Outline, FillRule)>) {
+        self.clip_path = clip_path;
+    }
+
+    #[inline]
+    pub(crate) fn is_layer(&self) -> bool {
+        self.opacity < 1.0 || self.blur > 0.0 || self.blend_mode != BlendMode::SrcOver ||
+            self.isolated
+    }
+}
+
+impl Default for SceneGroup {
+    #[inline]
+    fn default() -> SceneGroup {
+        SceneGroup::new()
+    }
 }
 
 impl PathObject {
+    /// Creates a path object filled with the nonzero winding rule.
     #[inline]
     pub fn new(outline: Outline, paint: PaintId, name: String) -> PathObject {
-        PathObject { outline, paint, name }
+        PathObject {
+            outline,
+            outline_id: next_cache_id(),
+            paint,
+            name,
+            fill_rule: FillRule::Winding,
+            blend_mode: BlendMode::SrcOver,
+        }
     }
 
     #[inline]
@@ -215,8 +829,219 @@ impl PathObject {
         &self.outline
     }
 
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     #[inline]
     pub(crate) fn paint(&self) -> PaintId {
         self.paint
     }
+
+    #[inline]
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    #[inline]
+    pub fn set_fill_rule(&mut self, fill_rule: FillRule) {
+        self.fill_rule = fill_rule;
+    }
+
+    #[inline]
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    #[inline]
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PathObject, Scene, SceneGroup};
+    use crate::options::{BuildOptions, RasterizeOptions, RenderTransform};
+    use crate::paint::{Paint, PaintId};
+    use pathfinder_content::color::ColorU;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::gradient::Gradient;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+
+    fn rect_path(rect: RectF, name: &str) -> PathObject {
+        let mut contour = Contour::new();
+        contour.push_endpoint(rect.origin());
+        contour.push_endpoint(rect.upper_right());
+        contour.push_endpoint(rect.lower_right());
+        contour.push_endpoint(rect.lower_left());
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        PathObject::new(outline, PaintId(0), name.to_owned())
+    }
+
+    #[test]
+    fn test_mutate_paths() {
+        let mut scene = Scene::new();
+        let small = RectF::new(Vector2F::splat(1.0), Vector2F::splat(2.0));
+        let large = RectF::new(Vector2F::splat(1.0), Vector2F::splat(9.0));
+        let a = scene.push_path(rect_path(small, "a"));
+        let b = scene.push_path(rect_path(large, "b"));
+        let c = scene.push_path(rect_path(small, "c"));
+        assert_eq!(scene.bounds().max_x(), 10.0);
+
+        assert!(scene.move_path(c, Some(a)));
+        assert!(scene.move_path(a, None));
+        assert_eq!(scene.path_ids(), &[c, b, a]);
+        assert!(scene.move_path(b, Some(c)));
+        assert!(scene.move_path(c, Some(a)));
+        assert_eq!(scene.path_ids(), &[b, c, a]);
+        for &(path_id, name) in &[(a, "a"), (b, "b"), (c, "c")] {
+            assert_eq!(scene.path(path_id).unwrap().name(), name);
+        }
+
+        assert_eq!(scene.remove_path(b).unwrap().name(), "b");
+        assert!(scene.remove_path(b).is_none());
+        assert!(!scene.move_path(a, Some(b)));
+        assert_eq!(scene.bounds().max_x(), 3.0);
+
+        assert!(scene.replace_path(c, rect_path(large, "d")).is_some());
+        assert_eq!(scene.find_by_name("d"), Some(c));
+        assert_eq!(scene.find_by_name("c"), None);
+        assert_eq!(scene.bounds().max_x(), 10.0);
+
+        // Paths strictly inside the bounds can change without shrinking them.
+        let inner = RectF::new(Vector2F::splat(4.0), Vector2F::splat(2.0));
+        let e = scene.push_path(rect_path(inner, "e"));
+        let bounds = scene.bounds();
+        assert!(scene.remove_path(a).is_some());
+        assert_eq!(scene.bounds(), bounds);
+        let moved = RectF::new(Vector2F::splat(6.0), Vector2F::splat(9.0));
+        assert!(scene.replace_path(e, rect_path(moved, "e")).is_some());
+        assert_eq!(scene.bounds().max_x(), 15.0);
+        assert!(scene.remove_path(e).is_some());
+        assert_eq!(scene.bounds().max_x(), 10.0);
+        assert_eq!(scene.path(c).unwrap().name(), "d");
+    }
+
+    #[test]
+    fn test_group_layer() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(32.0)));
+        scene.push_paint(&Paint::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+
+        let mut group = SceneGroup::new();
+        group.set_transform(Transform2F::from_translation(Vector2F::new(8.0, 0.0)));
+        group.set_opacity(0.5);
+        let clip_rect = RectF::new(Vector2F::default(), Vector2F::new(12.0, 32.0));
+        group.set_clip_path(Some((rect_path(clip_rect, "").outline().clone(), FillRule::Winding)));
+        scene.push_group(group);
+        scene.push_path(rect_path(RectF::new(Vector2F::default(), Vector2F::splat(16.0)), "a"));
+        scene.push_path(rect_path(RectF::new(Vector2F::splat(4.0), Vector2F::splat(16.0)), "b"));
+        scene.pop_group();
+        assert_eq!(scene.bounds().max_x(), 20.0);
+
+        let pixels = scene.rasterize_to_pixels(Vector2I::splat(32), RasterizeOptions::default());
+        let alpha = |x: usize, y: usize| pixels[(y * 32 + x) * 4 + 3];
+        // The overlapping paths are composited together, so the overlap isn't any darker.
+        let overlap_alpha = alpha(14, 10);
+        assert!(overlap_alpha == 127 || overlap_alpha == 128);
+        assert_eq!(alpha(9, 2), overlap_alpha);
+        // Clipped out, and outside the transformed paths, respectively.
+        assert_eq!(alpha(22, 6), 0);
+        assert_eq!(alpha(4, 4), 0);
+    }
+
+    #[test]
+    fn test_group_blur() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(32.0)));
+        scene.push_paint(&Paint::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+        let mut gradient = Gradient::linear(LineSegment2F::new(Vector2F::default(),
+                                                               Vector2F::new(32.0, 0.0)));
+        gradient.add_color_stop(0.0, ColorU { r: 0, g: 0, b: 255, a: 255 });
+        gradient.add_color_stop(1.0, ColorU { r: 0, g: 255, b: 0, a: 255 });
+        let gradient = scene.push_paint(&Paint::Gradient(gradient));
+
+        // A blurred gradient, with an opaque rect covering it entirely.
+        let rect = RectF::new(Vector2F::default(), Vector2F::new(16.0, 32.0));
+        let mut group = SceneGroup::new();
+        group.set_blur(2.0);
+        scene.push_group(group);
+        scene.push_path(PathObject::new(rect_path(rect, "").outline().clone(),
+                                        gradient,
+                                        "blurred".to_owned()));
+        scene.pop_group();
+        scene.push_path(rect_path(rect, "cover"));
+
+        let pixels = scene.rasterize_to_pixels(Vector2I::splat(32), RasterizeOptions::default());
+        let pixel = |x: usize, y: usize| &pixels[(y * 32 + x) * 4..(y * 32 + x + 1) * 4];
+        assert_eq!(pixel(8, 16), &[255, 0, 0, 255]);
+        // The blur spreads past the cover, even though the cover hides all of the blurred path.
+        let edge = pixel(17, 16);
+        assert!(edge[3] > 45 && edge[3] < 70, "unexpected edge alpha {}", edge[3]);
+        assert!(edge[0] == 0 && edge[1] > 0 && edge[2] > 0);
+        assert_eq!(pixel(24, 16)[3], 0);
+    }
+
+    #[test]
+    fn test_hit_test() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(64.0)));
+        let bottom = scene.push_path(rect_path(RectF::new(Vector2F::default(),
+                                                          Vector2F::splat(16.0)), "bottom"));
+        let mut group = SceneGroup::new();
+        group.set_transform(Transform2F::from_translation(Vector2F::splat(8.0)));
+        scene.push_group(group);
+        let top = scene.push_path(rect_path(RectF::new(Vector2F::default(),
+                                                       Vector2F::splat(16.0)), "top"));
+        scene.pop_group();
+
+        let options = BuildOptions::default();
+        assert_eq!(scene.hit_test(Vector2F::splat(4.0), options.clone()), Some(bottom));
+        assert_eq!(scene.hit_test(Vector2F::splat(12.0), options.clone()), Some(top));
+        assert_eq!(scene.hit_test(Vector2F::splat(20.0), options.clone()), Some(top));
+        assert_eq!(scene.hit_test(Vector2F::splat(30.0), options), None);
+
+        let options = BuildOptions {
+            transform: RenderTransform::Transform2D(Transform2F::from_scale(Vector2F::splat(2.0))),
+            ..BuildOptions::default()
+        };
+        assert_eq!(scene.hit_test(Vector2F::splat(8.0), options.clone()), Some(bottom));
+        assert_eq!(scene.hit_test(Vector2F::splat(40.0), options), Some(top));
+
+        let options = BuildOptions { dilation: Vector2F::splat(0.5), ..BuildOptions::default() };
+        assert_eq!(scene.hit_test(Vector2F::splat(12.0), options.clone()), Some(top));
+        assert_eq!(scene.hit_test(Vector2F::splat(24.2), options), Some(top));
+    }
+
+    #[test]
+    fn test_hit_test_scene() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(64.0)));
+        let bottom = scene.push_path(rect_path(RectF::new(Vector2F::default(),
+                                                          Vector2F::splat(32.0)), "bottom"));
+        let mut group = SceneGroup::new();
+        group.set_transform(Transform2F::from_translation(Vector2F::splat(8.0)) *
+                            Transform2F::from_scale(Vector2F::splat(2.0)));
+        let clip_rect = RectF::new(Vector2F::default(), Vector2F::splat(4.0));
+        group.set_clip_path(Some((rect_path(clip_rect, "clip").outline, FillRule::Winding)));
+        scene.push_group(group);
+        let top = scene.push_path(rect_path(RectF::new(Vector2F::default(),
+                                                       Vector2F::splat(8.0)), "top"));
+        scene.pop_group();
+
+        // The top path covers (8, 8) to (24, 24), clipped to (8, 8) to (16, 16).
+        assert_eq!(scene.hit_test_scene(Vector2F::splat(4.0)), Some(bottom));
+        assert_eq!(scene.hit_test_scene(Vector2F::splat(12.0)), Some(top));
+        assert_eq!(scene.hit_test_scene(Vector2F::splat(20.0)), Some(bottom));
+        assert_eq!(scene.hit_test_scene(Vector2F::splat(40.0)), None);
+        assert_eq!(scene.hit_test(Vector2F::splat(12.0), BuildOptions::default()), Some(top));
+    }
 }
@@ -0,0 +1,598 @@
+// pathfinder/renderer/src/serialization/binary.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A compact binary encoding for serde.
+//!
+//! Values are stored in little-endian order with no padding and no field names. Sequences, maps,
+//! and strings are prefixed with their length as a `u32`, and enums with their variant index as a
+//! `u32`. The encoding isn't self-describing, so it can only be read back into the same types
+//! that wrote it.
+
+use super::SceneFormatError;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::de::value::U32Deserializer;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+use std::io::{Read, Write};
+
+pub(crate) struct Serializer<W> where W: Write {
+    writer: W,
+}
+
+impl<W> Serializer<W> where W: Write {
+    pub(crate) fn new(writer: W) -> Serializer<W> {
+        Serializer { writer }
+    }
+
+    pub(crate) fn flush(&mut self) -> Result<(), SceneFormatError> {
+        Ok(self.writer.flush()?)
+    }
+
+    fn write_length(&mut self, length: Option<usize>) -> Result<(), SceneFormatError> {
+        match length {
+            Some(length) if length <= u32::MAX as usize => {
+                Ok(self.writer.write_u32::<LittleEndian>(length as u32)?)
+            }
+            Some(_) => Err(ser::Error::custom("sequence is too long")),
+            None => Err(ser::Error::custom("sequence length must be known")),
+        }
+    }
+}
+
+impl<W> ser::Serializer for &mut Serializer<W> where W: Write {
+    type Ok = ();
+    type Error = SceneFormatError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, value: bool) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_u8(value as u8)?)
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_i8(value)?)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_i16::<LittleEndian>(value)?)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_i32::<LittleEndian>(value)?)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_i64::<LittleEndian>(value)?)
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_u8(value)?)
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_u16::<LittleEndian>(value)?)
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_u32::<LittleEndian>(value)?)
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_u64::<LittleEndian>(value)?)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_f32::<LittleEndian>(value)?)
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_f64::<LittleEndian>(value)?)
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_u32::<LittleEndian>(value as u32)?)
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), SceneFormatError> {
+        self.serialize_bytes(value.as_bytes())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), SceneFormatError> {
+        self.write_length(Some(value.len()))?;
+        Ok(self.writer.write_all(value)?)
+    }
+
+    fn serialize_none(self) -> Result<(), SceneFormatError> {
+        Ok(self.writer.write_u8(0)?)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), SceneFormatError>
+                         where T: Serialize + ?Sized {
+        self.writer.write_u8(1)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SceneFormatError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), SceneFormatError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, variant_index: u32, _: &'static str)
+                              -> Result<(), SceneFormatError> {
+        self.serialize_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T>(self, _: &'static str, value: &T)
+                                   -> Result<(), SceneFormatError>
+                                   where T: Serialize + ?Sized {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(self,
+                                    _: &'static str,
+                                    variant_index: u32,
+                                    _: &'static str,
+                                    value: &T)
+                                    -> Result<(), SceneFormatError>
+                                    where T: Serialize + ?Sized {
+        self.writer.write_u32::<LittleEndian>(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, length: Option<usize>) -> Result<Self, SceneFormatError> {
+        self.write_length(length)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, SceneFormatError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, SceneFormatError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(self,
+                               _: &'static str,
+                               variant_index: u32,
+                               _: &'static str,
+                               _: usize)
+                               -> Result<Self, SceneFormatError> {
+        self.writer.write_u32::<LittleEndian>(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, length: Option<usize>) -> Result<Self, SceneFormatError> {
+        self.write_length(length)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, SceneFormatError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self,
+                                _: &'static str,
+                                variant_index: u32,
+                                _: &'static str,
+                                _: usize)
+                                -> Result<Self, SceneFormatError> {
+        self.writer.write_u32::<LittleEndian>(variant_index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<W> ser::SerializeSeq for &mut Serializer<W> where W: Write {
+    type Ok = ();
+    type Error = SceneFormatError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SceneFormatError>
+                            where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SceneFormatError> {
+        Ok(())
+    }
+}
+
+impl<W> ser::SerializeTuple for &mut Serializer<W> where W: Write {
+    type Ok = ();
+    type Error = SceneFormatError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SceneFormatError>
+                            where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SceneFormatError> {
+        Ok(())
+    }
+}
+
+impl<W> ser::SerializeTupleStruct for &mut Serializer<W> where W: Write {
+    type Ok = ();
+    type Error = SceneFormatError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SceneFormatError>
+                          where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SceneFormatError> {
+        Ok(())
+    }
+}
+
+impl<W> ser::SerializeTupleVariant for &mut Serializer<W> where W: Write {
+    type Ok = ();
+    type Error = SceneFormatError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SceneFormatError>
+                          where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SceneFormatError> {
+        Ok(())
+    }
+}
+
+impl<W> ser::SerializeMap for &mut Serializer<W> where W: Write {
+    type Ok = ();
+    type Error = SceneFormatError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), SceneFormatError>
+                        where T: Serialize + ?Sized {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), SceneFormatError>
+                          where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SceneFormatError> {
+        Ok(())
+    }
+}
+
+impl<W> ser::SerializeStruct for &mut Serializer<W> where W: Write {
+    type Ok = ();
+    type Error = SceneFormatError;
+
+    fn serialize_field<T>(&mut self, _: &'static str, value: &T) -> Result<(), SceneFormatError>
+                          where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SceneFormatError> {
+        Ok(())
+    }
+}
+
+impl<W> ser::SerializeStructVariant for &mut Serializer<W> where W: Write {
+    type Ok = ();
+    type Error = SceneFormatError;
+
+    fn serialize_field<T>(&mut self, _: &'static str, value: &T) -> Result<(), SceneFormatError>
+                          where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SceneFormatError> {
+        Ok(())
+    }
+}
+
+pub(crate) struct Deserializer<R> where R: Read {
+    reader: R,
+}
+
+impl<R> Deserializer<R> where R: Read {
+    pub(crate) fn new(reader: R) -> Deserializer<R> {
+        Deserializer { reader }
+    }
+
+    pub(crate) fn reader_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    fn read_length(&mut self) -> Result<usize, SceneFormatError> {
+        Ok(self.reader.read_u32::<LittleEndian>()? as usize)
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>, SceneFormatError> {
+        // Read incrementally so that a corrupt length can't cause a huge allocation up front.
+        let length = self.read_length()?;
+        let mut bytes = vec![];
+        (&mut self.reader).take(length as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != length {
+            return Err(de::Error::custom("unexpected end of data"));
+        }
+        Ok(bytes)
+    }
+
+    fn read_string(&mut self) -> Result<String, SceneFormatError> {
+        String::from_utf8(self.read_bytes()?).map_err(|_| de::Error::custom("invalid UTF-8"))
+    }
+}
+
+impl<'de, R> de::Deserializer<'de> for &mut Deserializer<R> where R: Read {
+    type Error = SceneFormatError;
+
+    fn deserialize_any<V>(self, _: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        Err(de::Error::custom("the binary scene format isn't self-describing"))
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                           where V: Visitor<'de> {
+        match self.reader.read_u8()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            _ => Err(de::Error::custom("invalid boolean")),
+        }
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                         where V: Visitor<'de> {
+        visitor.visit_i8(self.reader.read_i8()?)
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        visitor.visit_i16(self.reader.read_i16::<LittleEndian>()?)
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        visitor.visit_i32(self.reader.read_i32::<LittleEndian>()?)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        visitor.visit_i64(self.reader.read_i64::<LittleEndian>()?)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                         where V: Visitor<'de> {
+        visitor.visit_u8(self.reader.read_u8()?)
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        visitor.visit_u16(self.reader.read_u16::<LittleEndian>()?)
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        visitor.visit_u32(self.reader.read_u32::<LittleEndian>()?)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        visitor.visit_u64(self.reader.read_u64::<LittleEndian>()?)
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        visitor.visit_f32(self.reader.read_f32::<LittleEndian>()?)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        visitor.visit_f64(self.reader.read_f64::<LittleEndian>()?)
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                           where V: Visitor<'de> {
+        match std::char::from_u32(self.reader.read_u32::<LittleEndian>()?) {
+            Some(value) => visitor.visit_char(value),
+            None => Err(de::Error::custom("invalid character")),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        visitor.visit_string(self.read_string()?)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                             where V: Visitor<'de> {
+        visitor.visit_string(self.read_string()?)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                            where V: Visitor<'de> {
+        visitor.visit_byte_buf(self.read_bytes()?)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                               where V: Visitor<'de> {
+        visitor.visit_byte_buf(self.read_bytes()?)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                             where V: Visitor<'de> {
+        match self.reader.read_u8()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            _ => Err(de::Error::custom("invalid option")),
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                           where V: Visitor<'de> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _: &'static str, visitor: V)
+                                  -> Result<V::Value, SceneFormatError>
+                                  where V: Visitor<'de> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V)
+                                     -> Result<V::Value, SceneFormatError>
+                                     where V: Visitor<'de> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        let length = self.read_length()?;
+        visitor.visit_seq(Access { deserializer: self, remaining: length })
+    }
+
+    fn deserialize_tuple<V>(self, length: usize, visitor: V) -> Result<V::Value, SceneFormatError>
+                            where V: Visitor<'de> {
+        visitor.visit_seq(Access { deserializer: self, remaining: length })
+    }
+
+    fn deserialize_tuple_struct<V>(self, _: &'static str, length: usize, visitor: V)
+                                   -> Result<V::Value, SceneFormatError>
+                                   where V: Visitor<'de> {
+        visitor.visit_seq(Access { deserializer: self, remaining: length })
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, SceneFormatError>
+                          where V: Visitor<'de> {
+        let length = self.read_length()?;
+        visitor.visit_map(Access { deserializer: self, remaining: length })
+    }
+
+    fn deserialize_struct<V>(self,
+                             _: &'static str,
+                             fields: &'static [&'static str],
+                             visitor: V)
+                             -> Result<V::Value, SceneFormatError>
+                             where V: Visitor<'de> {
+        visitor.visit_seq(Access { deserializer: self, remaining: fields.len() })
+    }
+
+    fn deserialize_enum<V>(self,
+                           _: &'static str,
+                           _: &'static [&'static str],
+                           visitor: V)
+                           -> Result<V::Value, SceneFormatError>
+                           where V: Visitor<'de> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V>(self, _: V) -> Result<V::Value, SceneFormatError>
+                                 where V: Visitor<'de> {
+        Err(de::Error::custom("the binary scene format has no identifiers"))
+    }
+
+    fn deserialize_ignored_any<V>(self, _: V) -> Result<V::Value, SceneFormatError>
+                                  where V: Visitor<'de> {
+        Err(de::Error::custom("the binary scene format can't skip values"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+// Provides the elements of sequences, tuples, structs, and maps.
+struct Access<'a, R> where R: Read {
+    deserializer: &'a mut Deserializer<R>,
+    remaining: usize,
+}
+
+impl<'de, 'a, R> de::SeqAccess<'de> for Access<'a, R> where R: Read {
+    type Error = SceneFormatError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, SceneFormatError>
+                            where T: DeserializeSeed<'de> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de, 'a, R> de::MapAccess<'de> for Access<'a, R> where R: Read {
+    type Error = SceneFormatError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, SceneFormatError>
+                        where K: DeserializeSeed<'de> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, SceneFormatError>
+                          where V: DeserializeSeed<'de> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de, R> de::EnumAccess<'de> for &mut Deserializer<R> where R: Read {
+    type Error = SceneFormatError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), SceneFormatError>
+                       where V: DeserializeSeed<'de> {
+        let variant_index = self.reader.read_u32::<LittleEndian>()?;
+        let variant_deserializer: U32Deserializer<SceneFormatError> =
+            variant_index.into_deserializer();
+        let variant = seed.deserialize(variant_deserializer)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, R> de::VariantAccess<'de> for &mut Deserializer<R> where R: Read {
+    type Error = SceneFormatError;
+
+    fn unit_variant(self) -> Result<(), SceneFormatError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, SceneFormatError>
+                               where T: DeserializeSeed<'de> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, length: usize, visitor: V) -> Result<V::Value, SceneFormatError>
+                        where V: Visitor<'de> {
+        visitor.visit_seq(Access { deserializer: self, remaining: length })
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V)
+                         -> Result<V::Value, SceneFormatError>
+                         where V: Visitor<'de> {
+        visitor.visit_seq(Access { deserializer: self, remaining: fields.len() })
+    }
+}
@@ -0,0 +1,294 @@
+// pathfinder/renderer/src/serialization/mod.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Saving and loading scenes and render commands, enabled with the `serde` feature.
+//!
+//! Scenes can be stored in a compact binary format or as JSON. Both begin with a format version,
+//! and loading fails with `SceneFormatError::UnsupportedVersion` if it doesn't match
+//! `SCENE_FORMAT_VERSION`.
+
+use crate::paint::Paint;
+use crate::scene::{GroupId, GroupNode, PathObject, Scene, SceneGroup};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use pathfinder_geometry::rect::RectF;
+use serde::de::Error as DeserializeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read, Write};
+
+pub mod recording;
+
+mod binary;
+
+/// The version of the scene format written by this library.
+///
+/// This must be incremented whenever the serialized representation of a scene changes.
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+// The first four bytes of every scene in the binary format.
+const SCENE_BINARY_MAGIC: [u8; 4] = *b"PFSC";
+
+#[derive(Debug)]
+pub enum SceneFormatError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The data doesn't begin with the expected signature.
+    BadSignature,
+    /// The data was written with a different version of the format.
+    UnsupportedVersion(u32),
+    /// The data is malformed.
+    Invalid(String),
+}
+
+impl Scene {
+    pub fn write_binary<W>(&self, mut writer: W) -> Result<(), SceneFormatError> where W: Write {
+        writer.write_all(&SCENE_BINARY_MAGIC)?;
+        writer.write_u32::<LittleEndian>(SCENE_FORMAT_VERSION)?;
+        self.serialize(&mut binary::Serializer::new(writer))
+    }
+
+    pub fn read_binary<R>(mut reader: R) -> Result<Scene, SceneFormatError> where R: Read {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != SCENE_BINARY_MAGIC {
+            return Err(SceneFormatError::BadSignature);
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != SCENE_FORMAT_VERSION {
+            return Err(SceneFormatError::UnsupportedVersion(version));
+        }
+        Scene::deserialize(&mut binary::Deserializer::new(reader))
+    }
+
+    /// Returns a human-readable representation of the scene, useful for debugging.
+    pub fn to_json(&self) -> Result<String, SceneFormatError> {
+        let json = SerializedJSONScene { version: SCENE_FORMAT_VERSION, scene: self };
+        Ok(serde_json::to_string_pretty(&json)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Scene, SceneFormatError> {
+        // Check the version first, since the rest of the scene may not parse otherwise.
+        let version: SerializedJSONSceneVersion = serde_json::from_str(json)?;
+        if version.version != SCENE_FORMAT_VERSION {
+            return Err(SceneFormatError::UnsupportedVersion(version.version));
+        }
+        let json: SerializedJSONScene<Scene> = serde_json::from_str(json)?;
+        Ok(json.scene)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedJSONScene<S> {
+    version: u32,
+    scene: S,
+}
+
+#[derive(Deserialize)]
+struct SerializedJSONSceneVersion {
+    version: u32,
+}
+
+#[derive(Serialize)]
+struct SerializedSceneRef<'a> {
+    view_box: RectF,
+    bounds: RectF,
+    paints: &'a [Paint],
+    groups: Vec<SerializedGroup<&'a SceneGroup>>,
+    paths: &'a [PathObject],
+    path_groups: Vec<Option<u32>>,
+}
+
+#[derive(Deserialize)]
+struct SerializedScene {
+    view_box: RectF,
+    bounds: RectF,
+    paints: Vec<Paint>,
+    groups: Vec<SerializedGroup<SceneGroup>>,
+    paths: Vec<PathObject>,
+    path_groups: Vec<Option<u32>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedGroup<G> {
+    group: G,
+    parent: Option<u32>,
+}
+
+impl Serialize for Scene {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        SerializedSceneRef {
+            view_box: self.view_box(),
+            bounds: self.bounds(),
+            paints: &self.paints,
+            groups: self.groups.iter().map(|node| {
+                SerializedGroup {
+                    group: &node.group,
+                    parent: node.parent.map(|parent| parent.0),
+                }
+            }).collect(),
+            paths: &self.paths,
+            path_groups: self.path_groups.iter().map(|group| group.map(|group| group.0)).collect(),
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Scene {
+    fn deserialize<D>(deserializer: D) -> Result<Scene, D::Error> where D: Deserializer<'de> {
+        let serialized = SerializedScene::deserialize(deserializer)?;
+
+        let mut scene = Scene::new();
+        scene.set_view_box(serialized.view_box);
+        for (paint_index, paint) in serialized.paints.iter().enumerate() {
+            match scene.try_push_paint(paint) {
+                Some(paint_id) if paint_id.0 as usize == paint_index => {}
+                Some(_) => return Err(D::Error::custom("scene has duplicate paints")),
+                None => return Err(D::Error::custom("scene's paints don't fit in its textures")),
+            }
+        }
+        for (group_index, group) in serialized.groups.into_iter().enumerate() {
+            // Parents precede their children.
+            if let Some(parent) = group.parent {
+                if parent as usize >= group_index {
+                    return Err(D::Error::custom("group refers to an invalid parent"));
+                }
+            }
+            scene.groups.push(GroupNode::new(group.group, group.parent.map(GroupId)));
+        }
+        if serialized.path_groups.len() != serialized.paths.len() {
+            return Err(D::Error::custom("wrong number of path groups"));
+        }
+        for (path, group) in serialized.paths.into_iter().zip(serialized.path_groups) {
+            if path.paint().0 as usize >= scene.paints.len() {
+                return Err(D::Error::custom("path refers to a nonexistent paint"));
+            }
+            if let Some(group) = group {
+                if group as usize >= scene.groups.len() {
+                    return Err(D::Error::custom("path refers to a nonexistent group"));
+                }
+            }
+            scene.push_path(path);
+            *scene.path_groups.last_mut().unwrap() = group.map(GroupId);
+        }
+        scene.set_bounds(serialized.bounds);
+        Ok(scene)
+    }
+}
+
+impl Display for SceneFormatError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            SceneFormatError::Io(ref error) => write!(formatter, "I/O error: {}", error),
+            SceneFormatError::Json(ref error) => write!(formatter, "JSON error: {}", error),
+            SceneFormatError::BadSignature => formatter.write_str("unrecognized data signature"),
+            SceneFormatError::UnsupportedVersion(version) => {
+                write!(formatter, "unsupported format version {}", version)
+            }
+            SceneFormatError::Invalid(ref message) => {
+                write!(formatter, "invalid data: {}", message)
+            }
+        }
+    }
+}
+
+impl Error for SceneFormatError {}
+
+impl From<io::Error> for SceneFormatError {
+    fn from(error: io::Error) -> SceneFormatError {
+        SceneFormatError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SceneFormatError {
+    fn from(error: serde_json::Error) -> SceneFormatError {
+        SceneFormatError::Json(error)
+    }
+}
+
+impl serde::ser::Error for SceneFormatError {
+    fn custom<T>(message: T) -> SceneFormatError where T: Display {
+        SceneFormatError::Invalid(message.to_string())
+    }
+}
+
+impl serde::de::Error for SceneFormatError {
+    fn custom<T>(message: T) -> SceneFormatError where T: Display {
+        SceneFormatError::Invalid(message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SceneFormatError;
+    use crate::paint::Paint;
+    use crate::scene::{PathObject, Scene, SceneGroup};
+    use pathfinder_content::color::ColorU;
+    use pathfinder_content::effects::BlendMode;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::gradient::Gradient;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+
+    #[test]
+    fn test_round_trip() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::new(100.0, 50.0)));
+        let mut gradient = Gradient::radial(LineSegment2F::new(Vector2F::splat(10.0),
+                                                               Vector2F::splat(20.0)),
+                                            1.0,
+                                            15.0);
+        gradient.add_color_stop(0.0, ColorU::black());
+        gradient.add_color_stop(1.0, ColorU { r: 255, g: 128, b: 0, a: 255 });
+        let color = scene.push_paint(&Paint::Color(ColorU { r: 1, g: 2, b: 3, a: 4 }));
+        let gradient = scene.push_paint(&Paint::Gradient(gradient));
+
+        let mut contour = Contour::new();
+        contour.push_endpoint(Vector2F::new(1.0, 2.0));
+        contour.push_quadratic(Vector2F::new(30.0, 0.0), Vector2F::new(40.0, 45.0));
+        contour.push_cubic(Vector2F::new(20.0, 30.0),
+                           Vector2F::new(5.0, 30.0),
+                           Vector2F::new(2.0, 40.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        scene.push_path(PathObject::new(outline.clone(), color, "first".to_owned()));
+        let mut group = SceneGroup::new();
+        group.set_transform(Transform2F::from_scale(Vector2F::new(0.5, 1.0)));
+        group.set_opacity(0.5);
+        group.set_blur(2.0);
+        group.set_clip_path(Some((outline.clone(), FillRule::Winding)));
+        scene.push_group(group);
+        let mut path = PathObject::new(outline, gradient, "second".to_owned());
+        path.set_fill_rule(FillRule::EvenOdd);
+        path.set_blend_mode(BlendMode::Multiply);
+        scene.push_path(path);
+        scene.pop_group();
+
+        let mut binary = vec![];
+        scene.write_binary(&mut binary).unwrap();
+        let from_binary = Scene::read_binary(&binary[..]).unwrap();
+        let from_json = Scene::from_json(&scene.to_json().unwrap()).unwrap();
+        for loaded_scene in &[from_binary, from_json] {
+            assert_eq!(loaded_scene.to_json().unwrap(), scene.to_json().unwrap());
+            assert_eq!(loaded_scene.bounds(), scene.bounds());
+            assert_eq!(loaded_scene.paths[1].outline().bounds(),
+                       scene.paths[1].outline().bounds());
+        }
+
+        binary[4] += 1;
+        match Scene::read_binary(&binary[..]) {
+            Err(SceneFormatError::UnsupportedVersion(2)) => {}
+            _ => panic!("expected an unsupported version error"),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+// pathfinder/renderer/src/serialization/recording.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Recording of render command streams, so that they can be replayed without building the scene.
+//!
+//! A recording is a header followed by the commands in the binary format. It may hold any number
+//! of frames, each running from `Start` to `Finish`.
+
+use crate::gpu::renderer::Renderer;
+use crate::gpu_data::RenderCommand;
+use crate::options::RenderCommandListener;
+use super::{SceneFormatError, binary};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use pathfinder_gpu::Device;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::sync::Mutex;
+
+/// The version of the render command format written by this library.
+///
+/// This must be incremented whenever the serialized representation of a render command changes.
+pub const RENDER_COMMAND_FORMAT_VERSION: u32 = 1;
+
+// The first four bytes of every render command recording.
+const RENDER_COMMAND_MAGIC: [u8; 4] = *b"PFRC";
+
+/// A listener that writes every render command it receives, optionally passing each one on to
+/// another listener afterward.
+///
+/// Output is buffered and flushed at the end of each frame. Listeners can't report errors, so if
+/// writing fails, a warning is logged and recording stops.
+pub struct RenderCommandRecorder<W> where W: Write + Send {
+    serializer: Mutex<Option<binary::Serializer<BufWriter<W>>>>,
+    next_listener: Option<Box<dyn RenderCommandListener>>,
+}
+
+impl<W> RenderCommandRecorder<W> where W: Write + Send {
+    pub fn new(writer: W) -> Result<RenderCommandRecorder<W>, SceneFormatError> {
+        RenderCommandRecorder::with_listener(writer, None)
+    }
+
+    pub fn with_listener(writer: W, next_listener: Option<Box<dyn RenderCommandListener>>)
+                         -> Result<RenderCommandRecorder<W>, SceneFormatError> {
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(&RENDER_COMMAND_MAGIC)?;
+        writer.write_u32::<LittleEndian>(RENDER_COMMAND_FORMAT_VERSION)?;
+        Ok(RenderCommandRecorder {
+            serializer: Mutex::new(Some(binary::Serializer::new(writer))),
+            next_listener,
+        })
+    }
+}
+
+impl<W> RenderCommandListener for RenderCommandRecorder<W> where W: Write + Send {
+    fn send(&self, command: RenderCommand) {
+        {
+            let mut serializer = self.serializer.lock().unwrap();
+            if let Some(Err(error)) = serializer.as_mut().map(|serializer| {
+                record_command(serializer, &command)
+            }) {
+                warn!("Failed to record render command, stopping recording: {}", error);
+                *serializer = None;
+            }
+        }
+
+        if let Some(ref next_listener) = self.next_listener {
+            next_listener.send(command);
+        }
+    }
+}
+
+fn record_command<W>(serializer: &mut binary::Serializer<W>, command: &RenderCommand)
+                     -> Result<(), SceneFormatError> where W: Write {
+    command.serialize(&mut *serializer)?;
+    // Flush at the end of each frame so that complete frames survive a crash.
+    if let RenderCommand::Finish { .. } = *command {
+        serializer.flush()?;
+    }
+    Ok(())
+}
+
+/// Reads the commands from a recording, in order.
+pub struct RenderCommandReader<R> where R: Read {
+    deserializer: binary::Deserializer<BufReader<R>>,
+}
+
+impl<R> RenderCommandReader<R> where R: Read {
+    pub fn new(mut reader: R) -> Result<RenderCommandReader<R>, SceneFormatError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != RENDER_COMMAND_MAGIC {
+            return Err(SceneFormatError::BadSignature);
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != RENDER_COMMAND_FORMAT_VERSION {
+            return Err(SceneFormatError::UnsupportedVersion(version));
+        }
+        Ok(RenderCommandReader {
+            deserializer: binary::Deserializer::new(BufReader::new(reader)),
+        })
+    }
+
+    /// Sends the commands of the next frame to the given renderer. Returns false if the
+    /// recording has no more frames.
+    pub fn replay_frame<D>(&mut self, renderer: &mut Renderer<D>) -> Result<bool, SceneFormatError>
+                           where D: Device {
+        let mut began = false;
+        for command in self.by_ref() {
+            let command = command?;
+            if !began {
+                renderer.begin_scene();
+                began = true;
+            }
+            renderer.render_command(&command);
+            if let RenderCommand::Finish { .. } = command {
+                break;
+            }
+        }
+        if began {
+            renderer.end_scene();
+        }
+        Ok(began)
+    }
+}
+
+impl<R> Iterator for RenderCommandReader<R> where R: Read {
+    type Item = Result<RenderCommand, SceneFormatError>;
+
+    fn next(&mut self) -> Option<Result<RenderCommand, SceneFormatError>> {
+        match self.deserializer.reader_mut().fill_buf() {
+            Ok([]) => return None,
+            Ok(_) => {}
+            Err(error) => return Some(Err(error.into())),
+        }
+        Some(RenderCommand::deserialize(&mut self.deserializer))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RenderCommandReader, RenderCommandRecorder};
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::cpu::renderer::Renderer as CPURenderer;
+    use crate::gpu::options::RendererOptions;
+    use crate::options::{BuildOptions, RasterizeOptions};
+    use crate::paint::Paint;
+    use crate::scene::{PathObject, Scene};
+    use pathfinder_content::color::ColorU;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(data)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(40.0)));
+        let paint = scene.push_paint(&Paint::Color(ColorU { r: 0, g: 128, b: 255, a: 192 }));
+        let mut outline = Outline::new();
+        let mut contour = Contour::new();
+        contour.push_ellipse(&Transform2F::from_scale(Vector2F::new(15.0, 10.0)).translate(
+            Vector2F::splat(20.0)));
+        outline.push_contour(contour);
+        scene.push_path(PathObject::new(outline, paint, String::new()));
+
+        let buffer = Arc::new(Mutex::new(vec![]));
+        let recorder = RenderCommandRecorder::new(SharedBuffer(buffer.clone())).unwrap();
+        scene.build(BuildOptions::default(), Box::new(recorder), &SequentialExecutor);
+
+        let size = Vector2I::splat(40);
+        let mut renderer = CPURenderer::new(size, RendererOptions::default());
+        let recording = buffer.lock().unwrap();
+        for command in RenderCommandReader::new(&recording[..]).unwrap() {
+            renderer.render_command(&command.unwrap());
+        }
+        assert_eq!(renderer.pixels(),
+                   scene.rasterize_to_pixels(size, RasterizeOptions::default()));
+    }
+}
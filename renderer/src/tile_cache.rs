@@ -0,0 +1,191 @@
+// pathfinder/renderer/src/tile_cache.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Retains tiled paths between builds, so that unchanged paths needn't be tiled again.
+
+use crate::gpu_data::BuiltObject;
+use crate::options::{PreparedBuildOptions, PreparedRenderTransform};
+use hashbrown::HashMap;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use std::sync::{Arc, Mutex};
+
+/// Tiled paths from the previous build, for use with `Scene::build_with_cache()`.
+///
+/// Paths are looked up by the identity of their outline and the state of their groups, so a path
+/// is reused as long as neither it nor its groups have been replaced and the build options that
+/// affect tiling (the transform, dilation, subpixel antialiasing, and tile size) and the view box
+/// haven't changed. Paths that aren't in the most recent build are evicted. Nothing is cached for
+/// builds with a perspective transform.
+pub struct TileCache {
+    entries: HashMap<PathCacheId, CachedObject>,
+}
+
+/// The outline ID of a path and the state ID of its innermost group.
+pub(crate) type PathCacheId = (u64, Option<u64>);
+
+#[derive(Clone)]
+pub(crate) struct CachedObject {
+    key: TileCacheKey,
+    built_object: Arc<BuiltObject>,
+}
+
+/// The state that tiling depends on, aside from the outline itself.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct TileCacheKey {
+    transform: Transform2F,
+    view_box: RectF,
+    dilation: Vector2F,
+    subpixel_aa_enabled: bool,
+    tile_size: Vector2I,
+}
+
+impl TileCache {
+    #[inline]
+    pub fn new() -> TileCache {
+        TileCache { entries: HashMap::new() }
+    }
+
+    /// Returns the number of paths in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+
+    pub(crate) fn get(&self, path_cache_id: PathCacheId, key: &TileCacheKey)
+                      -> Option<Arc<BuiltObject>> {
+        match self.entries.get(&path_cache_id) {
+            Some(entry) if entry.key == *key => Some(entry.built_object.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn replace_entries(&mut self, entries: Vec<(PathCacheId, CachedObject)>) {
+        self.entries.clear();
+        self.entries.extend(entries);
+    }
+}
+
+impl Default for TileCache {
+    #[inline]
+    fn default() -> TileCache {
+        TileCache::new()
+    }
+}
+
+impl CachedObject {
+    #[inline]
+    pub(crate) fn new(key: TileCacheKey, built_object: Arc<BuiltObject>) -> CachedObject {
+        CachedObject { key, built_object }
+    }
+}
+
+impl TileCacheKey {
+    pub(crate) fn new(options: &PreparedBuildOptions, view_box: RectF) -> Option<TileCacheKey> {
+        let transform = match options.transform {
+            PreparedRenderTransform::None => Transform2F::default(),
+            PreparedRenderTransform::Transform2D(transform) => transform,
+            PreparedRenderTransform::Perspective { .. } => return None,
+        };
+        Some(TileCacheKey {
+            transform,
+            view_box,
+            dilation: options.dilation,
+            subpixel_aa_enabled: options.subpixel_aa_enabled,
+            tile_size: options.tile_size,
+        })
+    }
+}
+
+/// The objects used by a build, which become the contents of the cache afterward.
+pub(crate) type UsedCacheEntries = Mutex<Vec<(PathCacheId, CachedObject)>>;
+
+#[cfg(test)]
+mod test {
+    use super::TileCache;
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::cpu::renderer::Renderer as CPURenderer;
+    use crate::gpu::options::RendererOptions;
+    use crate::gpu_data::RenderCommand;
+    use crate::options::{BuildOptions, RenderTransform};
+    use crate::paint::Paint;
+    use crate::scene::{PathObject, Scene};
+    use pathfinder_content::color::ColorU;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use std::sync::{Arc, Mutex};
+
+    fn render(scene: &Scene, transform: Transform2F, cache: Option<&mut TileCache>) -> Vec<u8> {
+        let options = BuildOptions {
+            transform: RenderTransform::Transform2D(transform),
+            ..BuildOptions::default()
+        };
+        let commands = Arc::new(Mutex::new(vec![]));
+        let listener_commands = commands.clone();
+        let listener = Box::new(move |command: RenderCommand| {
+            listener_commands.lock().unwrap().push(command)
+        });
+        match cache {
+            Some(cache) => scene.build_with_cache(options, listener, &SequentialExecutor, cache),
+            None => scene.build(options, listener, &SequentialExecutor),
+        }
+
+        let mut renderer = CPURenderer::new(Vector2I::splat(64), RendererOptions::default());
+        for command in commands.lock().unwrap().iter() {
+            renderer.render_command(command);
+        }
+        renderer.pixels()
+    }
+
+    #[test]
+    fn test_reuse_tiles() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(64.0)));
+        for (index, &radius) in [20.0, 12.0, 5.0].iter().enumerate() {
+            let paint = scene.push_paint(&Paint::Color(ColorU {
+                r: 80 * index as u8,
+                g: 255,
+                b: 0,
+                a: 160,
+            }));
+            let mut contour = Contour::new();
+            contour.push_ellipse(&Transform2F::from_uniform_scale(radius).translate(
+                Vector2F::new(24.0 + 4.0 * index as f32, 28.0)));
+            let mut outline = Outline::new();
+            outline.push_contour(contour);
+            scene.push_path(PathObject::new(outline, paint, String::new()));
+        }
+
+        let mut cache = TileCache::new();
+        let transforms = [
+            Transform2F::default(),
+            Transform2F::default(),
+            Transform2F::from_translation(Vector2F::new(3.5, -2.0)),
+        ];
+        for &transform in &transforms {
+            assert_eq!(render(&scene, transform, Some(&mut cache)),
+                       render(&scene, transform, None));
+            assert_eq!(cache.len(), 3);
+        }
+    }
+}
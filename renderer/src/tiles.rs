@@ -8,13 +8,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::builder::SceneBuilder;
 use crate::gpu_data::{AlphaTileBatchPrimitive, BuiltObject, TileObjectPrimitive};
-use crate::paint::{self, PaintId};
 use crate::sorted_vector::SortedVector;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::{Contour, Outline, PointIndex};
 use pathfinder_content::segment::Segment;
 use std::cmp::Ordering;
@@ -23,16 +22,18 @@ use std::mem;
 // TODO(pcwalton): Make this configurable.
 const FLATTENING_TOLERANCE: f32 = 0.1;
 
+/// The default tile size.
 pub const TILE_WIDTH: u32 = 16;
 pub const TILE_HEIGHT: u32 = 16;
 
+/// The range of tile dimensions that `BuildOptions::tile_size` accepts. The GPU renderer's mask
+/// framebuffer is 256 tiles on a side, so large tiles need a lot of memory.
+pub const MIN_TILE_SIZE: i32 = 4;
+pub const MAX_TILE_SIZE: i32 = 32;
+
 pub(crate) struct Tiler<'a> {
-    builder: &'a SceneBuilder<'a>,
     outline: &'a Outline,
     pub built_object: BuiltObject,
-    paint_id: PaintId,
-    object_index: u16,
-    object_is_opaque: bool,
 
     point_queue: SortedVector<QueuedEndpoint>,
     active_edges: SortedVector<ActiveEdge>,
@@ -41,27 +42,16 @@ pub(crate) struct Tiler<'a> {
 
 impl<'a> Tiler<'a> {
     #[allow(clippy::or_fun_call)]
-    pub(crate) fn new(
-        builder: &'a SceneBuilder<'a>,
-        outline: &'a Outline,
-        view_box: RectF,
-        object_index: u16,
-        paint_id: PaintId,
-        object_is_opaque: bool,
-    ) -> Tiler<'a> {
+    pub(crate) fn new(outline: &'a Outline, view_box: RectF, tile_size: Vector2I) -> Tiler<'a> {
         let bounds = outline
             .bounds()
             .intersection(view_box)
             .unwrap_or(RectF::default());
-        let built_object = BuiltObject::new(bounds);
+        let built_object = BuiltObject::new(bounds, tile_size);
 
         Tiler {
-            builder,
             outline,
             built_object,
-            object_index,
-            paint_id,
-            object_is_opaque,
 
             point_queue: SortedVector::new(),
             active_edges: SortedVector::new(),
@@ -83,9 +73,6 @@ impl<'a> Tiler<'a> {
             self.generate_strip(strip_origin_y);
         }
 
-        // Pack and cull.
-        self.pack_and_cull();
-
         // Done!
         debug!("{:#?}", self.built_object);
     }
@@ -95,7 +82,8 @@ impl<'a> Tiler<'a> {
         self.process_old_active_edges(strip_origin_y);
 
         // Add new active edges.
-        let strip_max_y = ((i32::from(strip_origin_y) + 1) * TILE_HEIGHT as i32) as f32;
+        let tile_size = self.built_object.tile_size;
+        let strip_max_y = ((i32::from(strip_origin_y) + 1) * tile_size.y()) as f32;
         while let Some(queued_endpoint) = self.point_queue.peek() {
             // We're done when we see an endpoint that belongs to the next tile strip.
             //
@@ -109,39 +97,6 @@ impl<'a> Tiler<'a> {
         }
     }
 
-    fn pack_and_cull(&mut self) {
-        for (tile_index, tile) in self.built_object.tiles.data.iter().enumerate() {
-            let tile_coords = self
-                .built_object
-                .local_tile_index_to_coords(tile_index as u32);
-
-            if tile.is_solid() {
-                // Blank tiles are always skipped.
-                if tile.backdrop == 0 {
-                    continue;
-                }
-
-                // If this is a solid tile, poke it into the Z-buffer and stop here.
-                if self.object_is_opaque {
-                    self.builder.z_buffer.update(tile_coords, self.object_index);
-                    continue;
-                }
-            }
-
-            let origin_uv = paint::paint_id_to_tex_coords(self.paint_id);
-
-            let alpha_tile = AlphaTileBatchPrimitive::new(
-                tile_coords,
-                tile.backdrop,
-                self.object_index,
-                tile.alpha_tile_index as u16,
-                origin_uv,
-            );
-
-            self.built_object.alpha_tiles.push(alpha_tile);
-        }
-    }
-
     fn process_old_active_edges(&mut self, tile_y: i32) {
         let mut current_tile_x = self.built_object.tile_rect().min_x();
         let mut current_subtile_x = 0.0;
@@ -153,7 +108,8 @@ impl<'a> Tiler<'a> {
         // FIXME(pcwalton): Yuck.
         let mut last_segment_x = -9999.0;
 
-        let tile_top = (i32::from(tile_y) * TILE_HEIGHT as i32) as f32;
+        let tile_size = self.built_object.tile_size;
+        let tile_top = (i32::from(tile_y) * tile_size.y()) as f32;
 
         debug!("---------- tile y {}({}) ----------", tile_y, tile_top);
         debug!("old active edges: {:#?}", self.old_active_edges);
@@ -189,14 +145,13 @@ impl<'a> Tiler<'a> {
             last_segment_x = segment_x;
 
             // Do initial subtile fill, if necessary.
-            let segment_tile_x = f32::floor(segment_x) as i32 / TILE_WIDTH as i32;
+            let segment_tile_x = f32::floor(segment_x) as i32 / tile_size.x();
             if current_tile_x < segment_tile_x && current_subtile_x > 0.0 {
                 let current_x =
-                    (i32::from(current_tile_x) * TILE_WIDTH as i32) as f32 + current_subtile_x;
-                let tile_right_x = ((i32::from(current_tile_x) + 1) * TILE_WIDTH as i32) as f32;
+                    (i32::from(current_tile_x) * tile_size.x()) as f32 + current_subtile_x;
+                let tile_right_x = ((i32::from(current_tile_x) + 1) * tile_size.x()) as f32;
                 let current_tile_coords = Vector2I::new(current_tile_x, tile_y);
                 self.built_object.add_active_fill(
-                    self.builder,
                     current_x,
                     tile_right_x,
                     current_winding,
@@ -229,13 +184,12 @@ impl<'a> Tiler<'a> {
             // Do final subtile fill, if necessary.
             debug_assert_eq!(current_tile_x, segment_tile_x);
             let segment_subtile_x =
-                segment_x - (i32::from(current_tile_x) * TILE_WIDTH as i32) as f32;
+                segment_x - (i32::from(current_tile_x) * tile_size.x()) as f32;
             if segment_subtile_x > current_subtile_x {
                 let current_x =
-                    (i32::from(current_tile_x) * TILE_WIDTH as i32) as f32 + current_subtile_x;
+                    (i32::from(current_tile_x) * tile_size.x()) as f32 + current_subtile_x;
                 let current_tile_coords = Vector2I::new(current_tile_x, tile_y);
                 self.built_object.add_active_fill(
-                    self.builder,
                     current_x,
                     segment_x,
                     current_winding,
@@ -250,7 +204,7 @@ impl<'a> Tiler<'a> {
             // Process the edge.
             debug!("about to process existing active edge {:#?}", active_edge);
             debug_assert!(f32::abs(active_edge.crossing.y() - tile_top) < 0.1);
-            active_edge.process(self.builder, &mut self.built_object, tile_y);
+            active_edge.process(&mut self.built_object, tile_y);
             if !active_edge.segment.is_none() {
                 self.active_edges.push(active_edge);
             }
@@ -286,7 +240,6 @@ impl<'a> Tiler<'a> {
                 contour,
                 prev_endpoint_index,
                 &mut self.active_edges,
-                self.builder,
                 &mut self.built_object,
                 tile_y,
             );
@@ -310,7 +263,6 @@ impl<'a> Tiler<'a> {
                 contour,
                 point_index.point(),
                 &mut self.active_edges,
-                self.builder,
                 &mut self.built_object,
                 tile_y,
             );
@@ -354,10 +306,10 @@ impl<'a> Tiler<'a> {
     }
 }
 
-pub fn round_rect_out_to_tile_bounds(rect: RectF) -> RectI {
+pub fn round_rect_out_to_tile_bounds(rect: RectF, tile_size: Vector2I) -> RectI {
     rect.scale_xy(Vector2F::new(
-        1.0 / TILE_WIDTH as f32,
-        1.0 / TILE_HEIGHT as f32,
+        1.0 / tile_size.x() as f32,
+        1.0 / tile_size.y() as f32,
     ))
     .round_out()
     .to_i32()
@@ -367,13 +319,12 @@ fn process_active_segment(
     contour: &Contour,
     from_endpoint_index: u32,
     active_edges: &mut SortedVector<ActiveEdge>,
-    builder: &SceneBuilder,
     built_object: &mut BuiltObject,
     tile_y: i32,
 ) {
     let mut active_edge = ActiveEdge::from_segment(&contour.segment_after(from_endpoint_index));
     debug!("... process_active_segment({:#?})", active_edge);
-    active_edge.process(builder, built_object, tile_y);
+    active_edge.process(built_object, tile_y);
     if !active_edge.segment.is_none() {
         debug!("... ... pushing resulting active edge: {:#?}", active_edge);
         active_edges.push(active_edge);
@@ -420,8 +371,8 @@ impl ActiveEdge {
         ActiveEdge { segment: *segment, crossing }
     }
 
-    fn process(&mut self, builder: &SceneBuilder, built_object: &mut BuiltObject, tile_y: i32) {
-        let tile_bottom = ((i32::from(tile_y) + 1) * TILE_HEIGHT as i32) as f32;
+    fn process(&mut self, built_object: &mut BuiltObject, tile_y: i32) {
+        let tile_bottom = ((i32::from(tile_y) + 1) * built_object.tile_size.y()) as f32;
         debug!(
             "process_active_edge({:#?}, tile_y={}({}))",
             self, tile_y, tile_bottom
@@ -433,7 +384,7 @@ impl ActiveEdge {
         if segment.is_line() {
             let line_segment = segment.as_line_segment();
             self.segment =
-                match self.process_line_segment(line_segment, builder, built_object, tile_y) {
+                match self.process_line_segment(line_segment, built_object, tile_y) {
                     Some(lower_part) => Segment::line(lower_part),
                     None => Segment::none(),
                 };
@@ -450,7 +401,7 @@ impl ActiveEdge {
             let first_line_segment =
                 LineSegment2F::new(self.crossing, segment.baseline.upper_point()).orient(winding);
             if self
-                .process_line_segment(first_line_segment, builder, built_object, tile_y)
+                .process_line_segment(first_line_segment, built_object, tile_y)
                 .is_some()
             {
                 return;
@@ -481,7 +432,7 @@ impl ActiveEdge {
             );
 
             let line = before_segment.baseline.orient(winding);
-            match self.process_line_segment(line, builder, built_object, tile_y) {
+            match self.process_line_segment(line, built_object, tile_y) {
                 Some(lower_part) if split_t == 1.0 => {
                     self.segment = Segment::line(lower_part);
                     return;
@@ -502,23 +453,22 @@ impl ActiveEdge {
     fn process_line_segment(
         &mut self,
         line_segment: LineSegment2F,
-        builder: &SceneBuilder,
         built_object: &mut BuiltObject,
         tile_y: i32,
     ) -> Option<LineSegment2F> {
-        let tile_bottom = ((i32::from(tile_y) + 1) * TILE_HEIGHT as i32) as f32;
+        let tile_bottom = ((i32::from(tile_y) + 1) * built_object.tile_size.y()) as f32;
         debug!(
             "process_line_segment({:?}, tile_y={}) tile_bottom={}",
             line_segment, tile_y, tile_bottom
         );
 
         if line_segment.max_y() <= tile_bottom {
-            built_object.generate_fill_primitives_for_line(builder, line_segment, tile_y);
+            built_object.generate_fill_primitives_for_line(line_segment, tile_y);
             return None;
         }
 
         let (upper_part, lower_part) = line_segment.split_at_y(tile_bottom);
-        built_object.generate_fill_primitives_for_line(builder, upper_part, tile_y);
+        built_object.generate_fill_primitives_for_line(upper_part, tile_y);
         self.crossing = lower_part.upper_point();
         Some(lower_part)
     }
@@ -532,11 +482,13 @@ impl PartialOrd<ActiveEdge> for ActiveEdge {
 
 impl AlphaTileBatchPrimitive {
     #[inline]
-    fn new(tile_coords: Vector2I,
+    pub(crate) fn new(tile_coords: Vector2I,
            backdrop: i8,
            object_index: u16,
            tile_index: u16,
-           origin_uv: Vector2I)
+           paint_texel: Vector2I,
+           paint_kind: u8,
+           fill_rule: FillRule)
            -> AlphaTileBatchPrimitive {
         AlphaTileBatchPrimitive {
             tile_x_lo: (tile_coords.x() & 0xff) as u8,
@@ -545,8 +497,14 @@ impl AlphaTileBatchPrimitive {
             backdrop,
             object_index,
             tile_index,
-            origin_u: origin_uv.x() as u16,
-            origin_v: origin_uv.y() as u16,
+            paint_x: paint_texel.x() as u16,
+            paint_y: paint_texel.y() as u16,
+            fill_rule: match fill_rule {
+                FillRule::Winding => 0,
+                FillRule::EvenOdd => 1,
+            },
+            paint_kind,
+            pad: [0; 2],
         }
     }
 
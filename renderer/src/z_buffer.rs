@@ -11,7 +11,7 @@
 //! Software occlusion culling.
 
 use crate::gpu_data::SolidTileBatchPrimitive;
-use crate::paint;
+use crate::paint::PaintMetadata;
 use crate::scene::PathObject;
 use crate::tile_map::DenseTileMap;
 use crate::tiles;
@@ -25,8 +25,8 @@ pub struct ZBuffer {
 }
 
 impl ZBuffer {
-    pub fn new(view_box: RectF) -> ZBuffer {
-        let tile_rect = tiles::round_rect_out_to_tile_bounds(view_box);
+    pub fn new(view_box: RectF, tile_size: Vector2I) -> ZBuffer {
+        let tile_rect = tiles::round_rect_out_to_tile_bounds(view_box, tile_size);
         ZBuffer {
             buffer: DenseTileMap::from_builder(|_| AtomicUsize::new(0), tile_rect),
         }
@@ -56,8 +56,11 @@ impl ZBuffer {
         }
     }
 
-    pub fn build_solid_tiles(&self, paths: &[PathObject], object_range: Range<u32>)
-                             -> Vec<SolidTileBatchPrimitive> {
+    pub(crate) fn build_solid_tiles(&self,
+                                    paths: &[PathObject],
+                                    paint_metadata: &[PaintMetadata],
+                                    object_range: Range<u32>)
+                                    -> Vec<SolidTileBatchPrimitive> {
         let mut solid_tiles = vec![];
         for tile_index in 0..self.buffer.data.len() {
             let depth = self.buffer.data[tile_index].load(AtomicOrdering::Relaxed);
@@ -65,17 +68,19 @@ impl ZBuffer {
                 continue;
             }
 
-            let tile_coords = self.buffer.index_to_coords(tile_index);
+            let tile_coords = self.buffer.index_to_coords(tile_index) + self.buffer.rect.origin();
             let object_index = (depth - 1) as u32;
             if object_index < object_range.start || object_index >= object_range.end {
                 continue;
             }
 
-            let origin_uv = paint::paint_id_to_tex_coords(paths[object_index as usize].paint());
+            let paint_id = paths[object_index as usize].paint();
+            let paint_metadata = &paint_metadata[paint_id.0 as usize];
 
-            solid_tiles.push(SolidTileBatchPrimitive::new(tile_coords + self.buffer.rect.origin(),
+            solid_tiles.push(SolidTileBatchPrimitive::new(tile_coords,
                                                           object_index as u16,
-                                                          origin_uv));
+                                                          paint_metadata.texel,
+                                                          paint_metadata.kind));
         }
 
         solid_tiles
@@ -83,14 +88,15 @@ impl ZBuffer {
 }
 
 impl SolidTileBatchPrimitive {
-    fn new(tile_coords: Vector2I, object_index: u16, origin_uv: Vector2I)
+    fn new(tile_coords: Vector2I, object_index: u16, paint_texel: Vector2I, paint_kind: u8)
            -> SolidTileBatchPrimitive {
         SolidTileBatchPrimitive {
             tile_x: tile_coords.x() as i16,
             tile_y: tile_coords.y() as i16,
             object_index: object_index,
-            origin_u: origin_uv.x() as u16,
-            origin_v: origin_uv.y() as u16,
+            paint_x: paint_texel.x() as u16,
+            paint_y: paint_texel.y() as u16,
+            paint_kind,
             pad: 0,
         }
     }
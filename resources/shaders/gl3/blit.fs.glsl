@@ -0,0 +1,27 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+uniform sampler2D uSource;
+uniform float uOpacity;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+void main(){
+    oFragColor = texture(uSource, vTexCoord)* uOpacity;
+}
+
@@ -0,0 +1,161 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+const int BLEND_MODE_MULTIPLY = 0;
+const int BLEND_MODE_SCREEN = 1;
+const int BLEND_MODE_OVERLAY = 2;
+const int BLEND_MODE_DARKEN = 3;
+const int BLEND_MODE_LIGHTEN = 4;
+const int BLEND_MODE_COLOR_DODGE = 5;
+const int BLEND_MODE_COLOR_BURN = 6;
+const int BLEND_MODE_HARD_LIGHT = 7;
+const int BLEND_MODE_SOFT_LIGHT = 8;
+const int BLEND_MODE_DIFFERENCE = 9;
+const int BLEND_MODE_EXCLUSION = 10;
+const int BLEND_MODE_SRC_IN = 11;
+const int BLEND_MODE_DEST_IN = 12;
+const int BLEND_MODE_SRC_OUT = 13;
+const int BLEND_MODE_DEST_ATOP = 14;
+const int BLEND_MODE_COPY = 15;
+
+vec3 select3(bvec3 cond, vec3 a, vec3 b){
+    return mix(b, a, cond);
+}
+
+vec3 screen(vec3 dest, vec3 src){
+    return dest + src - dest * src;
+}
+
+vec3 hardLight(vec3 dest, vec3 src){
+    return select3(lessThanEqual(src, vec3(0.5)),
+                   dest * 2.0 * src,
+                   screen(dest, 2.0 * src - 1.0));
+}
+
+vec3 colorDodge(vec3 dest, vec3 src){
+    return select3(equal(dest, vec3(0.0)),
+                   vec3(0.0),
+                   select3(greaterThanEqual(src, vec3(1.0)),
+                           vec3(1.0),
+                           min(vec3(1.0), dest /(1.0 - src))));
+}
+
+vec3 colorBurn(vec3 dest, vec3 src){
+    return select3(greaterThanEqual(dest, vec3(1.0)),
+                   vec3(1.0),
+                   select3(lessThanEqual(src, vec3(0.0)),
+                           vec3(0.0),
+                           1.0 - min(vec3(1.0),(1.0 - dest)/ src)));
+}
+
+vec3 softLight(vec3 dest, vec3 src){
+    vec3 darkened = dest -(1.0 - 2.0 * src)* dest *(1.0 - dest);
+    vec3 d = select3(lessThanEqual(dest, vec3(0.25)),
+                     ((16.0 * dest - 12.0)* dest + 4.0)* dest,
+                     sqrt(dest));
+    vec3 lightened = dest +(2.0 * src - 1.0)*(d - dest);
+    return select3(lessThanEqual(src, vec3(0.5)), darkened, lightened);
+}
+
+vec3 blend(int blendMode, vec3 dest, vec3 src){
+    if(blendMode == BLEND_MODE_MULTIPLY)
+        return dest * src;
+    if(blendMode == BLEND_MODE_SCREEN)
+        return screen(dest, src);
+    if(blendMode == BLEND_MODE_OVERLAY)
+        return hardLight(src, dest);
+    if(blendMode == BLEND_MODE_DARKEN)
+        return min(dest, src);
+    if(blendMode == BLEND_MODE_LIGHTEN)
+        return max(dest, src);
+    if(blendMode == BLEND_MODE_COLOR_DODGE)
+        return colorDodge(dest, src);
+    if(blendMode == BLEND_MODE_COLOR_BURN)
+        return colorBurn(dest, src);
+    if(blendMode == BLEND_MODE_HARD_LIGHT)
+        return hardLight(dest, src);
+    if(blendMode == BLEND_MODE_SOFT_LIGHT)
+        return softLight(dest, src);
+    if(blendMode == BLEND_MODE_DIFFERENCE)
+        return abs(dest - src);
+    return dest + src - 2.0 * dest * src;
+}
+
+
+vec4 compositePorterDuff(int blendMode, vec4 src, vec4 dest){
+    if(blendMode == BLEND_MODE_SRC_IN)
+        return src * dest . a;
+    if(blendMode == BLEND_MODE_DEST_IN)
+        return dest * src . a;
+    if(blendMode == BLEND_MODE_SRC_OUT)
+        return src *(1.0 - dest . a);
+    if(blendMode == BLEND_MODE_DEST_ATOP)
+        return src *(1.0 - dest . a)+ dest * src . a;
+    return src;
+}
+
+
+
+vec4 compositeSeparable(int blendMode, vec4 src, vec4 dest){
+
+    vec3 srcRGB = src . a > 0.0 ? src . rgb / src . a : vec3(0.0);
+    vec3 destRGB = dest . a > 0.0 ? dest . rgb / dest . a : vec3(0.0);
+    vec3 blendedRGB = blend(blendMode, destRGB, srcRGB);
+    vec3 rgb = src . rgb *(1.0 - dest . a)+ dest . rgb *(1.0 - src . a)+ blendedRGB * src . a * dest . a;
+    return vec4(rgb, src . a + dest . a *(1.0 - src . a));
+}
+
+
+uniform sampler2D uSource;
+uniform sampler2D uDestTexture;
+uniform vec2 uFramebufferSize;
+uniform float uOpacity;
+uniform int uBlendMode;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+void main(){
+    vec4 srcColor = texture(uSource, vTexCoord)* uOpacity;
+    vec4 destColor = texture(uDestTexture, gl_FragCoord . xy / uFramebufferSize);
+
+    if(uBlendMode >= BLEND_MODE_SRC_IN)
+        oFragColor = compositePorterDuff(uBlendMode, srcColor, destColor);
+    else
+        oFragColor = compositeSeparable(uBlendMode, srcColor, destColor);
+}
+
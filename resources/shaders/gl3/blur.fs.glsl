@@ -0,0 +1,50 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+uniform sampler2D uSource;
+uniform vec2 uSourceSize;
+
+uniform float uSigma;
+
+uniform vec2 uDirection;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+
+
+vec4 sampleSource(float offset){
+    vec2 texCoord = vTexCoord + uDirection * offset / uSourceSize;
+    if(any(lessThan(texCoord, vec2(0.0)))|| any(greaterThan(texCoord, vec2(1.0))))
+        return vec4(0.0);
+    return texture(uSource, texCoord);
+}
+
+void main(){
+    int radius = int(ceil(uSigma * 3.0));
+    vec4 color = sampleSource(0.0);
+    float weightSum = 1.0;
+    for(int offset = 1; offset <= radius; offset++){
+        float weight = exp(- float(offset * offset)/(2.0 * uSigma * uSigma));
+        color +=(sampleSource(float(offset))+ sampleSource(- float(offset)))* weight;
+        weightSum += weight * 2.0;
+    }
+    oFragColor = color / weightSum;
+}
+
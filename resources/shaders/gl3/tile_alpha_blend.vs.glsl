@@ -0,0 +1,94 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+
+
+
+
+
+
+
+
+
+
+
+uniform mat4 uTransform;
+uniform vec2 uTileSize;
+uniform vec2 uStencilTextureSize;
+
+in uvec2 aTessCoord;
+in uvec3 aTileOrigin;
+in int aBackdrop;
+in int aTileIndex;
+in int aFillRule;
+
+out vec2 vTexCoord;
+out float vBackdrop;
+out float vFillRule;
+
+void computeColorVaryings(vec2 position);
+
+vec2 computeTileOffset(uint tileIndex, float stencilTextureWidth){
+    uint tilesPerRow = uint(stencilTextureWidth / uTileSize . x);
+    uvec2 tileOffset = uvec2(tileIndex % tilesPerRow, tileIndex / tilesPerRow);
+    return vec2(tileOffset)* uTileSize;
+}
+
+void computeVaryings(){
+    vec2 origin = vec2(aTileOrigin . xy)+ vec2(aTileOrigin . z & 15u, aTileOrigin . z >> 4u)* 256.0;
+    vec2 position =(origin + vec2(aTessCoord))* uTileSize;
+    vec2 maskTexCoordOrigin = computeTileOffset(uint(aTileIndex), uStencilTextureSize . x);
+    vec2 maskTexCoord = maskTexCoordOrigin + aTessCoord * uTileSize;
+
+    vTexCoord = maskTexCoord / uStencilTextureSize;
+    vBackdrop = float(aBackdrop);
+    vFillRule = float(aFillRule);
+    computeColorVaryings(position);
+    gl_Position = uTransform * vec4(position, 0.0, 1.0);
+}
+
+
+
+
+
+
+
+
+
+
+
+
+
+in uvec2 aPaintTexel;
+in int aPaintKind;
+
+flat out uvec2 vPaintTexel;
+flat out int vPaintKind;
+out vec2 vPosition;
+
+void computeColorVaryings(vec2 position){
+    vPaintTexel = aPaintTexel;
+    vPaintKind = aPaintKind;
+    vPosition = position;
+}
+
+
+void main(){
+    computeVaryings();
+}
+
@@ -0,0 +1,32 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+uniform sampler2D uStencilTexture;
+
+in vec2 vTexCoord;
+in float vBackdrop;
+in float vFillRule;
+in vec4 vColor;
+
+out vec4 oFragColor;
+
+void main(){
+    float winding = texture(uStencilTexture, vTexCoord). r + vBackdrop;
+    float evenOddCoverage = 1.0 - abs(1.0 - 2.0 * fract(abs(winding)* 0.5));
+    float coverage = mix(abs(winding), evenOddCoverage, vFillRule);
+    oFragColor = vec4(vColor . rgb, 1.0)*(vColor . a * coverage);
+}
+
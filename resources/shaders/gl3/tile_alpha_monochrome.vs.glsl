@@ -35,12 +35,13 @@ in uvec2 aTessCoord;
 in uvec3 aTileOrigin;
 in int aBackdrop;
 in int aTileIndex;
+in int aFillRule;
 
 out vec2 vTexCoord;
 out float vBackdrop;
-out vec4 vColor;
+out float vFillRule;
 
-vec4 getColor();
+void computeColorVaryings(vec2 position);
 
 vec2 computeTileOffset(uint tileIndex, float stencilTextureWidth){
     uint tilesPerRow = uint(stencilTextureWidth / uTileSize . x);
@@ -56,7 +57,8 @@ void computeVaryings(){
 
     vTexCoord = maskTexCoord / uStencilTextureSize;
     vBackdrop = float(aBackdrop);
-    vColor = getColor();
+    vFillRule = float(aFillRule);
+    computeColorVaryings(position);
     gl_Position = uTransform * vec4(position, 0.0, 1.0);
 }
 
@@ -74,8 +76,10 @@ void computeVaryings(){
 
 uniform vec4 uColor;
 
-vec4 getColor(){
-    return uColor;
+out vec4 vColor;
+
+void computeColorVaryings(vec2 position){
+    vColor = uColor;
 }
 
 
@@ -35,12 +35,13 @@ in uvec2 aTessCoord;
 in uvec3 aTileOrigin;
 in int aBackdrop;
 in int aTileIndex;
+in int aFillRule;
 
 out vec2 vTexCoord;
 out float vBackdrop;
-out vec4 vColor;
+out float vFillRule;
 
-vec4 getColor();
+void computeColorVaryings(vec2 position);
 
 vec2 computeTileOffset(uint tileIndex, float stencilTextureWidth){
     uint tilesPerRow = uint(stencilTextureWidth / uTileSize . x);
@@ -56,7 +57,8 @@ void computeVaryings(){
 
     vTexCoord = maskTexCoord / uStencilTextureSize;
     vBackdrop = float(aBackdrop);
-    vColor = getColor();
+    vFillRule = float(aFillRule);
+    computeColorVaryings(position);
     gl_Position = uTransform * vec4(position, 0.0, 1.0);
 }
 
@@ -72,13 +74,17 @@ void computeVaryings(){
 
 
 
-uniform sampler2D uPaintTexture;
-uniform vec2 uPaintTextureSize;
+in uvec2 aPaintTexel;
+in int aPaintKind;
 
-in vec2 aColorTexCoord;
+flat out uvec2 vPaintTexel;
+flat out int vPaintKind;
+out vec2 vPosition;
 
-vec4 getColor(){
-    return texture(uPaintTexture, aColorTexCoord);
+void computeColorVaryings(vec2 position){
+    vPaintTexel = aPaintTexel;
+    vPaintKind = aPaintKind;
+    vPosition = position;
 }
 
 
@@ -0,0 +1,185 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+const int PAINT_KIND_COLOR = 0;
+const int PAINT_KIND_GRADIENT = 1;
+const int PAINT_KIND_PATTERN = 2;
+
+const int PAINT_METADATA_HEADER_LENGTH = 4;
+
+uniform sampler2D uPaintTexture;
+uniform sampler2D uPaintMetadataTexture;
+
+flat in uvec2 vPaintTexel;
+flat in int vPaintKind;
+in vec2 vPosition;
+
+vec4 premultiply(vec4 color){
+    return vec4(color . rgb * color . a, color . a);
+}
+
+
+vec4 fetchPaintMetadata(int index){
+    int width = textureSize(uPaintMetadataTexture, 0). x;
+    return texelFetch(uPaintMetadataTexture, ivec2(index % width, index / width), 0);
+}
+
+vec2 transformToPaint(int index, vec2 position){
+    vec4 axes = fetchPaintMetadata(index);
+    vec2 origin = fetchPaintMetadata(index + 1). xy;
+    return axes . xy * position . x + axes . zw * position . y + origin;
+}
+
+
+vec2 computeRadialT(vec2 point, vec4 line, vec2 radii){
+    vec2 centerDelta = line . zw - line . xy;
+    float radiusDelta = radii . y - radii . x;
+    vec2 pointDelta = point - line . xy;
+
+    float a = dot(centerDelta, centerDelta)- radiusDelta * radiusDelta;
+    float b = dot(pointDelta, centerDelta)+ radii . x * radiusDelta;
+    float c = dot(pointDelta, pointDelta)- radii . x * radii . x;
+
+    if(abs(a)< 1e-6){
+        if(b == 0.0)
+            return vec2(0.0);
+        float t = c /(2.0 * b);
+        return vec2(t, radii . x + t * radiusDelta >= 0.0 ? 1.0 : 0.0);
+    }
+
+    float discriminant = b * b - a * c;
+    if(discriminant < 0.0)
+        return vec2(0.0);
+    float sqrtDiscriminant = sqrt(discriminant);
+    float t0 =(b + sqrtDiscriminant)/ a, t1 =(b - sqrtDiscriminant)/ a;
+    float tMax = max(t0, t1), tMin = min(t0, t1);
+    if(radii . x + tMax * radiusDelta >= 0.0)
+        return vec2(tMax, 1.0);
+    if(radii . x + tMin * radiusDelta >= 0.0)
+        return vec2(tMin, 1.0);
+    return vec2(0.0);
+}
+
+vec4 sampleGradient(int index, vec2 position){
+    vec2 point = transformToPaint(index, position);
+    vec2 radii = fetchPaintMetadata(index + 1). zw;
+    vec4 line = fetchPaintMetadata(index + 2);
+    vec4 info = fetchPaintMetadata(index + 3);
+
+    float t;
+    if(info . x == 0.0){
+        vec2 direction = line . zw - line . xy;
+        float squareLength = dot(direction, direction);
+        if(squareLength == 0.0)
+            return vec4(0.0);
+        t = dot(point - line . xy, direction)/ squareLength;
+    } else {
+        vec2 radialT = computeRadialT(point, line, radii);
+        if(radialT . y == 0.0)
+            return vec4(0.0);
+        t = radialT . x;
+    }
+
+
+    int stopCount = int(info . y);
+    if(stopCount == 0)
+        return vec4(0.0);
+    int stopIndex = index + PAINT_METADATA_HEADER_LENGTH;
+    float prevOffset = fetchPaintMetadata(stopIndex). x;
+    vec4 prevColor = fetchPaintMetadata(stopIndex + 1);
+    if(t <= prevOffset)
+        return premultiply(prevColor);
+    for(int i = 1; i < stopCount; i++){
+        float offset = fetchPaintMetadata(stopIndex + i * 2). x;
+        vec4 color = fetchPaintMetadata(stopIndex + i * 2 + 1);
+        if(offset > t)
+            return premultiply(mix(prevColor, color,(t - prevOffset)/(offset - prevOffset)));
+        prevOffset = offset;
+        prevColor = color;
+    }
+    return premultiply(prevColor);
+}
+
+
+vec4 fetchPatternTexel(ivec2 texel, ivec2 size, vec4 image){
+    ivec2 wrapped = texel - size * ivec2(floor(vec2(texel)/ vec2(size)));
+    ivec2 clamped = clamp(texel, ivec2(0), size - 1);
+    texel = ivec2(image . z != 0.0 ? wrapped . x : clamped . x, image . w != 0.0 ? wrapped . y : clamped . y);
+    return premultiply(texelFetch(uPaintTexture, ivec2(image . xy)+ texel, 0));
+}
+
+
+
+vec4 samplePattern(int index, vec2 position){
+    vec2 point = transformToPaint(index, position);
+    ivec2 size = ivec2(fetchPaintMetadata(index + 1). zw);
+    vec4 image = fetchPaintMetadata(index + 2);
+    float opacity = fetchPaintMetadata(index + 3). x;
+    if(size . x <= 0 || size . y <= 0)
+        return vec4(0.0);
+    if((image . z == 0.0 &&(point . x < 0.0 || point . x >= float(size . x)))||
+            (image . w == 0.0 &&(point . y < 0.0 || point . y >= float(size . y))))
+        return vec4(0.0);
+
+
+    point -= 0.5;
+    vec2 fraction = fract(point);
+    ivec2 origin = ivec2(floor(point));
+    vec4 upper = mix(fetchPatternTexel(origin, size, image),
+                     fetchPatternTexel(origin + ivec2(1, 0), size, image),
+                     fraction . x);
+    vec4 lower = mix(fetchPatternTexel(origin + ivec2(0, 1), size, image),
+                     fetchPatternTexel(origin + ivec2(1, 1), size, image),
+                     fraction . x);
+    return mix(upper, lower, fraction . y)* opacity;
+}
+
+
+vec4 samplePaint(){
+    int metadataIndex = int(vPaintTexel . y)* textureSize(uPaintMetadataTexture, 0). x +
+        int(vPaintTexel . x);
+    if(vPaintKind == PAINT_KIND_GRADIENT)
+        return sampleGradient(metadataIndex, vPosition);
+    if(vPaintKind == PAINT_KIND_PATTERN)
+        return samplePattern(metadataIndex, vPosition);
+    return premultiply(texelFetch(uPaintTexture, ivec2(vPaintTexel), 0));
+}
+
+
+out vec4 oFragColor;
+
+void main(){
+    oFragColor = samplePaint();
+}
+
@@ -33,13 +33,11 @@ uniform vec2 uTileSize;
 in uvec2 aTessCoord;
 in ivec2 aTileOrigin;
 
-out vec4 vColor;
-
-vec4 getColor();
+void computeColorVaryings(vec2 position);
 
 void computeVaryings(){
     vec2 position = vec2(aTileOrigin + ivec2(aTessCoord))* uTileSize;
-    vColor = getColor();
+    computeColorVaryings(position);
     gl_Position = uTransform * vec4(position, 0.0, 1.0);
 }
 
@@ -54,13 +52,17 @@ void computeVaryings(){
 
 
 
-uniform sampler2D uPaintTexture;
-uniform vec2 uPaintTextureSize;
+in uvec2 aPaintTexel;
+in int aPaintKind;
 
-in vec2 aColorTexCoord;
+flat out uvec2 vPaintTexel;
+flat out int vPaintKind;
+out vec2 vPosition;
 
-vec4 getColor(){
-    return texture(uPaintTexture, aColorTexCoord);
+void computeColorVaryings(vec2 position){
+    vPaintTexel = aPaintTexel;
+    vPaintKind = aPaintKind;
+    vPosition = position;
 }
 
 
@@ -0,0 +1,216 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#pragma clang diagnostic ignored "-Wmissing-prototypes"
+
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+struct spvDescriptorSetBuffer0
+{
+    texture2d<float> uSource [[id(0)]];
+    sampler uSourceSmplr [[id(1)]];
+    constant float* uOpacity [[id(2)]];
+    texture2d<float> uDestTexture [[id(3)]];
+    sampler uDestTextureSmplr [[id(4)]];
+    constant float2* uFramebufferSize [[id(5)]];
+    constant int* uBlendMode [[id(6)]];
+};
+
+constant int BLEND_MODE_MULTIPLY = 0;
+constant int BLEND_MODE_SCREEN = 1;
+constant int BLEND_MODE_OVERLAY = 2;
+constant int BLEND_MODE_DARKEN = 3;
+constant int BLEND_MODE_LIGHTEN = 4;
+constant int BLEND_MODE_COLOR_DODGE = 5;
+constant int BLEND_MODE_COLOR_BURN = 6;
+constant int BLEND_MODE_HARD_LIGHT = 7;
+constant int BLEND_MODE_SOFT_LIGHT = 8;
+constant int BLEND_MODE_DIFFERENCE = 9;
+constant int BLEND_MODE_SRC_IN = 11;
+constant int BLEND_MODE_DEST_IN = 12;
+constant int BLEND_MODE_SRC_OUT = 13;
+constant int BLEND_MODE_DEST_ATOP = 14;
+
+struct main0_out
+{
+    float4 oFragColor [[color(0)]];
+};
+
+struct main0_in
+{
+    float2 vTexCoord [[user(locn0)]];
+};
+
+float3 select3(thread const bool3& cond, thread const float3& a, thread const float3& b)
+{
+    return select(b, a, cond);
+}
+
+float3 screen(thread const float3& dest, thread const float3& src)
+{
+    return (dest + src) - (dest * src);
+}
+
+float3 hardLight(thread const float3& dest, thread const float3& src)
+{
+    bool3 param = src <= float3(0.5);
+    float3 param_1 = (dest * 2.0) * src;
+    float3 param_2 = dest;
+    float3 param_3 = (src * 2.0) - float3(1.0);
+    float3 param_4 = screen(param_2, param_3);
+    return select3(param, param_1, param_4);
+}
+
+float3 colorDodge(thread const float3& dest, thread const float3& src)
+{
+    bool3 param = dest == float3(0.0);
+    float3 param_1 = float3(0.0);
+    bool3 param_2 = src >= float3(1.0);
+    float3 param_3 = float3(1.0);
+    float3 param_4 = min(float3(1.0), dest / (float3(1.0) - src));
+    float3 param_5 = select3(param_2, param_3, param_4);
+    return select3(param, param_1, param_5);
+}
+
+float3 colorBurn(thread const float3& dest, thread const float3& src)
+{
+    bool3 param = dest >= float3(1.0);
+    float3 param_1 = float3(1.0);
+    bool3 param_2 = src <= float3(0.0);
+    float3 param_3 = float3(0.0);
+    float3 param_4 = float3(1.0) - min(float3(1.0), (float3(1.0) - dest) / src);
+    float3 param_5 = select3(param_2, param_3, param_4);
+    return select3(param, param_1, param_5);
+}
+
+float3 softLight(thread const float3& dest, thread const float3& src)
+{
+    float3 darkened = dest - (((float3(1.0) - (src * 2.0)) * dest) * (float3(1.0) - dest));
+    bool3 param = dest <= float3(0.25);
+    float3 param_1 = ((((dest * 16.0) - float3(12.0)) * dest) + float3(4.0)) * dest;
+    float3 param_2 = sqrt(dest);
+    float3 d = select3(param, param_1, param_2);
+    float3 lightened = dest + (((src * 2.0) - float3(1.0)) * (d - dest));
+    bool3 param_3 = src <= float3(0.5);
+    return select3(param_3, darkened, lightened);
+}
+
+float3 blend(thread const int& blendMode, thread const float3& dest, thread const float3& src)
+{
+    if (blendMode == BLEND_MODE_MULTIPLY)
+    {
+        return dest * src;
+    }
+    if (blendMode == BLEND_MODE_SCREEN)
+    {
+        return screen(dest, src);
+    }
+    if (blendMode == BLEND_MODE_OVERLAY)
+    {
+        return hardLight(src, dest);
+    }
+    if (blendMode == BLEND_MODE_DARKEN)
+    {
+        return min(dest, src);
+    }
+    if (blendMode == BLEND_MODE_LIGHTEN)
+    {
+        return max(dest, src);
+    }
+    if (blendMode == BLEND_MODE_COLOR_DODGE)
+    {
+        return colorDodge(dest, src);
+    }
+    if (blendMode == BLEND_MODE_COLOR_BURN)
+    {
+        return colorBurn(dest, src);
+    }
+    if (blendMode == BLEND_MODE_HARD_LIGHT)
+    {
+        return hardLight(dest, src);
+    }
+    if (blendMode == BLEND_MODE_SOFT_LIGHT)
+    {
+        return softLight(dest, src);
+    }
+    if (blendMode == BLEND_MODE_DIFFERENCE)
+    {
+        return abs(dest - src);
+    }
+    return (dest + src) - ((dest * 2.0) * src);
+}
+
+float4 compositePorterDuff(thread const int& blendMode, thread const float4& src, thread const float4& dest)
+{
+    if (blendMode == BLEND_MODE_SRC_IN)
+    {
+        return src * dest.w;
+    }
+    if (blendMode == BLEND_MODE_DEST_IN)
+    {
+        return dest * src.w;
+    }
+    if (blendMode == BLEND_MODE_SRC_OUT)
+    {
+        return src * (1.0 - dest.w);
+    }
+    if (blendMode == BLEND_MODE_DEST_ATOP)
+    {
+        return (src * (1.0 - dest.w)) + (dest * src.w);
+    }
+    return src;
+}
+
+float4 compositeSeparable(thread const int& blendMode, thread const float4& src, thread const float4& dest)
+{
+    float3 _srcRGB;
+    if (src.w > 0.0)
+    {
+        _srcRGB = src.xyz / float3(src.w);
+    }
+    else
+    {
+        _srcRGB = float3(0.0);
+    }
+    float3 srcRGB = _srcRGB;
+    float3 _destRGB;
+    if (dest.w > 0.0)
+    {
+        _destRGB = dest.xyz / float3(dest.w);
+    }
+    else
+    {
+        _destRGB = float3(0.0);
+    }
+    float3 destRGB = _destRGB;
+    int param = blendMode;
+    float3 param_1 = destRGB;
+    float3 param_2 = srcRGB;
+    float3 blendedRGB = blend(param, param_1, param_2);
+    float3 rgb = ((src.xyz * (1.0 - dest.w)) + (dest.xyz * (1.0 - src.w))) + ((blendedRGB * src.w) * dest.w);
+    return float4(rgb, src.w + (dest.w * (1.0 - src.w)));
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]], float4 gl_FragCoord [[position]])
+{
+    main0_out out = {};
+    float4 srcColor = spvDescriptorSet0.uSource.sample(spvDescriptorSet0.uSourceSmplr, in.vTexCoord) * (*spvDescriptorSet0.uOpacity);
+    float4 destColor = spvDescriptorSet0.uDestTexture.sample(spvDescriptorSet0.uDestTextureSmplr, (gl_FragCoord.xy / (*spvDescriptorSet0.uFramebufferSize)));
+    if ((*spvDescriptorSet0.uBlendMode) >= BLEND_MODE_SRC_IN)
+    {
+        int param = (*spvDescriptorSet0.uBlendMode);
+        float4 param_1 = srcColor;
+        float4 param_2 = destColor;
+        out.oFragColor = compositePorterDuff(param, param_1, param_2);
+    }
+    else
+    {
+        int param_3 = (*spvDescriptorSet0.uBlendMode);
+        float4 param_4 = srcColor;
+        float4 param_5 = destColor;
+        out.oFragColor = compositeSeparable(param_3, param_4, param_5);
+    }
+    return out;
+}
+
@@ -0,0 +1,66 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#pragma clang diagnostic ignored "-Wmissing-prototypes"
+
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+struct spvDescriptorSetBuffer0
+{
+    constant float* uSigma [[id(0)]];
+    constant float2* uDirection [[id(1)]];
+    constant float2* uSourceSize [[id(2)]];
+    texture2d<float> uSource [[id(3)]];
+    sampler uSourceSmplr [[id(4)]];
+};
+
+struct main0_out
+{
+    float4 oFragColor [[color(0)]];
+};
+
+struct main0_in
+{
+    float2 vTexCoord [[user(locn0)]];
+};
+
+float4 sampleSource(thread const float& offset, thread float2& vTexCoord, thread float2 uDirection, thread float2 uSourceSize, thread texture2d<float> uSource, thread const sampler uSourceSmplr)
+{
+    float2 texCoord = vTexCoord + ((uDirection * offset) / uSourceSize);
+    bool _41 = any(texCoord < float2(0.0));
+    bool _50;
+    if (!_41)
+    {
+        _50 = any(texCoord > float2(1.0));
+    }
+    else
+    {
+        _50 = _41;
+    }
+    if (_50)
+    {
+        return float4(0.0);
+    }
+    return uSource.sample(uSourceSmplr, texCoord);
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
+{
+    main0_out out = {};
+    int radius = int(ceil((*spvDescriptorSet0.uSigma) * 3.0));
+    float param = 0.0;
+    float4 color = sampleSource(param, in.vTexCoord, (*spvDescriptorSet0.uDirection), (*spvDescriptorSet0.uSourceSize), spvDescriptorSet0.uSource, spvDescriptorSet0.uSourceSmplr);
+    float weightSum = 1.0;
+    for (int offset = 1; offset <= radius; offset++)
+    {
+        float weight = exp((-float(offset * offset)) / ((2.0 * (*spvDescriptorSet0.uSigma)) * (*spvDescriptorSet0.uSigma)));
+        float param_1 = float(offset);
+        float param_2 = -float(offset);
+        color += ((sampleSource(param_1, in.vTexCoord, (*spvDescriptorSet0.uDirection), (*spvDescriptorSet0.uSourceSize), spvDescriptorSet0.uSource, spvDescriptorSet0.uSourceSmplr) + sampleSource(param_2, in.vTexCoord, (*spvDescriptorSet0.uDirection), (*spvDescriptorSet0.uSourceSize), spvDescriptorSet0.uSource, spvDescriptorSet0.uSourceSmplr)) * weight);
+        weightSum += (weight * 2.0);
+    }
+    out.oFragColor = color / float4(weightSum);
+    return out;
+}
+
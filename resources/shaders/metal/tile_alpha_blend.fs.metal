@@ -0,0 +1,443 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#pragma clang diagnostic ignored "-Wmissing-prototypes"
+
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+struct spvDescriptorSetBuffer0
+{
+    texture2d<float> uStencilTexture [[id(0)]];
+    sampler uStencilTextureSmplr [[id(1)]];
+    texture2d<float> uPaintTexture [[id(2)]];
+    sampler uPaintTextureSmplr [[id(3)]];
+    texture2d<float> uDestTexture [[id(4)]];
+    sampler uDestTextureSmplr [[id(5)]];
+    constant float2* uFramebufferSize [[id(6)]];
+    constant int* uBlendMode [[id(7)]];
+    texture2d<float> uPaintMetadataTexture [[id(8)]];
+    sampler uPaintMetadataTextureSmplr [[id(9)]];
+};
+
+constant int PAINT_KIND_GRADIENT = 1;
+constant int PAINT_KIND_PATTERN = 2;
+constant int PAINT_METADATA_HEADER_LENGTH = 4;
+
+constant int BLEND_MODE_MULTIPLY = 0;
+constant int BLEND_MODE_SCREEN = 1;
+constant int BLEND_MODE_OVERLAY = 2;
+constant int BLEND_MODE_DARKEN = 3;
+constant int BLEND_MODE_LIGHTEN = 4;
+constant int BLEND_MODE_COLOR_DODGE = 5;
+constant int BLEND_MODE_COLOR_BURN = 6;
+constant int BLEND_MODE_HARD_LIGHT = 7;
+constant int BLEND_MODE_SOFT_LIGHT = 8;
+constant int BLEND_MODE_DIFFERENCE = 9;
+constant int BLEND_MODE_SRC_IN = 11;
+constant int BLEND_MODE_DEST_IN = 12;
+constant int BLEND_MODE_SRC_OUT = 13;
+constant int BLEND_MODE_DEST_ATOP = 14;
+
+struct main0_out
+{
+    float4 oFragColor [[color(0)]];
+};
+
+struct main0_in
+{
+    float2 vTexCoord [[user(locn0)]];
+    float vBackdrop [[user(locn1)]];
+    float vFillRule [[user(locn2)]];
+    uint2 vPaintTexel [[user(locn3), flat]];
+    int vPaintKind [[user(locn4), flat]];
+    float2 vPosition [[user(locn5)]];
+};
+
+float4 fetchPaintMetadata(thread const int& index, thread texture2d<float> uPaintMetadataTexture)
+{
+    int width = int(uPaintMetadataTexture.get_width());
+    return uPaintMetadataTexture.read(uint2(int2(index % width, index / width)), 0);
+}
+
+float2 transformToPaint(thread const int& index, thread const float2& position, thread texture2d<float> uPaintMetadataTexture)
+{
+    int param = index;
+    float4 axes = fetchPaintMetadata(param, uPaintMetadataTexture);
+    int param_1 = index + 1;
+    float2 origin = fetchPaintMetadata(param_1, uPaintMetadataTexture).xy;
+    return ((axes.xy * position.x) + (axes.zw * position.y)) + origin;
+}
+
+float4 premultiply(thread const float4& color)
+{
+    return float4(color.xyz * color.w, color.w);
+}
+
+float2 computeRadialT(thread const float2& point, thread const float4& line, thread const float2& radii)
+{
+    float2 centerDelta = line.zw - line.xy;
+    float radiusDelta = radii.y - radii.x;
+    float2 pointDelta = point - line.xy;
+    float a = dot(centerDelta, centerDelta) - (radiusDelta * radiusDelta);
+    float b = dot(pointDelta, centerDelta) + (radii.x * radiusDelta);
+    float c = dot(pointDelta, pointDelta) - (radii.x * radii.x);
+    if (abs(a) < 9.9999999747524270787835121154785e-07)
+    {
+        if (b == 0.0)
+        {
+            return float2(0.0);
+        }
+        float t = c / (2.0 * b);
+        return float2(t, ((radii.x + (t * radiusDelta)) >= 0.0) ? 1.0 : 0.0);
+    }
+    float discriminant = (b * b) - (a * c);
+    if (discriminant < 0.0)
+    {
+        return float2(0.0);
+    }
+    float sqrtDiscriminant = sqrt(discriminant);
+    float t0 = (b + sqrtDiscriminant) / a;
+    float t1 = (b - sqrtDiscriminant) / a;
+    float tMax = fast::max(t0, t1);
+    float tMin = fast::min(t0, t1);
+    if ((radii.x + (tMax * radiusDelta)) >= 0.0)
+    {
+        return float2(tMax, 1.0);
+    }
+    if ((radii.x + (tMin * radiusDelta)) >= 0.0)
+    {
+        return float2(tMin, 1.0);
+    }
+    return float2(0.0);
+}
+
+float4 sampleGradient(thread const int& index, thread const float2& position, thread texture2d<float> uPaintMetadataTexture)
+{
+    int param = index;
+    float2 param_1 = position;
+    float2 point = transformToPaint(param, param_1, uPaintMetadataTexture);
+    int param_2 = index + 1;
+    float2 radii = fetchPaintMetadata(param_2, uPaintMetadataTexture).zw;
+    int param_3 = index + 2;
+    float4 line = fetchPaintMetadata(param_3, uPaintMetadataTexture);
+    int param_4 = index + 3;
+    float4 info = fetchPaintMetadata(param_4, uPaintMetadataTexture);
+    float t;
+    if (info.x == 0.0)
+    {
+        float2 direction = line.zw - line.xy;
+        float squareLength = dot(direction, direction);
+        if (squareLength == 0.0)
+        {
+            return float4(0.0);
+        }
+        t = dot(point - line.xy, direction) / squareLength;
+    }
+    else
+    {
+        float2 param_5 = point;
+        float4 param_6 = line;
+        float2 param_7 = radii;
+        float2 radialT = computeRadialT(param_5, param_6, param_7);
+        if (radialT.y == 0.0)
+        {
+            return float4(0.0);
+        }
+        t = radialT.x;
+    }
+    int stopCount = int(info.y);
+    if (stopCount == 0)
+    {
+        return float4(0.0);
+    }
+    int stopIndex = index + PAINT_METADATA_HEADER_LENGTH;
+    int param_8 = stopIndex;
+    float prevOffset = fetchPaintMetadata(param_8, uPaintMetadataTexture).x;
+    int param_9 = stopIndex + 1;
+    float4 prevColor = fetchPaintMetadata(param_9, uPaintMetadataTexture);
+    if (t <= prevOffset)
+    {
+        float4 param_10 = prevColor;
+        return premultiply(param_10);
+    }
+    for (int i = 1; i < stopCount; i++)
+    {
+        int param_11 = stopIndex + (i * 2);
+        float offset = fetchPaintMetadata(param_11, uPaintMetadataTexture).x;
+        int param_12 = (stopIndex + (i * 2)) + 1;
+        float4 color = fetchPaintMetadata(param_12, uPaintMetadataTexture);
+        if (offset > t)
+        {
+            float4 param_13 = mix(prevColor, color, float4((t - prevOffset) / (offset - prevOffset)));
+            return premultiply(param_13);
+        }
+        prevOffset = offset;
+        prevColor = color;
+    }
+    float4 param_14 = prevColor;
+    return premultiply(param_14);
+}
+
+float4 fetchPatternTexel(thread int2& texel, thread const int2& size, thread const float4& image, thread texture2d<float> uPaintTexture)
+{
+    int2 wrapped = texel - (size * int2(floor(float2(texel) / float2(size))));
+    int2 clamped = clamp(texel, int2(0), size - int2(1));
+    int _wrappedX;
+    if (image.z != 0.0)
+    {
+        _wrappedX = wrapped.x;
+    }
+    else
+    {
+        _wrappedX = clamped.x;
+    }
+    int _wrappedY;
+    if (image.w != 0.0)
+    {
+        _wrappedY = wrapped.y;
+    }
+    else
+    {
+        _wrappedY = clamped.y;
+    }
+    texel = int2(_wrappedX, _wrappedY);
+    float4 param = uPaintTexture.read(uint2(int2(image.xy) + texel), 0);
+    return premultiply(param);
+}
+
+float4 samplePattern(thread const int& index, thread const float2& position, thread texture2d<float> uPaintMetadataTexture, thread texture2d<float> uPaintTexture)
+{
+    int param = index;
+    float2 param_1 = position;
+    float2 point = transformToPaint(param, param_1, uPaintMetadataTexture);
+    int param_2 = index + 1;
+    int2 size = int2(fetchPaintMetadata(param_2, uPaintMetadataTexture).zw);
+    int param_3 = index + 2;
+    float4 image = fetchPaintMetadata(param_3, uPaintMetadataTexture);
+    int param_4 = index + 3;
+    float opacity = fetchPaintMetadata(param_4, uPaintMetadataTexture).x;
+    if ((size.x <= 0) || (size.y <= 0))
+    {
+        return float4(0.0);
+    }
+    if (((image.z == 0.0) && ((point.x < 0.0) || (point.x >= float(size.x)))) || ((image.w == 0.0) && ((point.y < 0.0) || (point.y >= float(size.y)))))
+    {
+        return float4(0.0);
+    }
+    point -= float2(0.5);
+    float2 fraction = fract(point);
+    int2 origin = int2(floor(point));
+    int2 param_5 = origin;
+    int2 param_6 = size;
+    float4 param_7 = image;
+    int2 param_8 = origin + int2(1, 0);
+    int2 param_9 = size;
+    float4 param_10 = image;
+    float4 upper = mix(fetchPatternTexel(param_5, param_6, param_7, uPaintTexture), fetchPatternTexel(param_8, param_9, param_10, uPaintTexture), float4(fraction.x));
+    int2 param_11 = origin + int2(0, 1);
+    int2 param_12 = size;
+    float4 param_13 = image;
+    int2 param_14 = origin + int2(1);
+    int2 param_15 = size;
+    float4 param_16 = image;
+    float4 lower = mix(fetchPatternTexel(param_11, param_12, param_13, uPaintTexture), fetchPatternTexel(param_14, param_15, param_16, uPaintTexture), float4(fraction.x));
+    return mix(upper, lower, float4(fraction.y)) * opacity;
+}
+
+float4 samplePaint(thread texture2d<float> uPaintMetadataTexture, thread const uint2& vPaintTexel, thread const int& vPaintKind, thread const float2& vPosition, thread texture2d<float> uPaintTexture)
+{
+    int metadataIndex = (int(vPaintTexel.y) * int(uPaintMetadataTexture.get_width())) + int(vPaintTexel.x);
+    if (vPaintKind == PAINT_KIND_GRADIENT)
+    {
+        int param = metadataIndex;
+        float2 param_1 = vPosition;
+        return sampleGradient(param, param_1, uPaintMetadataTexture);
+    }
+    if (vPaintKind == PAINT_KIND_PATTERN)
+    {
+        int param_2 = metadataIndex;
+        float2 param_3 = vPosition;
+        return samplePattern(param_2, param_3, uPaintMetadataTexture, uPaintTexture);
+    }
+    float4 param_4 = uPaintTexture.read(uint2(int2(vPaintTexel)), 0);
+    return premultiply(param_4);
+}
+
+float3 select3(thread const bool3& cond, thread const float3& a, thread const float3& b)
+{
+    return select(b, a, cond);
+}
+
+float3 screen(thread const float3& dest, thread const float3& src)
+{
+    return (dest + src) - (dest * src);
+}
+
+float3 hardLight(thread const float3& dest, thread const float3& src)
+{
+    bool3 param = src <= float3(0.5);
+    float3 param_1 = (dest * 2.0) * src;
+    float3 param_2 = dest;
+    float3 param_3 = (src * 2.0) - float3(1.0);
+    float3 param_4 = screen(param_2, param_3);
+    return select3(param, param_1, param_4);
+}
+
+float3 colorDodge(thread const float3& dest, thread const float3& src)
+{
+    bool3 param = dest == float3(0.0);
+    float3 param_1 = float3(0.0);
+    bool3 param_2 = src >= float3(1.0);
+    float3 param_3 = float3(1.0);
+    float3 param_4 = min(float3(1.0), dest / (float3(1.0) - src));
+    float3 param_5 = select3(param_2, param_3, param_4);
+    return select3(param, param_1, param_5);
+}
+
+float3 colorBurn(thread const float3& dest, thread const float3& src)
+{
+    bool3 param = dest >= float3(1.0);
+    float3 param_1 = float3(1.0);
+    bool3 param_2 = src <= float3(0.0);
+    float3 param_3 = float3(0.0);
+    float3 param_4 = float3(1.0) - min(float3(1.0), (float3(1.0) - dest) / src);
+    float3 param_5 = select3(param_2, param_3, param_4);
+    return select3(param, param_1, param_5);
+}
+
+float3 softLight(thread const float3& dest, thread const float3& src)
+{
+    float3 darkened = dest - (((float3(1.0) - (src * 2.0)) * dest) * (float3(1.0) - dest));
+    bool3 param = dest <= float3(0.25);
+    float3 param_1 = ((((dest * 16.0) - float3(12.0)) * dest) + float3(4.0)) * dest;
+    float3 param_2 = sqrt(dest);
+    float3 d = select3(param, param_1, param_2);
+    float3 lightened = dest + (((src * 2.0) - float3(1.0)) * (d - dest));
+    bool3 param_3 = src <= float3(0.5);
+    return select3(param_3, darkened, lightened);
+}
+
+float3 blend(thread const int& blendMode, thread const float3& dest, thread const float3& src)
+{
+    if (blendMode == BLEND_MODE_MULTIPLY)
+    {
+        return dest * src;
+    }
+    if (blendMode == BLEND_MODE_SCREEN)
+    {
+        return screen(dest, src);
+    }
+    if (blendMode == BLEND_MODE_OVERLAY)
+    {
+        return hardLight(src, dest);
+    }
+    if (blendMode == BLEND_MODE_DARKEN)
+    {
+        return min(dest, src);
+    }
+    if (blendMode == BLEND_MODE_LIGHTEN)
+    {
+        return max(dest, src);
+    }
+    if (blendMode == BLEND_MODE_COLOR_DODGE)
+    {
+        return colorDodge(dest, src);
+    }
+    if (blendMode == BLEND_MODE_COLOR_BURN)
+    {
+        return colorBurn(dest, src);
+    }
+    if (blendMode == BLEND_MODE_HARD_LIGHT)
+    {
+        return hardLight(dest, src);
+    }
+    if (blendMode == BLEND_MODE_SOFT_LIGHT)
+    {
+        return softLight(dest, src);
+    }
+    if (blendMode == BLEND_MODE_DIFFERENCE)
+    {
+        return abs(dest - src);
+    }
+    return (dest + src) - ((dest * 2.0) * src);
+}
+
+float4 compositePorterDuff(thread const int& blendMode, thread const float4& src, thread const float4& dest)
+{
+    if (blendMode == BLEND_MODE_SRC_IN)
+    {
+        return src * dest.w;
+    }
+    if (blendMode == BLEND_MODE_DEST_IN)
+    {
+        return dest * src.w;
+    }
+    if (blendMode == BLEND_MODE_SRC_OUT)
+    {
+        return src * (1.0 - dest.w);
+    }
+    if (blendMode == BLEND_MODE_DEST_ATOP)
+    {
+        return (src * (1.0 - dest.w)) + (dest * src.w);
+    }
+    return src;
+}
+
+float4 compositeSeparable(thread const int& blendMode, thread const float4& src, thread const float4& dest)
+{
+    float3 _srcRGB;
+    if (src.w > 0.0)
+    {
+        _srcRGB = src.xyz / float3(src.w);
+    }
+    else
+    {
+        _srcRGB = float3(0.0);
+    }
+    float3 srcRGB = _srcRGB;
+    float3 _destRGB;
+    if (dest.w > 0.0)
+    {
+        _destRGB = dest.xyz / float3(dest.w);
+    }
+    else
+    {
+        _destRGB = float3(0.0);
+    }
+    float3 destRGB = _destRGB;
+    int param = blendMode;
+    float3 param_1 = destRGB;
+    float3 param_2 = srcRGB;
+    float3 blendedRGB = blend(param, param_1, param_2);
+    float3 rgb = ((src.xyz * (1.0 - dest.w)) + (dest.xyz * (1.0 - src.w))) + ((blendedRGB * src.w) * dest.w);
+    return float4(rgb, src.w + (dest.w * (1.0 - src.w)));
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]], float4 gl_FragCoord [[position]])
+{
+    main0_out out = {};
+    float winding = spvDescriptorSet0.uStencilTexture.sample(spvDescriptorSet0.uStencilTextureSmplr, in.vTexCoord).x + in.vBackdrop;
+    float evenOddCoverage = 1.0 - abs(1.0 - (2.0 * fract(abs(winding) * 0.5)));
+    float coverage = mix(abs(winding), evenOddCoverage, in.vFillRule);
+    float4 srcColor = samplePaint(spvDescriptorSet0.uPaintMetadataTexture, in.vPaintTexel, in.vPaintKind, in.vPosition, spvDescriptorSet0.uPaintTexture);
+    float4 destColor = spvDescriptorSet0.uDestTexture.sample(spvDescriptorSet0.uDestTextureSmplr, (gl_FragCoord.xy / (*spvDescriptorSet0.uFramebufferSize)));
+    float4 blendedColor;
+    if ((*spvDescriptorSet0.uBlendMode) >= BLEND_MODE_SRC_IN)
+    {
+        int param = (*spvDescriptorSet0.uBlendMode);
+        float4 param_1 = srcColor;
+        float4 param_2 = destColor;
+        blendedColor = compositePorterDuff(param, param_1, param_2);
+    }
+    else
+    {
+        int param_3 = (*spvDescriptorSet0.uBlendMode);
+        float4 param_4 = srcColor;
+        float4 param_5 = destColor;
+        blendedColor = compositeSeparable(param_3, param_4, param_5);
+    }
+    out.oFragColor = mix(destColor, blendedColor, float4(coverage));
+    return out;
+}
+
@@ -0,0 +1,74 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#pragma clang diagnostic ignored "-Wmissing-prototypes"
+
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+struct spvDescriptorSetBuffer0
+{
+    constant float2* uTileSize [[id(0)]];
+    constant float2* uStencilTextureSize [[id(1)]];
+    constant float4x4* uTransform [[id(2)]];
+};
+
+struct main0_out
+{
+    float2 vTexCoord [[user(locn0)]];
+    float vBackdrop [[user(locn1)]];
+    float vFillRule [[user(locn2)]];
+    uint2 vPaintTexel [[user(locn3), flat]];
+    int vPaintKind [[user(locn4), flat]];
+    float2 vPosition [[user(locn5)]];
+    float4 gl_Position [[position]];
+};
+
+struct main0_in
+{
+    uint2 aTessCoord [[attribute(0)]];
+    uint3 aTileOrigin [[attribute(1)]];
+    int aBackdrop [[attribute(2)]];
+    int aTileIndex [[attribute(3)]];
+    int aFillRule [[attribute(4)]];
+    uint2 aPaintTexel [[attribute(5)]];
+    int aPaintKind [[attribute(6)]];
+};
+
+float2 computeTileOffset(thread const uint& tileIndex, thread const float& stencilTextureWidth, thread float2 uTileSize)
+{
+    uint tilesPerRow = uint(stencilTextureWidth / uTileSize.x);
+    uint2 tileOffset = uint2(tileIndex % tilesPerRow, tileIndex / tilesPerRow);
+    return float2(tileOffset) * uTileSize;
+}
+
+void computeColorVaryings(thread const float2& position, thread uint2& vPaintTexel, thread uint2& aPaintTexel, thread int& vPaintKind, thread int& aPaintKind, thread float2& vPosition)
+{
+    vPaintTexel = aPaintTexel;
+    vPaintKind = aPaintKind;
+    vPosition = position;
+}
+
+void computeVaryings(thread float2 uTileSize, thread uint3& aTileOrigin, thread uint2& aTessCoord, thread int& aTileIndex, thread float2 uStencilTextureSize, thread float2& vTexCoord, thread float& vBackdrop, thread int& aBackdrop, thread float& vFillRule, thread int& aFillRule, thread uint2& vPaintTexel, thread uint2& aPaintTexel, thread int& vPaintKind, thread int& aPaintKind, thread float2& vPosition, thread float4& gl_Position, thread float4x4 uTransform)
+{
+    float2 origin = float2(aTileOrigin.xy) + (float2(float(aTileOrigin.z & 15u), float(aTileOrigin.z >> 4u)) * 256.0);
+    float2 position = (origin + float2(aTessCoord)) * uTileSize;
+    uint param = uint(aTileIndex);
+    float param_1 = uStencilTextureSize.x;
+    float2 maskTexCoordOrigin = computeTileOffset(param, param_1, uTileSize);
+    float2 maskTexCoord = maskTexCoordOrigin + (float2(aTessCoord) * uTileSize);
+    vTexCoord = maskTexCoord / uStencilTextureSize;
+    vBackdrop = float(aBackdrop);
+    vFillRule = float(aFillRule);
+    float2 param_2 = position;
+    computeColorVaryings(param_2, vPaintTexel, aPaintTexel, vPaintKind, aPaintKind, vPosition);
+    gl_Position = uTransform * float4(position, 0.0, 1.0);
+}
+
+vertex main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
+{
+    main0_out out = {};
+    computeVaryings((*spvDescriptorSet0.uTileSize), in.aTileOrigin, in.aTessCoord, in.aTileIndex, (*spvDescriptorSet0.uStencilTextureSize), out.vTexCoord, out.vBackdrop, in.aBackdrop, out.vFillRule, in.aFillRule, out.vPaintTexel, in.aPaintTexel, out.vPaintKind, in.aPaintKind, out.vPosition, out.gl_Position, (*spvDescriptorSet0.uTransform));
+    return out;
+}
+
@@ -19,14 +19,17 @@ struct main0_in
 {
     float2 vTexCoord [[user(locn0)]];
     float vBackdrop [[user(locn1)]];
-    float4 vColor [[user(locn2)]];
+    float vFillRule [[user(locn2)]];
+    float4 vColor [[user(locn3)]];
 };
 
 fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
 {
     main0_out out = {};
-    float coverage = abs(spvDescriptorSet0.uStencilTexture.sample(spvDescriptorSet0.uStencilTextureSmplr, in.vTexCoord).x + in.vBackdrop);
-    out.oFragColor = float4(in.vColor.xyz, in.vColor.w * coverage);
+    float winding = spvDescriptorSet0.uStencilTexture.sample(spvDescriptorSet0.uStencilTextureSmplr, in.vTexCoord).x + in.vBackdrop;
+    float evenOddCoverage = 1.0 - abs(1.0 - (2.0 * fract(abs(winding) * 0.5)));
+    float coverage = mix(abs(winding), evenOddCoverage, in.vFillRule);
+    out.oFragColor = float4(in.vColor.xyz, 1.0) * (in.vColor.w * coverage);
     return out;
 }
 
@@ -18,7 +18,8 @@ struct main0_out
 {
     float2 vTexCoord [[user(locn0)]];
     float vBackdrop [[user(locn1)]];
-    float4 vColor [[user(locn2)]];
+    float vFillRule [[user(locn2)]];
+    float4 vColor [[user(locn3)]];
     float4 gl_Position [[position]];
 };
 
@@ -28,6 +29,7 @@ struct main0_in
     uint3 aTileOrigin [[attribute(1)]];
     int aBackdrop [[attribute(2)]];
     int aTileIndex [[attribute(3)]];
+    int aFillRule [[attribute(4)]];
 };
 
 float2 computeTileOffset(thread const uint& tileIndex, thread const float& stencilTextureWidth, thread float2 uTileSize)
@@ -37,12 +39,12 @@ float2 computeTileOffset(thread const uint& tileIndex, thread const float& stenc
     return float2(tileOffset) * uTileSize;
 }
 
-float4 getColor(thread float4 uColor)
+void computeColorVaryings(thread const float2& position, thread float4& vColor, thread float4 uColor)
 {
-    return uColor;
+    vColor = uColor;
 }
 
-void computeVaryings(thread float2 uTileSize, thread uint3& aTileOrigin, thread uint2& aTessCoord, thread int& aTileIndex, thread float2 uStencilTextureSize, thread float2& vTexCoord, thread float& vBackdrop, thread int& aBackdrop, thread float4& vColor, thread float4& gl_Position, thread float4x4 uTransform, thread float4 uColor)
+void computeVaryings(thread float2 uTileSize, thread uint3& aTileOrigin, thread uint2& aTessCoord, thread int& aTileIndex, thread float2 uStencilTextureSize, thread float2& vTexCoord, thread float& vBackdrop, thread int& aBackdrop, thread float& vFillRule, thread int& aFillRule, thread float4& vColor, thread float4 uColor, thread float4& gl_Position, thread float4x4 uTransform)
 {
     float2 origin = float2(aTileOrigin.xy) + (float2(float(aTileOrigin.z & 15u), float(aTileOrigin.z >> 4u)) * 256.0);
     float2 position = (origin + float2(aTessCoord)) * uTileSize;
@@ -52,14 +54,16 @@ void computeVaryings(thread float2 uTileSize, thread uint3& aTileOrigin, thread
     float2 maskTexCoord = maskTexCoordOrigin + (float2(aTessCoord) * uTileSize);
     vTexCoord = maskTexCoord / uStencilTextureSize;
     vBackdrop = float(aBackdrop);
-    vColor = getColor(uColor);
+    vFillRule = float(aFillRule);
+    float2 param_2 = position;
+    computeColorVaryings(param_2, vColor, uColor);
     gl_Position = uTransform * float4(position, 0.0, 1.0);
 }
 
 vertex main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
 {
     main0_out out = {};
-    computeVaryings((*spvDescriptorSet0.uTileSize), in.aTileOrigin, in.aTessCoord, in.aTileIndex, (*spvDescriptorSet0.uStencilTextureSize), out.vTexCoord, out.vBackdrop, in.aBackdrop, out.vColor, out.gl_Position, (*spvDescriptorSet0.uTransform), (*spvDescriptorSet0.uColor));
+    computeVaryings((*spvDescriptorSet0.uTileSize), in.aTileOrigin, in.aTessCoord, in.aTileIndex, (*spvDescriptorSet0.uStencilTextureSize), out.vTexCoord, out.vBackdrop, in.aBackdrop, out.vFillRule, in.aFillRule, out.vColor, (*spvDescriptorSet0.uColor), out.gl_Position, (*spvDescriptorSet0.uTransform));
     return out;
 }
 
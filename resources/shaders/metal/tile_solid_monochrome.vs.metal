@@ -25,22 +25,23 @@ struct main0_in
     int2 aTileOrigin [[attribute(1)]];
 };
 
-float4 getColor(thread float4 uColor)
+void computeColorVaryings(thread const float2& position, thread float4& vColor, thread float4 uColor)
 {
-    return uColor;
+    vColor = uColor;
 }
 
-void computeVaryings(thread int2& aTileOrigin, thread uint2& aTessCoord, thread float2 uTileSize, thread float4& vColor, thread float4& gl_Position, thread float4x4 uTransform, thread float4 uColor)
+void computeVaryings(thread int2& aTileOrigin, thread uint2& aTessCoord, thread float2 uTileSize, thread float4& vColor, thread float4 uColor, thread float4& gl_Position, thread float4x4 uTransform)
 {
     float2 position = float2(aTileOrigin + int2(aTessCoord)) * uTileSize;
-    vColor = getColor(uColor);
+    float2 param = position;
+    computeColorVaryings(param, vColor, uColor);
     gl_Position = uTransform * float4(position, 0.0, 1.0);
 }
 
 vertex main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
 {
     main0_out out = {};
-    computeVaryings(in.aTileOrigin, in.aTessCoord, (*spvDescriptorSet0.uTileSize), out.vColor, out.gl_Position, (*spvDescriptorSet0.uTransform), (*spvDescriptorSet0.uColor));
+    computeVaryings(in.aTileOrigin, in.aTessCoord, (*spvDescriptorSet0.uTileSize), out.vColor, (*spvDescriptorSet0.uColor), out.gl_Position, (*spvDescriptorSet0.uTransform));
     return out;
 }
 
@@ -10,13 +10,13 @@ struct spvDescriptorSetBuffer0
 {
     constant float2* uTileSize [[id(0)]];
     constant float4x4* uTransform [[id(1)]];
-    texture2d<float> uPaintTexture [[id(2)]];
-    sampler uPaintTextureSmplr [[id(3)]];
 };
 
 struct main0_out
 {
-    float4 vColor [[user(locn0)]];
+    uint2 vPaintTexel [[user(locn0), flat]];
+    int vPaintKind [[user(locn1), flat]];
+    float2 vPosition [[user(locn2)]];
     float4 gl_Position [[position]];
 };
 
@@ -24,25 +24,29 @@ struct main0_in
 {
     uint2 aTessCoord [[attribute(0)]];
     int2 aTileOrigin [[attribute(1)]];
-    float2 aColorTexCoord [[attribute(2)]];
+    uint2 aPaintTexel [[attribute(2)]];
+    int aPaintKind [[attribute(3)]];
 };
 
-float4 getColor(thread texture2d<float> uPaintTexture, thread const sampler uPaintTextureSmplr, thread float2& aColorTexCoord)
+void computeColorVaryings(thread const float2& position, thread uint2& vPaintTexel, thread uint2& aPaintTexel, thread int& vPaintKind, thread int& aPaintKind, thread float2& vPosition)
 {
-    return uPaintTexture.sample(uPaintTextureSmplr, aColorTexCoord, level(0.0));
+    vPaintTexel = aPaintTexel;
+    vPaintKind = aPaintKind;
+    vPosition = position;
 }
 
-void computeVaryings(thread int2& aTileOrigin, thread uint2& aTessCoord, thread float2 uTileSize, thread float4& vColor, thread float4& gl_Position, thread float4x4 uTransform, thread texture2d<float> uPaintTexture, thread const sampler uPaintTextureSmplr, thread float2& aColorTexCoord)
+void computeVaryings(thread int2& aTileOrigin, thread uint2& aTessCoord, thread float2 uTileSize, thread uint2& vPaintTexel, thread uint2& aPaintTexel, thread int& vPaintKind, thread int& aPaintKind, thread float2& vPosition, thread float4& gl_Position, thread float4x4 uTransform)
 {
     float2 position = float2(aTileOrigin + int2(aTessCoord)) * uTileSize;
-    vColor = getColor(uPaintTexture, uPaintTextureSmplr, aColorTexCoord);
+    float2 param = position;
+    computeColorVaryings(param, vPaintTexel, aPaintTexel, vPaintKind, aPaintKind, vPosition);
     gl_Position = uTransform * float4(position, 0.0, 1.0);
 }
 
 vertex main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
 {
     main0_out out = {};
-    computeVaryings(in.aTileOrigin, in.aTessCoord, (*spvDescriptorSet0.uTileSize), out.vColor, out.gl_Position, (*spvDescriptorSet0.uTransform), spvDescriptorSet0.uPaintTexture, spvDescriptorSet0.uPaintTextureSmplr, in.aColorTexCoord);
+    computeVaryings(in.aTileOrigin, in.aTessCoord, (*spvDescriptorSet0.uTileSize), out.vPaintTexel, in.aPaintTexel, out.vPaintKind, in.aPaintKind, out.vPosition, out.gl_Position, (*spvDescriptorSet0.uTransform));
     return out;
 }
 
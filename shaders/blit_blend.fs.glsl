@@ -0,0 +1,40 @@
+#version 330
+
+// pathfinder/shaders/blit_blend.fs.glsl
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Composites a layer using a blend mode that the blending hardware can't express. As with
+// `tile_alpha_blend.fs.glsl`, `uDestTexture` must hold a copy of the destination framebuffer.
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+#include "blend.inc.glsl"
+
+uniform sampler2D uSource;
+uniform sampler2D uDestTexture;
+uniform vec2 uFramebufferSize;
+uniform float uOpacity;
+uniform int uBlendMode;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+void main() {
+    vec4 srcColor = texture(uSource, vTexCoord) * uOpacity;
+    vec4 destColor = texture(uDestTexture, gl_FragCoord.xy / uFramebufferSize);
+
+    if (uBlendMode >= BLEND_MODE_SRC_IN)
+        oFragColor = compositePorterDuff(uBlendMode, srcColor, destColor);
+    else
+        oFragColor = compositeSeparable(uBlendMode, srcColor, destColor);
+}
@@ -0,0 +1,47 @@
+#version 330
+
+// pathfinder/shaders/blur.fs.glsl
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// One pass of a separable Gaussian blur.
+
+precision highp float;
+
+uniform sampler2D uSource;
+uniform vec2 uSourceSize;
+// The standard deviation of the Gaussian, in pixels.
+uniform float uSigma;
+// The axis to blur along: (1, 0) or (0, 1).
+uniform vec2 uDirection;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+// Fetches the pixel `offset` pixels away along the blur axis. Pixels outside the source are
+// transparent.
+vec4 sampleSource(float offset) {
+    vec2 texCoord = vTexCoord + uDirection * offset / uSourceSize;
+    if (any(lessThan(texCoord, vec2(0.0))) || any(greaterThan(texCoord, vec2(1.0))))
+        return vec4(0.0);
+    return texture(uSource, texCoord);
+}
+
+void main() {
+    int radius = int(ceil(uSigma * 3.0));
+    vec4 color = sampleSource(0.0);
+    float weightSum = 1.0;
+    for (int offset = 1; offset <= radius; offset++) {
+        float weight = exp(-float(offset * offset) / (2.0 * uSigma * uSigma));
+        color += (sampleSource(float(offset)) + sampleSource(-float(offset))) * weight;
+        weightSum += weight * 2.0;
+    }
+    oFragColor = color / weightSum;
+}
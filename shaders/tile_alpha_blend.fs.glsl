@@ -0,0 +1,50 @@
+#version 330
+
+// pathfinder/shaders/tile_alpha_blend.fs.glsl
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Composites an alpha tile using a separable blend mode or a Porter-Duff operator that affects the
+// destination outside the source. Blending is done here rather than by the fixed-function
+// hardware, so `uDestTexture` must hold a copy of the destination framebuffer. The result is mixed
+// with the destination by coverage, so that the operator only applies where the path is.
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+#include "tile_paint.inc.glsl"
+#include "blend.inc.glsl"
+
+uniform sampler2D uStencilTexture;
+uniform sampler2D uDestTexture;
+uniform vec2 uFramebufferSize;
+uniform int uBlendMode;
+
+in vec2 vTexCoord;
+in float vBackdrop;
+in float vFillRule;
+
+out vec4 oFragColor;
+
+void main() {
+    float winding = texture(uStencilTexture, vTexCoord).r + vBackdrop;
+    float evenOddCoverage = 1.0 - abs(1.0 - 2.0 * fract(abs(winding) * 0.5));
+    float coverage = mix(abs(winding), evenOddCoverage, vFillRule);
+
+    vec4 srcColor = samplePaint();
+    vec4 destColor = texture(uDestTexture, gl_FragCoord.xy / uFramebufferSize);
+
+    vec4 blendedColor;
+    if (uBlendMode >= BLEND_MODE_SRC_IN)
+        blendedColor = compositePorterDuff(uBlendMode, srcColor, destColor);
+    else
+        blendedColor = compositeSeparable(uBlendMode, srcColor, destColor);
+    oFragColor = mix(destColor, blendedColor, coverage);
+}
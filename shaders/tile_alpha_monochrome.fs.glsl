@@ -1,6 +1,6 @@
 #version 330
 
-// pathfinder/shaders/tile_alpha.fs.glsl
+// pathfinder/shaders/tile_alpha_monochrome.fs.glsl
 //
 // Copyright © 2018 The Pathfinder Project Developers.
 //
@@ -16,11 +16,14 @@ uniform sampler2D uStencilTexture;
 
 in vec2 vTexCoord;
 in float vBackdrop;
+in float vFillRule;
 in vec4 vColor;
 
 out vec4 oFragColor;
 
 void main() {
-    float coverage = abs(texture(uStencilTexture, vTexCoord).r + vBackdrop);
-    oFragColor = vec4(vColor.rgb, vColor.a * coverage);
+    float winding = texture(uStencilTexture, vTexCoord).r + vBackdrop;
+    float evenOddCoverage = 1.0 - abs(1.0 - 2.0 * fract(abs(winding) * 0.5));
+    float coverage = mix(abs(winding), evenOddCoverage, vFillRule);
+    oFragColor = vec4(vColor.rgb, 1.0) * (vColor.a * coverage);
 }
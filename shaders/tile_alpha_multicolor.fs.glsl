@@ -0,0 +1,32 @@
+#version 330
+
+// pathfinder/shaders/tile_alpha_multicolor.fs.glsl
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+#include "tile_paint.inc.glsl"
+
+uniform sampler2D uStencilTexture;
+
+in vec2 vTexCoord;
+in float vBackdrop;
+in float vFillRule;
+
+out vec4 oFragColor;
+
+void main() {
+    float winding = texture(uStencilTexture, vTexCoord).r + vBackdrop;
+    float evenOddCoverage = 1.0 - abs(1.0 - 2.0 * fract(abs(winding) * 0.5));
+    float coverage = mix(abs(winding), evenOddCoverage, vFillRule);
+    oFragColor = samplePaint() * coverage;
+}
@@ -10,6 +10,8 @@
 
 uniform vec4 uColor;
 
-vec4 getColor() {
-    return uColor;
+out vec4 vColor;
+
+void computeColorVaryings(vec2 position) {
+    vColor = uColor;
 }
@@ -8,11 +8,15 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-uniform sampler2D uPaintTexture;
-uniform vec2 uPaintTextureSize;
+in uvec2 aPaintTexel;
+in int aPaintKind;
 
-in vec2 aColorTexCoord;
+flat out uvec2 vPaintTexel;
+flat out int vPaintKind;
+out vec2 vPosition;
 
-vec4 getColor() {
-    return texture(uPaintTexture, aColorTexCoord);
+void computeColorVaryings(vec2 position) {
+    vPaintTexel = aPaintTexel;
+    vPaintKind = aPaintKind;
+    vPosition = position;
 }
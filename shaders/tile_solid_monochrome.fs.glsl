@@ -1,6 +1,6 @@
 #version 330
 
-// pathfinder/shaders/tile_solid.fs.glsl
+// pathfinder/shaders/tile_solid_monochrome.fs.glsl
 //
 // Copyright © 2019 The Pathfinder Project Developers.
 //
@@ -14,12 +14,10 @@ uniform vec2 uTileSize;
 in uvec2 aTessCoord;
 in ivec2 aTileOrigin;
 
-out vec4 vColor;
-
-vec4 getColor();
+void computeColorVaryings(vec2 position);
 
 void computeVaryings() {
     vec2 position = vec2(aTileOrigin + ivec2(aTessCoord)) * uTileSize;
-    vColor = getColor();
+    computeColorVaryings(position);
     gl_Position = uTransform * vec4(position, 0.0, 1.0);
 }
@@ -14,6 +14,7 @@
 extern crate bitflags;
 
 use pathfinder_content::color::ColorU;
+use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::Outline;
 use pathfinder_content::segment::{Segment, SegmentFlags};
 use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
@@ -23,10 +24,11 @@ use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::Vector2F;
 use pathfinder_renderer::paint::Paint;
-use pathfinder_renderer::scene::{PathObject, Scene};
+use pathfinder_renderer::scene::{PathObject, Scene, SceneGroup};
 use std::fmt::{Display, Formatter, Result as FormatResult};
 use std::mem;
-use usvg::{Color as SvgColor, LineCap as UsvgLineCap, LineJoin as UsvgLineJoin, Node, NodeExt};
+use usvg::{Color as SvgColor, FillRule as UsvgFillRule, LineCap as UsvgLineCap};
+use usvg::{LineJoin as UsvgLineJoin, Node, NodeExt};
 use usvg::{NodeKind, Opacity, Paint as UsvgPaint, PathSegment as UsvgPathSegment};
 use usvg::{Rect as UsvgRect, Transform as UsvgTransform, Tree, Visibility};
 
@@ -106,9 +108,22 @@ impl BuiltSVG {
                         .insert(BuildResultFlags::UNSUPPORTED_MASK_ATTR);
                 }
 
+                // Transforms are already applied to the outlines, so the group only needs to
+                // carry the opacity.
+                let opacity = group.opacity.value() as f32;
+                if opacity < 1.0 {
+                    let mut scene_group = SceneGroup::new();
+                    scene_group.set_opacity(opacity);
+                    self.scene.push_group(scene_group);
+                }
+
                 for kid in node.children() {
                     self.process_node(&kid, &transform)
                 }
+
+                if opacity < 1.0 {
+                    self.scene.pop_group();
+                }
             }
             NodeKind::Path(ref path) if path.visibility == Visibility::Visible => {
                 if let Some(ref fill) = path.fill {
@@ -123,7 +138,9 @@ impl BuiltSVG {
                     let outline = Outline::from_segments(path);
 
                     let name = format!("Fill({})", node.id());
-                    self.scene.push_path(PathObject::new(outline, style, name));
+                    let mut path = PathObject::new(outline, style, name);
+                    path.set_fill_rule(FillRule::from_usvg_fill_rule(fill.rule));
+                    self.scene.push_path(path);
                 }
 
                 if let Some(ref stroke) = path.stroke {
@@ -246,16 +263,14 @@ impl PaintExt for Paint {
     #[inline]
     fn from_svg_paint(svg_paint: &UsvgPaint, opacity: Opacity, result_flags: &mut BuildResultFlags)
                       -> Paint {
-        Paint {
-            color: match *svg_paint {
-                UsvgPaint::Color(color) => ColorU::from_svg_color(color, opacity),
-                UsvgPaint::Link(_) => {
-                    // TODO(pcwalton)
-                    result_flags.insert(BuildResultFlags::UNSUPPORTED_LINK_PAINT);
-                    ColorU::black()
-                }
-            },
-        }
+        Paint::Color(match *svg_paint {
+            UsvgPaint::Color(color) => ColorU::from_svg_color(color, opacity),
+            UsvgPaint::Link(_) => {
+                // TODO(pcwalton)
+                result_flags.insert(BuildResultFlags::UNSUPPORTED_LINK_PAINT);
+                ColorU::black()
+            }
+        })
     }
 }
 
@@ -378,6 +393,20 @@ impl ColorUExt for ColorU {
     }
 }
 
+trait FillRuleExt {
+    fn from_usvg_fill_rule(usvg_fill_rule: UsvgFillRule) -> Self;
+}
+
+impl FillRuleExt for FillRule {
+    #[inline]
+    fn from_usvg_fill_rule(usvg_fill_rule: UsvgFillRule) -> FillRule {
+        match usvg_fill_rule {
+            UsvgFillRule::NonZero => FillRule::Winding,
+            UsvgFillRule::EvenOdd => FillRule::EvenOdd,
+        }
+    }
+}
+
 trait LineCapExt {
     fn from_usvg_line_cap(usvg_line_cap: UsvgLineCap) -> Self;
 }
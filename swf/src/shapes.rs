@@ -160,8 +160,8 @@ impl StyleLayer {
 
     pub(crate) fn fill(&self) -> Paint {
         match &self.fill {
-            PaintOrLine::Paint(paint) => *paint,
-            PaintOrLine::Line(line) => line.color,
+            PaintOrLine::Paint(paint) => paint.clone(),
+            PaintOrLine::Line(line) => line.color.clone(),
         }
     }
 
@@ -263,14 +263,12 @@ fn get_new_styles<'a>(
                     }
                 }
             ) =>  {
-                Some(PaintOrLine::Paint(Paint {
-                    color: ColorU {
-                        r: *r,
-                        g: *g,
-                        b: *b,
-                        a: *a
-                    }
-                }))
+                Some(PaintOrLine::Paint(Paint::Color(ColorU {
+                    r: *r,
+                    g: *g,
+                    b: *b,
+                    a: *a
+                })))
             },
             _ => unimplemented!("Unimplemented fill style")
         }
@@ -304,7 +302,7 @@ fn get_new_styles<'a>(
                 // assert_eq!(start_cap, end_cap);
                 Some(PaintOrLine::Line(SwfLineStyle {
                     width: Twips(*width as i32),
-                    color: Paint { color: ColorU { r: *r, g: *g, b: *b, a: *a } },
+                    color: Paint::Color(ColorU { r: *r, g: *g, b: *b, a: *a }),
                     join: match join {
                         JoinStyle::Bevel => LineJoin::Bevel,
                         JoinStyle::Round => LineJoin::Round,